@@ -0,0 +1,80 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use feos_core::parameter::{Identifier, Parameter, PureRecord};
+use feos_core::{PhaseDiagram, PhaseEquilibrium, SolverOptions};
+use feos_uvtheory::{Perturbation, UVParameters, UVRecord, UVTheory, UVTheoryOptions};
+use ndarray::Array2;
+use quantity::si::*;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Lennard-Jones parameters of argon and krypton.
+fn records() -> Vec<PureRecord<UVRecord, feos_core::joback::JobackRecord>> {
+    [
+        ("argon", 39.948, 3.405, 119.8),
+        ("krypton", 83.798, 3.66, 166.7),
+    ]
+    .iter()
+    .map(|&(name, mw, sigma, epsilon_k)| {
+        let record = UVRecord::new(12.0, 6.0, sigma, epsilon_k);
+        let identifier = Identifier::new(name, None, None, None, None, None);
+        PureRecord::new(identifier, mw, record, None)
+    })
+    .collect()
+}
+
+fn argon(perturbation: Perturbation) -> Rc<UVTheory> {
+    let parameters = UVParameters::new_pure(records().remove(0));
+    let options = UVTheoryOptions {
+        perturbation,
+        ..Default::default()
+    };
+    Rc::new(UVTheory::with_options(Arc::new(parameters), options).unwrap())
+}
+
+fn argon_krypton() -> Rc<UVTheory> {
+    let parameters = UVParameters::from_records(records(), Array2::default((2, 2)));
+    Rc::new(UVTheory::new(Arc::new(parameters)))
+}
+
+fn pure(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pure");
+    for (name, perturbation) in [
+        ("wca", Perturbation::WeeksChandlerAndersen),
+        ("bh", Perturbation::BarkerHenderson),
+    ] {
+        let eos = argon(perturbation);
+        group.bench_function(format!("vle_{}", name), |b| {
+            b.iter(|| {
+                PhaseEquilibrium::pure(&eos, 120.0 * KELVIN, None, SolverOptions::default())
+                    .unwrap()
+            })
+        });
+        group.bench_function(format!("phase_diagram_{}", name), |b| {
+            b.iter(|| {
+                PhaseDiagram::pure(&eos, 90.0 * KELVIN, 50, None, SolverOptions::default()).unwrap()
+            })
+        });
+        group.bench_function(format!("par_phase_diagram_{}", name), |b| {
+            b.iter(|| {
+                eos.par_phase_diagram_pure(90.0 * KELVIN, 50, None, SolverOptions::default())
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn binary(c: &mut Criterion) {
+    let mut group = c.benchmark_group("binary");
+    let eos = argon_krypton();
+    group.bench_function("phase_diagram_wca", |b| {
+        b.iter(|| {
+            PhaseDiagram::binary_vle(&eos, 150.0 * KELVIN, Some(21), None, Default::default())
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, pure, binary);
+criterion_main!(benches);
@@ -0,0 +1,128 @@
+use feos_core::*;
+use feos_dft::fundamental_measure_theory::FMTVersion;
+use feos_dft::interface::*;
+use feos_dft::python::*;
+use feos_dft::*;
+use feos_uvtheory::python::PyUVParameters;
+use feos_uvtheory::{
+    Perturbation, UVTheoryFunctional, UVTheoryOptions, ValidityDomain, VirialOrder,
+};
+use numpy::convert::ToPyArray;
+use numpy::{PyArray1, PyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use quantity::python::*;
+use quantity::si::*;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Initialize the uv-theory Helmholtz energy functional.
+///
+/// Parameters
+/// ----------
+/// parameters : UVParameters
+///     The parameters of the uv-theory functional to use.
+/// fmt_version : FMTVersion, optional
+///     The specific variant of fundamental measure theory.
+///     Defaults to FMTVersion.WhiteBear.
+/// max_eta : float, optional
+///     Maximum packing fraction. Defaults to 0.5.
+/// perturbation : Perturbation, optional
+///     Division type of the Mie potential. Defaults to WCA division.
+/// virial_order : VirialOrder, optional
+///     Highest virial coefficient reproduced by the model. Defaults to
+///     VirialOrder.Second, VirialOrder.Third selects uv-B3-theory (WCA only).
+/// max_iter_cross_assoc : unsigned integer, optional
+///     Maximum number of iterations for cross association. Defaults to 50.
+/// tol_cross_assoc : float
+///     Tolerance for convergence of cross association. Defaults to 1e-10.
+/// cutoff_radius : float, optional
+///     Cutoff radius of the potential in units of sigma. Has to be larger
+///     than the position of the potential minimum.
+///     Defaults to the full-range potential.
+/// shift_potential : bool, optional
+///     Shift the truncated potential to zero at the cutoff radius.
+///     Defaults to False.
+/// strict : bool, optional
+///     Refuse to evaluate outside of the validity domain of the model:
+///     the perturbation contributions are NaN at weighted densities
+///     outside of the domain. Defaults to False.
+///
+/// Returns
+/// -------
+/// UVTheoryFunctional
+///
+/// Raises
+/// ------
+/// ValueError
+///     If the options are incompatible or, in strict mode, the parameters
+///     are outside of the validity domain.
+#[pyclass(name = "UVTheoryFunctional", unsendable)]
+#[pyo3(
+    text_signature = "(parameters, fmt_version, max_eta, perturbation, virial_order, max_iter_cross_assoc, tol_cross_assoc, cutoff_radius, shift_potential, strict)"
+)]
+#[derive(Clone)]
+pub struct PyUVTheoryFunctional(pub Rc<DFT<UVTheoryFunctional>>);
+
+#[pymethods]
+impl PyUVTheoryFunctional {
+    #[new]
+    #[args(
+        fmt_version = "FMTVersion::WhiteBear",
+        max_eta = "0.5",
+        max_iter_cross_assoc = "50",
+        tol_cross_assoc = "1e-10",
+        shift_potential = "false",
+        strict = "false"
+    )]
+    fn new(
+        parameters: PyUVParameters,
+        fmt_version: FMTVersion,
+        max_eta: f64,
+        perturbation: Option<Perturbation>,
+        virial_order: Option<VirialOrder>,
+        max_iter_cross_assoc: usize,
+        tol_cross_assoc: f64,
+        cutoff_radius: Option<f64>,
+        shift_potential: bool,
+        strict: bool,
+    ) -> PyResult<Self> {
+        let options = UVTheoryOptions {
+            max_eta,
+            perturbation: perturbation.unwrap_or(Perturbation::WeeksChandlerAndersen),
+            virial_order: virial_order.unwrap_or(VirialOrder::Second),
+            max_iter_cross_assoc,
+            tol_cross_assoc,
+            cutoff_radius,
+            shift_potential,
+            validity_domain: strict.then(ValidityDomain::default),
+        };
+        Ok(Self(Rc::new(UVTheoryFunctional::with_options(
+            parameters.0,
+            fmt_version,
+            options,
+        )?)))
+    }
+}
+
+impl_equation_of_state!(PyUVTheoryFunctional);
+
+impl_state!(DFT<UVTheoryFunctional>, PyUVTheoryFunctional);
+impl_state_molarweight!(DFT<UVTheoryFunctional>, PyUVTheoryFunctional);
+impl_phase_equilibrium!(DFT<UVTheoryFunctional>, PyUVTheoryFunctional);
+
+impl_planar_interface!(UVTheoryFunctional);
+impl_surface_tension_diagram!(UVTheoryFunctional);
+
+#[pymodule]
+pub fn dft(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyUVTheoryFunctional>()?;
+    m.add_class::<PyState>()?;
+    m.add_class::<PyPhaseDiagram>()?;
+    m.add_class::<PyPhaseEquilibrium>()?;
+    m.add_class::<PyPlanarInterface>()?;
+    m.add_class::<Geometry>()?;
+    m.add_class::<PyDFTSolver>()?;
+    m.add_class::<FMTVersion>()?;
+    m.add_class::<PySurfaceTensionDiagram>()
+}
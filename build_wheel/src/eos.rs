@@ -1,6 +1,6 @@
 use feos_core::*;
 use feos_uvtheory::python::PyUVParameters;
-use feos_uvtheory::{UVTheory, UVTheoryOptions, Perturbation};
+use feos_uvtheory::{Perturbation, UVTheory, UVTheoryOptions, ValidityDomain, VirialOrder};
 use numpy::convert::ToPyArray;
 use numpy::{PyArray1, PyArray2};
 use pyo3::exceptions::PyValueError;
@@ -10,7 +10,6 @@ use quantity::si::*;
 use std::collections::HashMap;
 use std::rc::Rc;
 
-
 /// Initialize UV Theory equation of state.
 ///
 /// Parameters
@@ -19,36 +18,104 @@ use std::rc::Rc;
 ///     The parameters of the UV Theory equation of state to use.
 /// max_eta : float, optional
 ///     Maximum packing fraction. Defaults to 0.5.
+/// perturbation : Perturbation, optional
+///     Division type of the Mie potential. Defaults to WCA division.
+/// virial_order : VirialOrder, optional
+///     Highest virial coefficient reproduced by the model. Defaults to
+///     VirialOrder.Second, VirialOrder.Third selects uv-B3-theory (WCA only).
+/// max_iter_cross_assoc : unsigned integer, optional
+///     Maximum number of iterations for cross association. Defaults to 50.
+/// tol_cross_assoc : float
+///     Tolerance for convergence of cross association. Defaults to 1e-10.
+/// cutoff_radius : float, optional
+///     Cutoff radius of the potential in units of sigma. Has to be larger
+///     than the position of the potential minimum.
+///     Defaults to the full-range potential.
+/// shift_potential : bool, optional
+///     Shift the truncated potential to zero at the cutoff radius.
+///     Defaults to False.
+/// strict : bool, optional
+///     Refuse to evaluate outside of the validity domain of the model:
+///     parameters outside of the domain raise a ValueError, properties of
+///     states outside of the domain are NaN and `UVTheory.check_state`
+///     raises a ValueError for them. Defaults to False.
 ///
 /// Returns
 /// -------
 /// UVTheory
 ///     The UV Theory equation of state that can be used to compute thermodynamic
 ///     states.
+///
+/// Raises
+/// ------
+/// ValueError
+///     If the options are incompatible or, in strict mode, the parameters
+///     are outside of the validity domain.
 #[pyclass(name = "UVTheory", unsendable)]
-#[pyo3(text_signature = "(parameters, max_eta, perturbation)")]
+#[pyo3(
+    text_signature = "(parameters, max_eta, perturbation, virial_order, max_iter_cross_assoc, tol_cross_assoc, cutoff_radius, shift_potential, strict)"
+)]
 #[derive(Clone)]
 pub struct PyUVTheory(pub Rc<UVTheory>);
 
 #[pymethods]
 impl PyUVTheory {
     #[new]
-    #[args(max_eta = "0.5")]
-    fn new(parameters: PyUVParameters, max_eta: f64, perturbation: Option<Perturbation>) -> Self {
-        let options = match perturbation {
-            Some(p) => UVTheoryOptions {
-                max_eta,
-                perturbation: p,
-            },
-            None => UVTheoryOptions {
-                max_eta,
-                perturbation: Perturbation::WeeksChandlerAndersen,
-            },
+    #[args(
+        max_eta = "0.5",
+        max_iter_cross_assoc = "50",
+        tol_cross_assoc = "1e-10",
+        shift_potential = "false",
+        strict = "false"
+    )]
+    fn new(
+        parameters: PyUVParameters,
+        max_eta: f64,
+        perturbation: Option<Perturbation>,
+        virial_order: Option<VirialOrder>,
+        max_iter_cross_assoc: usize,
+        tol_cross_assoc: f64,
+        cutoff_radius: Option<f64>,
+        shift_potential: bool,
+        strict: bool,
+    ) -> PyResult<Self> {
+        let options = UVTheoryOptions {
+            max_eta,
+            perturbation: perturbation.unwrap_or(Perturbation::WeeksChandlerAndersen),
+            virial_order: virial_order.unwrap_or(VirialOrder::Second),
+            max_iter_cross_assoc,
+            tol_cross_assoc,
+            cutoff_radius,
+            shift_potential,
+            validity_domain: strict.then(ValidityDomain::default),
         };
-        Self(Rc::new(UVTheory::with_options(
+        Ok(Self(Rc::new(UVTheory::with_options(
             parameters.0.clone(),
             options,
-        )))
+        )?)))
+    }
+
+    /// Check a state in strict mode.
+    ///
+    /// Parameters
+    /// ----------
+    /// state : State
+    ///     The state to check.
+    ///
+    /// Returns
+    /// -------
+    /// State
+    ///     The state, if strict mode is disabled or the state is inside of
+    ///     the validity domain.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If strict mode is enabled and the state is outside of the
+    ///     validity domain.
+    #[pyo3(text_signature = "($self, state)")]
+    fn check_state(&self, state: PyState) -> PyResult<PyState> {
+        Ok(PyState(self.0.check_state(state.0)?))
     }
 }
 
@@ -56,6 +123,7 @@ impl_equation_of_state!(PyUVTheory);
 impl_virial_coefficients!(PyUVTheory);
 
 impl_state!(UVTheory, PyUVTheory);
+impl_state_molarweight!(UVTheory, PyUVTheory);
 impl_phase_equilibrium!(UVTheory, PyUVTheory);
 
 #[pymodule]
@@ -1,12 +1,15 @@
+use feos_core::python::joback::PyJobackRecord;
 use feos_core::python::parameter::*;
 use feos_core::{Contributions, Verbosity};
 use feos_uvtheory::python::*;
-use feos_uvtheory::Perturbation;
+use feos_uvtheory::{CombiningRule, Perturbation, VirialOrder};
 use pyo3::prelude::*;
 use pyo3::wrap_pymodule;
 use quantity::python::__PYO3_PYMODULE_DEF_QUANTITY;
 
+mod dft;
 mod eos;
+use dft::__PYO3_PYMODULE_DEF_DFT;
 use eos::__PYO3_PYMODULE_DEF_EOS;
 
 #[pymodule]
@@ -15,17 +18,25 @@ pub fn feos_uvtheory(py: Python<'_>, m: &PyModule) -> PyResult<()> {
     m.add_class::<Verbosity>()?;
     m.add_class::<Contributions>()?;
     m.add_class::<Perturbation>()?;
+    m.add_class::<VirialOrder>()?;
+    m.add_class::<CombiningRule>()?;
 
     m.add_class::<PyUVRecord>()?;
+    m.add_class::<PyJobackRecord>()?;
     m.add_class::<PyPureRecord>()?;
+    m.add_class::<PySegmentRecord>()?;
+    m.add_class::<PyChemicalRecord>()?;
+    m.add_class::<PyBinarySegmentRecord>()?;
     m.add_class::<PyUVParameters>()?;
 
     m.add_wrapped(wrap_pymodule!(eos))?;
+    m.add_wrapped(wrap_pymodule!(dft))?;
     m.add_wrapped(wrap_pymodule!(quantity))?;
     py.run(
         "\
 import sys
 sys.modules['feos_uvtheory.eos'] = eos
+sys.modules['feos_uvtheory.dft'] = dft
 quantity.SINumber.__module__ = 'feos_uvtheory.si'
 quantity.SIArray1.__module__ = 'feos_uvtheory.si'
 quantity.SIArray2.__module__ = 'feos_uvtheory.si'
@@ -0,0 +1,311 @@
+use crate::UVTheory;
+use feos_core::{EosError, EosResult, EquationOfState, StateHD};
+use ndarray::{Array1, Array2, ArrayView1, Axis, Zip};
+use num_dual::{Dual64, DualNum, DualVec64, HyperDual, StaticVec};
+use quantity::si::*;
+
+/// Residual Helmholtz energy and its derivatives at many states.
+///
+/// All quantities refer to the reduced residual Helmholtz energy per
+/// particle `a = A^res / (NkT)` as function of temperature, density and
+/// mole fractions. Properties of the individual states are stored
+/// contiguously for every quantity (structure of arrays), the chemical
+/// potentials are stored with shape `(components, states)`.
+#[derive(Clone, Debug)]
+pub struct ResidualBatch {
+    /// `a`
+    pub helmholtz_energy: Array1<f64>,
+    /// `(da/dT)_rho,x`
+    pub da_dt: SIArray1,
+    /// `(da/drho)_T,x`
+    pub da_drho: SIArray1,
+    /// `(d^2a/dT^2)_rho,x`
+    pub d2a_dt2: SIArray1,
+    /// `(d^2a/dT drho)_x`
+    pub d2a_dtdrho: SIArray1,
+    /// `(d^2a/drho^2)_T,x`
+    pub d2a_drho2: SIArray1,
+    /// Residual chemical potentials `mu_i^res / kT`.
+    pub chemical_potential: Array2<f64>,
+}
+
+/// Derivatives of the reduced residual Helmholtz energy per particle of a
+/// single state (or a single contribution) in units of Kelvin and Angstrom.
+#[derive(Clone, Debug)]
+pub(crate) struct Derivatives {
+    pub a: f64,
+    pub a_t: f64,
+    pub a_rho: f64,
+    pub a_tt: f64,
+    pub a_trho: f64,
+    pub a_rhorho: f64,
+    /// Residual chemical potentials `mu_i^res / kT`.
+    pub mu: Array1<f64>,
+}
+
+impl std::ops::AddAssign<&Derivatives> for Derivatives {
+    fn add_assign(&mut self, rhs: &Derivatives) {
+        self.a += rhs.a;
+        self.a_t += rhs.a_t;
+        self.a_rho += rhs.a_rho;
+        self.a_tt += rhs.a_tt;
+        self.a_trho += rhs.a_trho;
+        self.a_rhorho += rhs.a_rhorho;
+        self.mu += &rhs.mu;
+    }
+}
+
+impl UVTheory {
+    /// Residual Helmholtz energy and its first and second derivatives at
+    /// every combination of `temperature[k]`, `density[k]` and `molefracs.column(k)`.
+    ///
+    /// The mole fractions are given with shape `(components, states)`. Every
+    /// state is evaluated independently with a single evaluation for all
+    /// temperature and density derivatives and one evaluation per component
+    /// for the chemical potentials; the states are distributed over threads.
+    /// Only the results are stored as structure of arrays: the Helmholtz energy
+    /// contributions are not vectorized over states, their per-component loops
+    /// are still evaluated for one state at a time.
+    ///
+    /// Returns an error if the numbers of temperatures, densities and compositions
+    /// differ or if the number of rows of `molefracs` is not the number of components.
+    pub fn residual_batch(
+        &self,
+        temperature: &SIArray1,
+        density: &SIArray1,
+        molefracs: &Array2<f64>,
+    ) -> EosResult<ResidualBatch> {
+        let n = temperature.len();
+        if molefracs.nrows() != self.components() {
+            return Err(EosError::IncompatibleComponents(
+                self.components(),
+                molefracs.nrows(),
+            ));
+        }
+        if density.len() != n || molefracs.ncols() != n {
+            return Err(EosError::UndeterminedState(format!(
+                "{} temperatures, {} densities and {} compositions",
+                n,
+                density.len(),
+                molefracs.ncols()
+            )));
+        }
+        let rho_unit = 1.0 / (ANGSTROM.powi(3) * NAV);
+        let t = temperature.to_reduced(KELVIN)?;
+        let rho = density.to_reduced(rho_unit)?;
+
+        let states: Vec<usize> = (0..n).collect();
+        let derivatives: Vec<Derivatives> = self.par_map(&states, |eos, &k| {
+            eos.derivatives_contributions(t[k], rho[k], molefracs.column(k))
+                .into_iter()
+                .map(|(_, d)| d)
+                .reduce(|mut acc, d| {
+                    acc += &d;
+                    acc
+                })
+                .unwrap()
+        });
+
+        let column = |f: fn(&Derivatives) -> f64| Array1::from_iter(derivatives.iter().map(f));
+        let mut chemical_potential = Array2::zeros((self.components(), n));
+        Zip::from(chemical_potential.axis_iter_mut(Axis(1)))
+            .and(&derivatives)
+            .for_each(|mut mu, d| mu.assign(&d.mu));
+        Ok(ResidualBatch {
+            helmholtz_energy: column(|d| d.a),
+            da_dt: column(|d| d.a_t) / KELVIN,
+            da_drho: column(|d| d.a_rho) / rho_unit,
+            d2a_dt2: column(|d| d.a_tt) / KELVIN.powi(2),
+            d2a_dtdrho: column(|d| d.a_trho) / (KELVIN * rho_unit),
+            d2a_drho2: column(|d| d.a_rhorho) / rho_unit.powi(2),
+            chemical_potential,
+        })
+    }
+
+    /// Derivatives of the residual Helmholtz energy of every contribution
+    /// at temperature `t` (in K), density `rho` (in 1/A^3) and composition `molefracs`.
+    ///
+    /// The derivatives with respect to temperature and density are obtained from
+    /// a single evaluation with hyper-dual numbers (`eps1` in temperature, `eps2` in
+    /// density) of dual vectors (temperature and density).
+    pub(crate) fn derivatives_contributions(
+        &self,
+        t: f64,
+        rho: f64,
+        molefracs: ArrayView1<f64>,
+    ) -> Vec<(String, Derivatives)> {
+        type D = HyperDual<DualVec64<2>, f64>;
+        let (zero, one) = (DualVec64::from(0.0), DualVec64::from(1.0));
+        let t_dual = D::new_scalar(
+            DualVec64::new(t, StaticVec::new_vec([1.0, 0.0])),
+            one,
+            zero,
+            zero,
+        );
+        let rho_dual = D::new_scalar(
+            DualVec64::new(rho, StaticVec::new_vec([0.0, 1.0])),
+            zero,
+            one,
+            zero,
+        );
+        let x = molefracs.mapv(D::from);
+        let a = self.evaluate_residual_contributions(&StateHD::new(t_dual, rho_dual.recip(), x));
+
+        // the derivatives with respect to the mole numbers at constant volume
+        // are the residual chemical potentials
+        let n = molefracs.len();
+        let mu: Vec<Vec<f64>> = (0..n)
+            .map(|i| {
+                let mut moles = molefracs.mapv(Dual64::from);
+                moles[i] = moles[i].derive();
+                self.evaluate_residual_contributions(&StateHD::new(
+                    Dual64::from(t),
+                    Dual64::from(rho.recip()),
+                    moles,
+                ))
+                .into_iter()
+                .map(|(_, a)| a.eps[0])
+                .collect()
+            })
+            .collect();
+
+        a.into_iter()
+            .enumerate()
+            .map(|(k, (name, a))| {
+                let derivatives = Derivatives {
+                    a: a.re.re,
+                    a_t: a.re.eps[0],
+                    a_rho: a.re.eps[1],
+                    a_tt: a.eps1[0].eps[0],
+                    a_trho: a.eps1[0].eps[1],
+                    a_rhorho: a.eps2[0].eps[1],
+                    mu: Array1::from_shape_fn(n, |i| mu[i][k]),
+                };
+                (name, derivatives)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parameters::utils::test_parameters_mixture;
+    use approx::assert_relative_eq;
+    use feos_core::{Contributions, State};
+    use ndarray::arr1;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[test]
+    fn batch_mismatch() {
+        let eos = UVTheory::new(Arc::new(test_parameters_mixture(
+            arr1(&[12.0, 12.0]),
+            arr1(&[6.0, 6.0]),
+            arr1(&[1.0, 1.0]),
+            arr1(&[1.0, 0.5]),
+        )));
+        let t = arr1(&[1.0, 2.0]) * KELVIN;
+        let rho = arr1(&[0.1, 0.2]) / (ANGSTROM.powi(3) * NAV);
+        assert!(matches!(
+            eos.residual_batch(&t, &rho, &Array2::from_elem((3, 2), 1.0 / 3.0)),
+            Err(EosError::IncompatibleComponents(2, 3))
+        ));
+        assert!(matches!(
+            eos.residual_batch(&t, &rho, &Array2::from_elem((2, 3), 0.5)),
+            Err(EosError::UndeterminedState(_))
+        ));
+    }
+
+    #[test]
+    fn batch_mixture() {
+        let parameters = test_parameters_mixture(
+            arr1(&[12.0, 12.0]),
+            arr1(&[6.0, 6.0]),
+            arr1(&[1.0, 1.0]),
+            arr1(&[1.0, 0.5]),
+        );
+        let eos = Rc::new(UVTheory::new(Arc::new(parameters)));
+        let n = 7;
+        let rho_unit = 1.0 / (ANGSTROM.powi(3) * NAV);
+        let t = Array1::linspace(1.0, 4.0, n) * KELVIN;
+        let rho = Array1::linspace(0.05, 0.8, n) * rho_unit;
+        let x0 = Array1::linspace(0.1, 0.9, n);
+        let mut x = Array2::zeros((2, n));
+        x.row_mut(0).assign(&x0);
+        x.row_mut(1).assign(&(1.0 - &x0));
+        let batch = eos.residual_batch(&t, &rho, &x).unwrap();
+
+        for k in 0..n {
+            let (tk, rhok) = (t.get(k), rho.get(k));
+            let moles = x.column(k).to_owned() * MOL;
+            let state = State::new_nvt(&eos, tk, MOL / rhok, &moles).unwrap();
+            let rt = RGAS * tk;
+            assert_relative_eq!(
+                batch.helmholtz_energy[k],
+                state
+                    .helmholtz_energy(Contributions::ResidualNvt)
+                    .to_reduced(MOL * rt)
+                    .unwrap(),
+                max_relative = 1e-10
+            );
+            let pressure = (batch.da_drho.get(k) * rhok * rhok * rt)
+                .to_reduced(PASCAL)
+                .unwrap();
+            assert_relative_eq!(
+                pressure,
+                state
+                    .pressure(Contributions::ResidualNvt)
+                    .to_reduced(PASCAL)
+                    .unwrap(),
+                max_relative = 1e-10
+            );
+            let mu = state.chemical_potential(Contributions::ResidualNvt);
+            for i in 0..2 {
+                assert_relative_eq!(
+                    batch.chemical_potential[(i, k)],
+                    mu.get(i).to_reduced(rt).unwrap(),
+                    max_relative = 1e-10
+                );
+            }
+            // c_v^res = -2T (da/dT) - T^2 (d^2a/dT^2)
+            let c_v = -(batch.da_dt.get(k) * tk * 2.0 + batch.d2a_dt2.get(k) * tk * tk)
+                .into_value()
+                .unwrap();
+            assert_relative_eq!(
+                c_v,
+                state
+                    .c_v(Contributions::ResidualNvt)
+                    .to_reduced(RGAS)
+                    .unwrap(),
+                max_relative = 1e-8
+            );
+            // dp/drho = 2 rho kT (da/drho) + rho^2 kT (d^2a/drho^2)
+            let dp_drho =
+                ((batch.da_drho.get(k) * rhok * 2.0 + batch.d2a_drho2.get(k) * rhok * rhok) * rt)
+                    .to_reduced(RGAS * KELVIN)
+                    .unwrap();
+            assert_relative_eq!(
+                dp_drho,
+                state
+                    .dp_drho(Contributions::ResidualNvt)
+                    .to_reduced(RGAS * KELVIN)
+                    .unwrap(),
+                max_relative = 1e-8
+            );
+            // dp/dT = rho^2 k (da/drho + T d^2a/dTdrho)
+            let dp_dt =
+                ((batch.da_drho.get(k) + batch.d2a_dtdrho.get(k) * tk) * rhok * rhok * RGAS)
+                    .to_reduced(PASCAL / KELVIN)
+                    .unwrap();
+            assert_relative_eq!(
+                dp_dt,
+                state
+                    .dp_dt(Contributions::ResidualNvt)
+                    .to_reduced(PASCAL / KELVIN)
+                    .unwrap(),
+                max_relative = 1e-8
+            );
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use crate::eos::{hs_diameter, perturbation_contributions, Perturbation, UVTheoryOptions};
+use crate::errors::UVTheoryResult;
+use crate::parameters::{mie_prefactor, UVParameters};
+use feos_core::joback::Joback;
+use feos_core::parameter::Parameter;
+use feos_core::{IdealGasContribution, MolarWeight};
+use feos_dft::adsorption::FluidParameters;
+use feos_dft::fundamental_measure_theory::{FMTContribution, FMTProperties, FMTVersion};
+use feos_dft::solvation::PairPotential;
+use feos_dft::{FunctionalContribution, HelmholtzEnergyFunctional, MoleculeShape, DFT};
+use hard_chain::ChainFunctional;
+use ndarray::{Array, Array1, Array2};
+use num_dual::DualNum;
+use perturbation::PerturbationFunctional;
+use quantity::si::*;
+use std::f64::consts::FRAC_PI_6;
+use std::rc::Rc;
+use std::sync::Arc;
+
+mod hard_chain;
+mod perturbation;
+
+/// uv-theory Helmholtz energy functional.
+///
+/// The hard-sphere contribution is described by fundamental measure theory
+/// using the temperature dependent diameters of the chosen division and the
+/// chain contribution follows the functional of Sauer and Gross (2017). The
+/// reference and attractive perturbations are evaluated at weighted densities.
+pub struct UVTheoryFunctional {
+    pub parameters: Arc<UVParameters>,
+    fmt_version: FMTVersion,
+    options: UVTheoryOptions,
+    contributions: Vec<Box<dyn FunctionalContribution>>,
+    joback: Joback,
+}
+
+impl UVTheoryFunctional {
+    pub fn new(parameters: Arc<UVParameters>) -> DFT<Self> {
+        Self::with_checked_options(
+            parameters,
+            FMTVersion::WhiteBear,
+            UVTheoryOptions::default(),
+        )
+    }
+
+    pub fn new_full(parameters: Arc<UVParameters>, fmt_version: FMTVersion) -> DFT<Self> {
+        Self::with_checked_options(parameters, fmt_version, UVTheoryOptions::default())
+    }
+
+    /// Returns an error if uv-B3-theory is combined with the Barker-Henderson
+    /// division or with a truncated potential, or if the parameters are outside
+    /// of the validity domain in strict mode.
+    pub fn with_options(
+        parameters: Arc<UVParameters>,
+        fmt_version: FMTVersion,
+        options: UVTheoryOptions,
+    ) -> UVTheoryResult<DFT<Self>> {
+        options.check_parameters(&parameters)?;
+        Ok(Self::with_checked_options(parameters, fmt_version, options))
+    }
+
+    /// Functional for options that are known to be compatible with each other
+    /// and with the parameters, e.g., the options of an existing functional.
+    fn with_checked_options(
+        parameters: Arc<UVParameters>,
+        fmt_version: FMTVersion,
+        options: UVTheoryOptions,
+    ) -> DFT<Self> {
+        let mut contributions: Vec<Box<dyn FunctionalContribution>> = Vec::with_capacity(3);
+
+        // Hard sphere contribution
+        let properties = Rc::new(UVTheoryFMTProperties {
+            parameters: parameters.clone(),
+            perturbation: options.perturbation.clone(),
+        });
+        contributions.push(Box::new(FMTContribution::new(&properties, fmt_version)));
+
+        // Hard chain contribution
+        if parameters.m.iter().any(|&mi| mi != 1.0) {
+            contributions.push(Box::new(ChainFunctional::new(properties)));
+        }
+
+        // Reference and attractive perturbation, the options have been checked before
+        for contribution in perturbation_contributions(&parameters, &options).unwrap() {
+            contributions.push(Box::new(PerturbationFunctional::new(
+                parameters.clone(),
+                options.perturbation.clone(),
+                contribution,
+                options.validity_domain,
+            )));
+        }
+
+        let joback = match &parameters.joback_records {
+            Some(joback_records) => Joback::new(joback_records.clone()),
+            None => Joback::default(parameters.ncomponents),
+        };
+
+        (Self {
+            parameters,
+            fmt_version,
+            options,
+            contributions,
+            joback,
+        })
+        .into()
+    }
+}
+
+impl HelmholtzEnergyFunctional for UVTheoryFunctional {
+    fn subset(&self, component_list: &[usize]) -> DFT<Self> {
+        Self::with_checked_options(
+            Arc::new(self.parameters.subset(component_list)),
+            self.fmt_version,
+            self.options.clone(),
+        )
+    }
+
+    fn compute_max_density(&self, moles: &Array1<f64>) -> f64 {
+        self.options.max_eta * moles.sum()
+            / (FRAC_PI_6 * &self.parameters.m * self.parameters.sigma.mapv(|v| v.powi(3)) * moles)
+                .sum()
+    }
+
+    fn contributions(&self) -> &[Box<dyn FunctionalContribution>] {
+        &self.contributions
+    }
+
+    fn ideal_gas(&self) -> &dyn IdealGasContribution {
+        &self.joback
+    }
+
+    fn molecule_shape(&self) -> MoleculeShape<'_> {
+        MoleculeShape::NonSpherical(&self.parameters.m)
+    }
+}
+
+impl MolarWeight<SIUnit> for UVTheoryFunctional {
+    fn molar_weight(&self) -> SIArray1 {
+        self.parameters.molarweight.clone() * GRAM / MOL
+    }
+}
+
+/// Hard-sphere properties for fundamental measure theory.
+struct UVTheoryFMTProperties {
+    parameters: Arc<UVParameters>,
+    perturbation: Perturbation,
+}
+
+impl FMTProperties for UVTheoryFMTProperties {
+    fn component_index(&self) -> Array1<usize> {
+        Array::from_shape_fn(self.parameters.ncomponents, |i| i)
+    }
+
+    fn chain_length(&self) -> Array1<f64> {
+        self.parameters.m.clone()
+    }
+
+    fn hs_diameter<D: DualNum<f64>>(&self, temperature: D) -> Array1<D> {
+        hs_diameter(&self.parameters, &self.perturbation, temperature)
+    }
+}
+
+impl FluidParameters for UVTheoryFunctional {
+    fn epsilon_k_ff(&self) -> Array1<f64> {
+        self.parameters.epsilon_k.clone()
+    }
+
+    fn sigma_ff(&self) -> &Array1<f64> {
+        &self.parameters.sigma
+    }
+}
+
+impl PairPotential for UVTheoryFunctional {
+    fn pair_potential(&self, r: &Array1<f64>) -> Array2<f64> {
+        let p = &self.parameters;
+        Array::from_shape_fn((p.ncomponents, r.len()), |(i, j)| {
+            let s = p.sigma[i] / r[j];
+            mie_prefactor(p.rep[i], p.att[i])
+                * p.epsilon_k[i]
+                * (s.powf(p.rep[i]) - s.powf(p.att[i]))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::eos::{UVTheory, VirialOrder};
+    use crate::errors::UVTheoryError;
+    use crate::parameters::utils::{
+        dme_co2_methane_parameters, methane_parameters, test_parameters_mixture,
+        water_methanol_parameters, water_parameters,
+    };
+    use crate::parameters::UVRecord;
+    use approx::assert_relative_eq;
+    use feos_core::{Contributions, PhaseEquilibrium, State};
+    use feos_dft::interface::PlanarInterface;
+    use ndarray::arr1;
+
+    fn assert_bulk_consistency(parameters: UVParameters, perturbation: Perturbation) {
+        let parameters = Arc::new(parameters);
+        let options = UVTheoryOptions {
+            max_eta: 0.5,
+            perturbation,
+            ..Default::default()
+        };
+        let eos = Rc::new(UVTheory::with_options(parameters.clone(), options.clone()).unwrap());
+        let func = Rc::new(
+            UVTheoryFunctional::with_options(parameters.clone(), FMTVersion::WhiteBear, options)
+                .unwrap(),
+        );
+
+        let t = 1.2 * parameters.epsilon_k[0] * KELVIN;
+        let moles = Array1::from_elem(parameters.ncomponents, 1.0) * MOL;
+        let volume = (parameters.sigma[0] * ANGSTROM).powi(3) / 0.6 * NAV * moles.sum();
+        let a_eos = State::new_nvt(&eos, t, volume, &moles)
+            .unwrap()
+            .molar_helmholtz_energy(Contributions::ResidualNvt)
+            .to_reduced(RGAS * t)
+            .unwrap();
+        let a_dft = State::new_nvt(&func, t, volume, &moles)
+            .unwrap()
+            .molar_helmholtz_energy(Contributions::ResidualNvt)
+            .to_reduced(RGAS * t)
+            .unwrap();
+        assert_relative_eq!(a_eos, a_dft, max_relative = 1e-10);
+    }
+
+    #[test]
+    fn incompatible_options() {
+        let parameters = Arc::new(methane_parameters(12.0, 6.0));
+        for options in [
+            UVTheoryOptions {
+                perturbation: Perturbation::BarkerHenderson,
+                virial_order: VirialOrder::Third,
+                ..Default::default()
+            },
+            UVTheoryOptions {
+                virial_order: VirialOrder::Third,
+                cutoff_radius: Some(2.5),
+                ..Default::default()
+            },
+        ] {
+            assert!(matches!(
+                UVTheoryFunctional::with_options(
+                    parameters.clone(),
+                    FMTVersion::WhiteBear,
+                    options
+                ),
+                Err(UVTheoryError::IncompatibleOptions(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn bulk_consistency_pure() {
+        assert_bulk_consistency(
+            methane_parameters(12.0, 6.0),
+            Perturbation::WeeksChandlerAndersen,
+        );
+        assert_bulk_consistency(methane_parameters(12.0, 6.0), Perturbation::BarkerHenderson);
+    }
+
+    #[test]
+    fn bulk_consistency_mixture() {
+        let p = || {
+            test_parameters_mixture(
+                arr1(&[12.0, 18.0]),
+                arr1(&[6.0, 6.0]),
+                arr1(&[1.0, 1.2]),
+                arr1(&[1.0, 0.8]),
+            )
+        };
+        assert_bulk_consistency(p(), Perturbation::WeeksChandlerAndersen);
+        assert_bulk_consistency(p(), Perturbation::BarkerHenderson);
+    }
+
+    #[test]
+    fn bulk_consistency_chain() {
+        let p = || {
+            let mut records = test_parameters_mixture(
+                arr1(&[12.0, 18.0]),
+                arr1(&[6.0, 6.0]),
+                arr1(&[1.0, 1.2]),
+                arr1(&[1.0, 0.8]),
+            )
+            .pure_records;
+            records[0].model_record = UVRecord::new(12.0, 6.0, 1.0, 1.0).with_segments(1.5);
+            records[1].model_record = UVRecord::new(18.0, 6.0, 1.2, 0.8).with_segments(2.5);
+            UVParameters::new_binary(records, None)
+        };
+        assert_bulk_consistency(p(), Perturbation::WeeksChandlerAndersen);
+        assert_bulk_consistency(p(), Perturbation::BarkerHenderson);
+    }
+
+    #[test]
+    fn bulk_consistency_association() {
+        assert_bulk_consistency(water_parameters(), Perturbation::WeeksChandlerAndersen);
+        assert_bulk_consistency(
+            water_methanol_parameters(None),
+            Perturbation::BarkerHenderson,
+        );
+    }
+
+    #[test]
+    fn bulk_consistency_polar() {
+        assert_bulk_consistency(
+            dme_co2_methane_parameters(),
+            Perturbation::WeeksChandlerAndersen,
+        );
+    }
+
+    #[test]
+    fn surface_tension_pure() {
+        let p = methane_parameters(12.0, 6.0);
+        let t = 0.8 * p.epsilon_k[0] * KELVIN;
+        let func = Rc::new(UVTheoryFunctional::new(Arc::new(p)));
+        let vle = PhaseEquilibrium::pure(&func, t, None, Default::default()).unwrap();
+        let interface = PlanarInterface::from_tanh(&vle, 512, 100.0 * ANGSTROM, 200.0 * KELVIN)
+            .unwrap()
+            .solve(None)
+            .unwrap();
+
+        // the profile approaches the coexisting bulk densities
+        let rho = interface
+            .profile
+            .density
+            .to_reduced(MOL / METER.powi(3))
+            .unwrap();
+        let rho_l = vle
+            .liquid()
+            .density
+            .to_reduced(MOL / METER.powi(3))
+            .unwrap();
+        let rho_v = vle.vapor().density.to_reduced(MOL / METER.powi(3)).unwrap();
+        assert_relative_eq!(rho[[0, 0]], rho_l, max_relative = 1e-4);
+        assert_relative_eq!(rho[[0, 511]], rho_v, max_relative = 1e-4);
+
+        // reduced surface tension (regression value)
+        let gamma = interface.surface_tension.unwrap() * (3.7039 * ANGSTROM).powi(2)
+            / (150.03 * KELVIN * KB);
+        assert_relative_eq!(
+            gamma.into_value().unwrap(),
+            0.8730372530336855,
+            max_relative = 1e-6
+        );
+    }
+
+    #[test]
+    fn surface_tension_truncated_shifted() {
+        // Lennard-Jones fluid truncated and shifted at 2.5 sigma; the width of the
+        // weight functions is adjusted to PC-SAFT, so that the surface tensions
+        // are about 20% larger than the simulation data, which are correlated by
+        // gamma* = 2.08 (1 - T* / 1.0779)^1.21
+        // (Vrabec et al., Mol. Phys. 104, 1509 (2006))
+        let p = methane_parameters(12.0, 6.0);
+        let options = UVTheoryOptions {
+            cutoff_radius: Some(2.5),
+            shift_potential: true,
+            ..Default::default()
+        };
+        let func = Rc::new(
+            UVTheoryFunctional::with_options(Arc::new(p), FMTVersion::WhiteBear, options).unwrap(),
+        );
+        for t in [0.8, 0.9] {
+            let vle = PhaseEquilibrium::pure(&func, t * 150.03 * KELVIN, None, Default::default())
+                .unwrap();
+            let interface = PlanarInterface::from_tanh(&vle, 512, 100.0 * ANGSTROM, 200.0 * KELVIN)
+                .unwrap()
+                .solve(None)
+                .unwrap();
+            let gamma = (interface.surface_tension.unwrap() * (3.7039 * ANGSTROM).powi(2)
+                / (150.03 * KELVIN * KB))
+                .into_value()
+                .unwrap();
+            let gamma_md = 2.08 * (1.0 - t / 1.0779f64).powf(1.21);
+            assert!(gamma > gamma_md);
+            assert_relative_eq!(gamma, gamma_md, max_relative = 0.25);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::eos::{hs_diameter, Perturbation, ValidityDomain};
+use crate::parameters::UVParameters;
+use feos_core::{EosResult, HelmholtzEnergy, HelmholtzEnergyDual, StateHD};
+use feos_dft::{
+    FunctionalContributionDual, WeightFunction, WeightFunctionInfo, WeightFunctionShape,
+};
+use ndarray::*;
+use num_dual::DualNum;
+use std::fmt;
+use std::sync::Arc;
+
+/// Radius of the weight functions of the perturbation in units of the
+/// hard-sphere diameter, adjusted for PC-SAFT to surface tensions of real
+/// substances (Sauer and Gross, Ind. Eng. Chem. Res. 56, 4119 (2017)).
+///
+/// The value is used for uv-theory without readjustment. Surface tensions of
+/// the Lennard-Jones fluid truncated and shifted at 2.5 sigma are about 20%
+/// larger than simulation data (Vrabec et al., Mol. Phys. 104, 1509 (2006)).
+const PSI_DFT: f64 = 1.3862;
+/// Radius of the weight functions for predictive density gradient theory,
+/// adjusted for PC-SAFT (Rehner and Gross, Phys. Rev. E 98, 063312 (2018)).
+const PSI_PDGT: f64 = 1.3286;
+
+/// Weighted density approximation of a bulk Helmholtz energy contribution.
+///
+/// The contribution is evaluated at locally averaged component densities
+/// so that the functional reduces to the equation of state for homogeneous
+/// density profiles. In strict mode, the Helmholtz energy density is NaN at
+/// weighted densities outside of the validity domain.
+pub struct PerturbationFunctional {
+    parameters: Arc<UVParameters>,
+    perturbation: Perturbation,
+    contribution: Box<dyn HelmholtzEnergy>,
+    validity_domain: Option<ValidityDomain>,
+}
+
+impl PerturbationFunctional {
+    pub fn new(
+        parameters: Arc<UVParameters>,
+        perturbation: Perturbation,
+        contribution: Box<dyn HelmholtzEnergy>,
+        validity_domain: Option<ValidityDomain>,
+    ) -> Self {
+        Self {
+            parameters,
+            perturbation,
+            contribution,
+            validity_domain,
+        }
+    }
+
+    fn weight_functions_psi<N: DualNum<f64> + ScalarOperand>(
+        &self,
+        psi: f64,
+        temperature: N,
+    ) -> WeightFunctionInfo<N> {
+        let d = hs_diameter(&self.parameters, &self.perturbation, temperature);
+        WeightFunctionInfo::new(Array1::from_shape_fn(d.len(), |i| i), false).add(
+            WeightFunction::new_scaled(d * psi, WeightFunctionShape::Theta),
+            false,
+        )
+    }
+}
+
+impl<N: DualNum<f64> + ScalarOperand> FunctionalContributionDual<N> for PerturbationFunctional
+where
+    dyn HelmholtzEnergy: HelmholtzEnergyDual<N>,
+{
+    fn weight_functions(&self, temperature: N) -> WeightFunctionInfo<N> {
+        self.weight_functions_psi(PSI_DFT, temperature)
+    }
+
+    fn weight_functions_pdgt(&self, temperature: N) -> WeightFunctionInfo<N> {
+        self.weight_functions_psi(PSI_PDGT, temperature)
+    }
+
+    fn calculate_helmholtz_energy_density(
+        &self,
+        temperature: N,
+        weighted_densities: ArrayView2<N>,
+    ) -> EosResult<Array1<N>> {
+        Ok(weighted_densities
+            .axis_iter(Axis(1))
+            .map(|rho| {
+                // the bulk contribution is not defined for a vanishing density
+                if rho.sum().re() < f64::EPSILON {
+                    return N::zero();
+                }
+                if let Some(domain) = &self.validity_domain {
+                    let violations = domain.check_state_variables(
+                        &self.parameters,
+                        temperature.re(),
+                        &rho.mapv(|r| r.re()),
+                    );
+                    if !violations.is_empty() {
+                        return N::from(f64::NAN);
+                    }
+                }
+                let state = StateHD::new(temperature, N::one(), rho.to_owned());
+                self.contribution.helmholtz_energy(&state)
+            })
+            .collect())
+    }
+}
+
+impl fmt::Display for PerturbationFunctional {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} functional", self.contribution)
+    }
+}
@@ -0,0 +1,337 @@
+use super::{hs_diameter, Perturbation};
+use crate::parameters::UVParameters;
+use feos_core::{EosError, EosResult, HelmholtzEnergyDual, StateHD};
+use ndarray::*;
+use num_dual::linalg::{norm, LU};
+use num_dual::*;
+use std::f64::consts::{FRAC_PI_3, PI};
+use std::fmt;
+use std::sync::Arc;
+
+/// Association contribution for a single associating component.
+#[derive(Debug, Clone)]
+pub struct Association {
+    pub parameters: Arc<UVParameters>,
+    pub perturbation: Perturbation,
+}
+
+/// Association contribution for multiple associating components.
+///
+/// The site fractions are determined according to Michelsen (2006).
+#[derive(Debug, Clone)]
+pub struct CrossAssociation {
+    pub parameters: Arc<UVParameters>,
+    pub perturbation: Perturbation,
+    pub max_iter: usize,
+    pub tol: f64,
+}
+
+/// Association strength with the contact value of the hard-sphere pair
+/// correlation function at the diameters of the chosen division.
+fn association_strength<D: DualNum<f64>>(
+    p: &UVParameters,
+    temperature: D,
+    r: &Array1<D>,
+    n2: D,
+    n3i: D,
+    ai: usize,
+    aj: usize,
+) -> D {
+    let k = r[ai] * r[aj] / (r[ai] + r[aj]) * (n2 * n3i * 2.0);
+    n3i * (k * (k / 18.0 + 0.5) + 1.0)
+        * p.sigma_ij[[ai, aj]].powi(3)
+        * p.kappa_aibj[[ai, aj]]
+        * (temperature.recip() * p.epsilon_k_aibj[[ai, aj]]).exp_m1()
+}
+
+/// Auxiliary variables of the segment fluid required for the association strength.
+fn auxiliary_variables<D: DualNum<f64>>(
+    p: &UVParameters,
+    perturbation: &Perturbation,
+    state: &StateHD<D>,
+) -> (Array1<D>, D, D) {
+    // temperature dependent segment radius
+    let r = hs_diameter(p, perturbation, state.temperature) * 0.5;
+
+    let n2 = (&state.partial_density * &p.m * &r * &r).sum() * 4.0 * PI;
+    let n3 = (&state.partial_density * &p.m * &r * &r * &r).sum() * 4.0 * FRAC_PI_3;
+    (r, n2, (-n3 + 1.0).recip())
+}
+
+impl<D: DualNum<f64>> HelmholtzEnergyDual<D> for Association {
+    /// Helmholtz energy for association, Wertheim TPT1
+    fn helmholtz_energy(&self, state: &StateHD<D>) -> D {
+        let p = &self.parameters;
+        let a = p.assoc_comp[0];
+        let (r, n2, n3i) = auxiliary_variables(p, &self.perturbation, state);
+
+        // association strength
+        let deltarho = association_strength(p, state.temperature, &r, n2, n3i, a, a)
+            * state.partial_density[a];
+
+        let na = p.na[a];
+        let nb = p.nb[a];
+        if nb > 0.0 {
+            // no cross association, two association sites
+            let xa = assoc_site_frac_ab(deltarho, na, nb);
+            let xb = (xa - 1.0) * (na / nb) + 1.0;
+
+            state.moles[a] * ((xa.ln() - xa * 0.5 + 0.5) * na + (xb.ln() - xb * 0.5 + 0.5) * nb)
+        } else {
+            // no cross association, one association site
+            let xa = assoc_site_frac_a(deltarho, na);
+
+            state.moles[a] * (xa.ln() - xa * 0.5 + 0.5) * na
+        }
+    }
+}
+
+impl fmt::Display for Association {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Association")
+    }
+}
+
+fn assoc_site_frac_ab<D: DualNum<f64>>(deltarho: D, na: f64, nb: f64) -> D {
+    if deltarho.re() > f64::EPSILON.sqrt() {
+        (((deltarho * (na - nb) + 1.0).powi(2) + deltarho * nb * 4.0).sqrt()
+            - (deltarho * (nb - na) + 1.0))
+            / (deltarho * na * 2.0)
+    } else {
+        D::one() + deltarho * nb * (deltarho * (nb + na) - 1.0)
+    }
+}
+
+fn assoc_site_frac_a<D: DualNum<f64>>(deltarho: D, na: f64) -> D {
+    if deltarho.re() > f64::EPSILON.sqrt() {
+        ((deltarho * na * 4.0 + 1.0).sqrt() - 1.0) / (deltarho * na * 2.0)
+    } else {
+        D::one() + deltarho * na * (deltarho * na * 2.0 - 1.0)
+    }
+}
+
+impl<D: DualNum<f64> + ScalarOperand> HelmholtzEnergyDual<D> for CrossAssociation {
+    /// Helmholtz energy for cross-association, Wertheim TPT1
+    fn helmholtz_energy(&self, state: &StateHD<D>) -> D {
+        let p = &self.parameters;
+        let (r, n2, n3i) = auxiliary_variables(p, &self.perturbation, state);
+
+        // check if density is close to 0
+        if state.partial_density.sum().re() < f64::EPSILON {
+            return D::zero();
+        }
+
+        // association strength
+        let delta = Array::from_shape_fn((p.nassoc, p.nassoc), |(i, j)| {
+            association_strength(
+                p,
+                state.temperature,
+                &r,
+                n2,
+                n3i,
+                p.assoc_comp[i],
+                p.assoc_comp[j],
+            )
+        });
+
+        // extract parameters of associating components
+        let na = Array::from_shape_fn(p.nassoc, |i| p.na[p.assoc_comp[i]]);
+        let nb = Array::from_shape_fn(p.nassoc, |i| p.nb[p.assoc_comp[i]]);
+        let rho = Array::from_shape_fn(p.nassoc, |i| state.partial_density[p.assoc_comp[i]]);
+
+        // cross-association according to Michelsen2006,
+        // states at which the iteration fails have a NaN Helmholtz energy
+        match self.site_fractions(&delta, &na, &nb, &rho) {
+            Ok(x) => {
+                let xa = x.slice(s![..p.nassoc]);
+                let xb = x.slice(s![p.nassoc..]);
+                let f = |x: D| x.ln() - x * 0.5 + 0.5;
+                (rho * (xa.mapv(f) * na + xb.mapv(f) * nb)).sum() * state.volume
+            }
+            Err(_) => D::from(f64::NAN),
+        }
+    }
+}
+
+impl CrossAssociation {
+    /// Fractions of non-bonded sites A (first half) and B (second half) of
+    /// all associating components.
+    fn site_fractions<D: DualNum<f64> + ScalarOperand>(
+        &self,
+        delta: &Array2<D>,
+        na: &Array1<f64>,
+        nb: &Array1<f64>,
+        rho: &Array1<D>,
+    ) -> EosResult<Array1<D>> {
+        let nassoc = self.parameters.nassoc;
+
+        // initialize monomer fraction
+        let mut x = Array::from_elem(2 * nassoc, 0.2);
+        let (delta_re, rho_re) = (delta.map(D::re), rho.map(D::re));
+        let mut converged = false;
+        for _ in 0..self.max_iter {
+            if newton_step_cross_association::<f64>(
+                &mut x, nassoc, &delta_re, na, nb, &rho_re, self.tol,
+            )? {
+                converged = true;
+                break;
+            }
+        }
+        if !converged {
+            return Err(EosError::NotConverged("Cross association".into()));
+        }
+
+        // calculate derivatives
+        let mut x_dual = x.mapv(D::from);
+        for _ in 0..D::NDERIV {
+            newton_step_cross_association(&mut x_dual, nassoc, delta, na, nb, rho, self.tol)?;
+        }
+        Ok(x_dual)
+    }
+}
+
+impl fmt::Display for CrossAssociation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cross-association")
+    }
+}
+
+fn newton_step_cross_association<D: DualNum<f64> + ScalarOperand>(
+    x: &mut Array1<D>,
+    nassoc: usize,
+    delta: &Array2<D>,
+    na: &Array1<f64>,
+    nb: &Array1<f64>,
+    rho: &Array1<D>,
+    tol: f64,
+) -> Result<bool, EosError> {
+    // gradient
+    let mut g: Array1<D> = Array::zeros(2 * nassoc);
+    // Hessian
+    let mut h: Array2<D> = Array::zeros((2 * nassoc, 2 * nassoc));
+
+    // slice arrays
+    let (xa, xb) = x.multi_slice_mut((s![..nassoc], s![nassoc..]));
+    let (mut ga, mut gb) = g.multi_slice_mut((s![..nassoc], s![nassoc..]));
+    let (mut haa, mut hab, mut hba, mut hbb) = h.multi_slice_mut((
+        s![..nassoc, ..nassoc],
+        s![..nassoc, nassoc..],
+        s![nassoc.., ..nassoc],
+        s![nassoc.., nassoc..],
+    ));
+
+    // calculate gradients and approximate Hessian
+    for i in 0..nassoc {
+        let d = &delta.index_axis(Axis(0), i) * rho;
+
+        let dnx = (&xb * nb * &d).sum() + 1.0;
+        ga[i] = xa[i].recip() - dnx;
+        hab.index_axis_mut(Axis(0), i).assign(&(&d * &(-nb)));
+        haa[(i, i)] = -dnx / xa[i];
+
+        let dnx = (&xa * na * &d).sum() + 1.0;
+        gb[i] = xb[i].recip() - dnx;
+        hba.index_axis_mut(Axis(0), i).assign(&(&d * &(-na)));
+        hbb[(i, i)] = -dnx / xb[i];
+    }
+
+    // Newton step
+    x.assign(&(&*x - &LU::new(h)?.solve(&g)));
+
+    // check convergence
+    Ok(norm(&g.map(D::re)) < tol)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parameters::utils::{water_methanol_parameters, water_parameters};
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn helmholtz_energy_water() {
+        let p = Arc::new(water_parameters());
+        let state = StateHD::new(350.0, 41.248289328513216, arr1(&[1.23]));
+        for perturbation in [
+            Perturbation::BarkerHenderson,
+            Perturbation::WeeksChandlerAndersen,
+        ] {
+            let assoc = Association {
+                parameters: p.clone(),
+                perturbation: perturbation.clone(),
+            };
+            let cross_assoc = CrossAssociation {
+                parameters: p.clone(),
+                perturbation,
+                max_iter: 50,
+                tol: 1e-10,
+            };
+            assert_relative_eq!(
+                assoc.helmholtz_energy(&state),
+                cross_assoc.helmholtz_energy(&state),
+                max_relative = 1e-10
+            );
+        }
+        let assoc = Association {
+            parameters: p,
+            perturbation: Perturbation::WeeksChandlerAndersen,
+        };
+        assert_relative_eq!(
+            assoc.helmholtz_energy(&state) / 1.23,
+            -4.272090118700335,
+            max_relative = 1e-10
+        );
+    }
+
+    #[test]
+    fn helmholtz_energy_cross_infinite_dilution() {
+        let water = Association {
+            parameters: Arc::new(water_parameters()),
+            perturbation: Perturbation::WeeksChandlerAndersen,
+        };
+        let mixture = CrossAssociation {
+            parameters: Arc::new(water_methanol_parameters(None)),
+            perturbation: Perturbation::WeeksChandlerAndersen,
+            max_iter: 50,
+            tol: 1e-10,
+        };
+        let t = 350.0;
+        let v = 41.248289328513216;
+        assert_relative_eq!(
+            water.helmholtz_energy(&StateHD::new(t, v, arr1(&[1.23]))),
+            mixture.helmholtz_energy(&StateHD::new(t, v, arr1(&[1.23, 0.0]))),
+            max_relative = 1e-10
+        );
+    }
+
+    #[test]
+    fn cross_association_not_converged() {
+        let mixture = CrossAssociation {
+            parameters: Arc::new(water_methanol_parameters(None)),
+            perturbation: Perturbation::WeeksChandlerAndersen,
+            max_iter: 1,
+            tol: 1e-10,
+        };
+        let state = StateHD::new(350.0, 41.248289328513216, arr1(&[0.6, 0.63]));
+        assert!(mixture.helmholtz_energy(&state).is_nan());
+        let state = StateHD::new(
+            Dual64::from(350.0).derive(),
+            Dual64::from(41.248289328513216),
+            arr1(&[Dual64::from(0.6), Dual64::from(0.63)]),
+        );
+        assert!(mixture.helmholtz_energy(&state).re.is_nan());
+    }
+
+    #[test]
+    fn cross_association_overrides() {
+        let p = water_methanol_parameters(None);
+        assert_relative_eq!(p.epsilon_k_aibj[[0, 1]], 0.5 * (2500.6706 + 2899.5));
+        assert_relative_eq!(p.epsilon_k_aibj[[0, 1]], p.epsilon_k_aibj[[1, 0]]);
+
+        let p = water_methanol_parameters(Some((0.04, 2700.0)));
+        assert_relative_eq!(p.kappa_aibj[[0, 1]], 0.04);
+        assert_relative_eq!(p.kappa_aibj[[1, 0]], 0.04);
+        assert_relative_eq!(p.epsilon_k_aibj[[0, 1]], 2700.0);
+        assert_relative_eq!(p.epsilon_k_aibj[[0, 0]], 2500.6706);
+    }
+}
@@ -1,4 +1,5 @@
 use super::hard_sphere_bh::diameter_bh;
+use super::{segment_state, Cutoff, MEAN_FIELD_RADIUS};
 use crate::parameters::*;
 use feos_core::{HelmholtzEnergyDual, StateHD};
 use ndarray::Array1;
@@ -6,7 +7,7 @@ use num_dual::DualNum;
 use std::{
     f64::consts::{FRAC_PI_3, PI},
     fmt,
-    rc::Rc,
+    sync::Arc,
 };
 
 const C_BH: [[f64; 4]; 2] = [
@@ -41,7 +42,8 @@ const C2: [[f64; 2]; 3] = [
 
 #[derive(Debug, Clone)]
 pub struct AttractivePerturbationBH {
-    pub parameters: Rc<UVParameters>,
+    pub parameters: Arc<UVParameters>,
+    pub cutoff: Cutoff,
 }
 
 impl fmt::Display for AttractivePerturbationBH {
@@ -53,6 +55,7 @@ impl fmt::Display for AttractivePerturbationBH {
 impl<D: DualNum<f64>> HelmholtzEnergyDual<D> for AttractivePerturbationBH {
     /// Helmholtz energy for attractive perturbation, eq. 52
     fn helmholtz_energy(&self, state: &StateHD<D>) -> D {
+        let state = &segment_state(&self.parameters, state);
         let p = &self.parameters;
         let x = &state.molefracs;
         let t = state.temperature;
@@ -64,19 +67,15 @@ impl<D: DualNum<f64>> HelmholtzEnergyDual<D> for AttractivePerturbationBH {
         let t_x = state.temperature / epsilon_k_x;
         let rho_x = density * sigma_x.powi(3);
 
-        let mean_field_constant_x = mean_field_constant(rep_x, att_x, D::one());
+        let mean_field_constant_x = self.cutoff.mean_field_constant(rep_x, att_x, D::one());
 
         let i_bh = correlation_integral_bh(rho_x, mean_field_constant_x, rep_x, att_x, d_x);
         let delta_a1u = density / t_x * i_bh * 2.0 * PI * weighted_sigma3_ij;
 
-        let u_fraction_bh = u_fraction_bh(
-            rep_x,
-            density * (x * &p.sigma.mapv(|s| s.powi(3))).sum(),
-            t_x.recip(),
-        );
+        let u_fraction_bh = u_fraction_bh(rep_x, rho_x, t_x.recip());
 
         let b21u = delta_b12u(t_x, mean_field_constant_x, weighted_sigma3_ij);
-        let b2bar = residual_virial_coefficient(p, x, state.temperature);
+        let b2bar = residual_virial_coefficient(p, x, state.temperature, &self.cutoff);
 
         state.moles.sum() * (delta_a1u + (-u_fraction_bh + 1.0) * (b2bar - b21u) * density)
     }
@@ -86,15 +85,20 @@ fn delta_b12u<D: DualNum<f64>>(t_x: D, mean_field_constant_x: D, weighted_sigma3
     -mean_field_constant_x / t_x * 2.0 * PI * weighted_sigma3_ij
 }
 
-fn residual_virial_coefficient<D: DualNum<f64>>(p: &UVParameters, x: &Array1<D>, t: D) -> D {
+fn residual_virial_coefficient<D: DualNum<f64>>(
+    p: &UVParameters,
+    x: &Array1<D>,
+    t: D,
+    cutoff: &Cutoff,
+) -> D {
+    let effective = p.effective_parameters(t);
     let mut delta_b2bar = D::zero();
     for i in 0..p.ncomponents {
-        let xi = x[i];
         for j in 0..p.ncomponents {
-            delta_b2bar += xi
+            delta_b2bar += x[i]
                 * x[j]
-                * p.sigma_ij[[i, j]].powi(3)
-                * delta_b2(t / p.eps_k_ij[[i, j]], p.rep_ij[[i, j]], p.att_ij[[i, j]]);
+                * effective.sigma_ij(i, j).powi(3)
+                * delta_b2(p, i, j, t / effective.eps_k_ij(i, j), cutoff);
         }
     }
     delta_b2bar
@@ -121,6 +125,14 @@ fn u_fraction_bh<D: DualNum<f64>>(rep_x: D, reduced_density: D, one_fluid_beta:
     for i in 0..4 {
         c[i] = inv_rep * CU_BH[i][1] + CU_BH[i][0];
     }
+    // At zero density the u-fraction, its first density derivative and all of its
+    // temperature and composition derivatives vanish, so zero is exact for every
+    // derivative that exists. The second density derivative of the non-integer
+    // powers diverges, but it enters the Helmholtz energy multiplied by the
+    // squared density, so the second virial coefficient is not affected.
+    if reduced_density.re() == 0.0 {
+        return D::zero();
+    }
     let a = 1.2187;
     let b = 4.2773;
     (activation(c[1], one_fluid_beta) * (-c[0] + 1.0) + c[0])
@@ -146,7 +158,7 @@ fn activation<D: DualNum<f64>>(c: D, one_fluid_beta: D) -> D {
 //     for i in 0..p.ncomponents {
 //         let xi = x[i];
 //         for j in 0..p.ncomponents {
-//             let _y = xi * x[j] * p.sigma_ij[[i, j]].powi(3);
+//             let _y = xi * x[j] * effective.sigma_ij(i, j).powi(3);
 //             weighted_sigma3_ij += _y;
 //             epsilon_k += _y * p.eps_k_ij[[i, j]];
 //             rep += xi * x[j] * p.rep_ij[[i, j]];
@@ -170,6 +182,7 @@ fn one_fluid_properties<D: DualNum<f64>>(
     t: D,
 ) -> (D, D, D, D, D, D) {
     let d = diameter_bh(p, t);
+    let effective = p.effective_parameters(t);
     // &p.sigma;
     let mut epsilon_k = D::zero();
     let mut weighted_sigma3_ij = D::zero();
@@ -181,15 +194,18 @@ fn one_fluid_properties<D: DualNum<f64>>(
 
         d_x_3 += x[i] * d[i].powi(3);
         for j in 0..p.ncomponents {
-            let _y = xi * x[j] * p.sigma_ij[[i, j]].powi(3);
+            let _y = xi * x[j] * effective.sigma_ij(i, j).powi(3);
             weighted_sigma3_ij += _y;
-            epsilon_k += _y * p.eps_k_ij[[i, j]];
+            epsilon_k += _y * effective.eps_k_ij(i, j);
 
             rep += xi * x[j] * p.rep_ij[[i, j]];
             att += xi * x[j] * p.att_ij[[i, j]];
         }
     }
-    let sigma_x = (x * &p.sigma.mapv(|v| v.powi(3))).sum().powf(1.0 / 3.0);
+    let sigma_x = (0..p.ncomponents)
+        .map(|i| x[i] * effective.sigma(i).powi(3))
+        .sum::<D>()
+        .powf(1.0 / 3.0);
     let dx = d_x_3.powf(1.0 / 3.0) / sigma_x;
 
     (
@@ -220,16 +236,32 @@ fn coefficients_bh<D: DualNum<f64>>(rep: D, att: D, d: D) -> [D; 3] {
     [c1, c2, c3]
 }
 
-fn delta_b2<D: DualNum<f64>>(reduced_temperature: D, rep: f64, att: f64) -> D {
-    let rc = 5.0;
-    let alpha = mean_field_constant(rep, att, rc);
-    let yeff = y_eff(reduced_temperature, rep, att);
-    -(yeff * (rc.powi(3) - 1.0) / 3.0 + reduced_temperature.recip() * alpha) * 2.0 * PI
+fn delta_b2<D: DualNum<f64>>(
+    p: &UVParameters,
+    i: usize,
+    j: usize,
+    reduced_temperature: D,
+    cutoff: &Cutoff,
+) -> D {
+    let rc = MEAN_FIELD_RADIUS;
+    let alpha = p.mean_field_constant_rc_ij[[i, j]];
+    let yeff = y_eff(reduced_temperature, &p.y_eff_bh_ij[[i, j]]);
+    let delta_b2 =
+        -(yeff * (rc.powi(3) - 1.0) / 3.0 + reduced_temperature.recip() * alpha) * 2.0 * PI;
+    // the shift is applied outside of the reference region r < sigma
+    cutoff.delta_b2(
+        delta_b2,
+        reduced_temperature,
+        p.rep_ij[[i, j]],
+        p.att_ij[[i, j]],
+        D::from(2.0 * FRAC_PI_3),
+    )
 }
 
-fn y_eff<D: DualNum<f64>>(reduced_temperature: D, rep: f64, att: f64) -> D {
-    // optimize: move this part to parameter initialization
-    let rc = 5.0;
+/// Temperature independent constants of the effective inverse temperature
+/// of a pair with the given exponents.
+pub(crate) fn y_eff_constants(rep: f64, att: f64) -> [f64; 4] {
+    let rc = MEAN_FIELD_RADIUS;
     let rs = 1.0;
     let c0 = 1.0
         - 3.0 * (mean_field_constant(rep, att, rs) - mean_field_constant(rep, att, rc))
@@ -237,7 +269,11 @@ fn y_eff<D: DualNum<f64>>(reduced_temperature: D, rep: f64, att: f64) -> D {
     let c1 = C2[0][0] + C2[0][1] / rep;
     let c2 = C2[1][0] + C2[1][1] / rep;
     let c3 = C2[2][0] + C2[2][1] / rep;
+    [c0, c1, c2, c3]
+}
 
+fn y_eff<D: DualNum<f64>>(reduced_temperature: D, constants: &[f64; 4]) -> D {
+    let [c0, c1, c2, c3] = *constants;
     let beta = reduced_temperature.recip();
     let beta_eff = beta * (-(beta * (beta * c2 + beta.powi(3) * c3 + c1) + 1.0).recip() * c0 + 1.0);
     beta_eff.exp() - 1.0
@@ -246,9 +282,13 @@ fn y_eff<D: DualNum<f64>>(reduced_temperature: D, rep: f64, att: f64) -> D {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::parameters::utils::methane_parameters;
+    use crate::eos::hard_sphere_bh::HardSphere;
+    use crate::eos::reference_perturbation_bh::ReferencePerturbationBH;
+    use crate::parameters::utils::{methane_parameters, test_parameters};
+    use crate::virial::MiePotential;
     use approx::assert_relative_eq;
     use ndarray::arr1;
+    use num_dual::{Dual64, HyperDual};
 
     #[test]
     fn test_attractive_perturbation() {
@@ -260,7 +300,8 @@ mod test {
 
         let p = methane_parameters(24.0, 6.0);
         let pt = AttractivePerturbationBH {
-            parameters: Rc::new(p.clone()),
+            parameters: Arc::new(p.clone()),
+            cutoff: Cutoff::new(&Default::default()),
         };
         let state = StateHD::new(
             reduced_temperature * p.epsilon_k[0],
@@ -295,7 +336,7 @@ mod test {
         dbg!(b21u);
         assert!(b21u.re() / p.sigma[0].powi(3) == -0.949898568221715);
 
-        let b2bar = residual_virial_coefficient(&p, x, state.temperature);
+        let b2bar = residual_virial_coefficient(&p, x, state.temperature, &pt.cutoff);
         dbg!(b2bar);
         assert_relative_eq!(
             b2bar.re() / p.sigma[0].powi(3),
@@ -311,4 +352,60 @@ mod test {
         //assert!(-1.16124062615291 == a.re())
         assert_relative_eq!(-1.16124062615291, a.re(), epsilon = 1e-5);
     }
+
+    /// Second virial coefficient of a contribution and its temperature derivative.
+    fn virial_coefficient<H>(contribution: &H, t: f64) -> Dual64
+    where
+        H: HelmholtzEnergyDual<HyperDual<Dual64, f64>>,
+    {
+        let t = HyperDual::from_re(Dual64::from(t).derive());
+        let rho = HyperDual::new_scalar(
+            Dual64::from(0.0),
+            Dual64::from(1.0),
+            Dual64::from(1.0),
+            Dual64::from(0.0),
+        );
+        // the mole fractions are not defined by the moles at zero density
+        let state = StateHD {
+            temperature: t,
+            volume: HyperDual::from(1.0),
+            moles: arr1(&[rho]),
+            molefracs: arr1(&[HyperDual::from(1.0)]),
+            partial_density: arr1(&[rho]),
+        };
+        contribution.helmholtz_energy(&state).eps1eps2[(0, 0)] * 0.5
+    }
+
+    #[test]
+    fn zero_density_limit() {
+        for rep in [10.0, 12.0, 24.0] {
+            let p = Arc::new(test_parameters(rep, 6.0, 1.0, 1.0));
+            let hs = HardSphere {
+                parameters: p.clone(),
+            };
+            let rf = ReferencePerturbationBH {
+                parameters: p.clone(),
+            };
+            let pt = AttractivePerturbationBH {
+                parameters: p.clone(),
+                cutoff: Cutoff::new(&Default::default()),
+            };
+            let x = arr1(&[Dual64::from(1.0)]);
+            let mie = MiePotential::new(rep, 6.0);
+            for t in [1.0, 2.0, 4.0] {
+                // the u-fraction does not contribute to the second virial coefficient
+                let b2bar =
+                    residual_virial_coefficient(&p, &x, Dual64::from(t).derive(), &pt.cutoff);
+                let b2_att = virial_coefficient(&pt, t);
+                assert_relative_eq!(b2_att.re, b2bar.re, max_relative = 1e-12);
+                assert_relative_eq!(b2_att.eps[0], b2bar.eps[0], max_relative = 1e-12);
+
+                // together with the reference, it reproduces the Mie fluid
+                let b2 = b2_att + virial_coefficient(&hs, t) + virial_coefficient(&rf, t);
+                let b2_mie = mie.second_virial_coefficient(Dual64::from(t).derive());
+                assert_relative_eq!(b2.re, b2_mie.re, epsilon = 5e-2);
+                assert_relative_eq!(b2.eps[0], b2_mie.eps[0], epsilon = 5e-2);
+            }
+        }
+    }
 }
@@ -0,0 +1,364 @@
+use super::attractive_perturbation_wca::{
+    correlation_integral_wca, delta_b12u, one_fluid_properties, C_WCA,
+};
+use super::hard_sphere_wca::{
+    diameter_wca, dimensionless_diameter_q_wca, dimensionless_diameter_q_wca_ij,
+    WCA_CONSTANTS_ETA_A_UVB3, WCA_CONSTANTS_ETA_B_UVB3,
+};
+use super::segment_state;
+use crate::parameters::*;
+use feos_core::{HelmholtzEnergyDual, StateHD};
+use ndarray::Array1;
+use num_dual::DualNum;
+use std::{f64::consts::PI, fmt, sync::Arc};
+
+/// Constants for the residual second virial coefficient (revised series approximation).
+const C_B2_RSAP: [[f64; 4]; 4] = [
+    [-0.063550989, 6.206829830, -37.45829549, 40.72849774],
+    [1.519053409, 13.14989643, 85.35058674, 374.1906360],
+    [0.693456220, 9.459946180, -53.28984218, 315.8199084],
+    [0.007492596, 0.546171170, 7.979562575, -119.6126395],
+];
+
+/// Constants for the third virial coefficient of Mie n-6 fluids.
+const K_LJ_B3: [f64; 16] = [
+    -3.9806, 79.565, 0.5489, 5.3632, 1.4245, 57.292, 0.0, 1.0031, -39.755, -81.213, 0.6987, 30.156,
+    -23.692, -85.006, 0.7762, 12.798,
+];
+const P_B3: [f64; 4] = [0.80844, -0.09541, 0.47525, -2.83283];
+const L_B3: [f64; 4] = [4.9485, -21.3, 7.0, 3.2162];
+const M_B3: [f64; 4] = [0.11853, 0.078556, -0.55039, 0.009163];
+
+/// Constants for the u-fraction of uv-B3-theory.
+const CU_WCA: [f64; 8] = [26.454, 1.8045, 1.7997, 161.96, 11.605, 12., 0.4, 2.0];
+
+/// Attractive perturbation of uv-B3-theory (WCA division).
+///
+/// In addition to the second virial coefficient, the low density limit is
+/// corrected with the third virial coefficient of Mie n-6 fluids
+/// (van Westen and Gross, 2021).
+#[derive(Debug, Clone)]
+pub struct AttractivePerturbationUVB3 {
+    pub parameters: Arc<UVParameters>,
+}
+
+impl fmt::Display for AttractivePerturbationUVB3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Attractive Perturbation")
+    }
+}
+
+impl<D: DualNum<f64>> HelmholtzEnergyDual<D> for AttractivePerturbationUVB3 {
+    /// Helmholtz energy for attractive perturbation
+    fn helmholtz_energy(&self, state: &StateHD<D>) -> D {
+        let state = &segment_state(&self.parameters, state);
+        let p = &self.parameters;
+        let x = &state.molefracs;
+        let t = state.temperature;
+        let density = state.partial_density.sum();
+        let d = diameter_wca(p, t);
+
+        // vdw effective one fluid properties
+        let (rep_x, att_x, sigma_x, weighted_sigma3_ij, epsilon_k_x, d_x) =
+            one_fluid_properties(p, x, t);
+        let t_x = state.temperature / epsilon_k_x;
+        let rho_x = density * sigma_x.powi(3);
+        let rm_x = (rep_x / att_x).powd((rep_x - att_x).recip());
+        let mean_field_constant_x = mean_field_constant(rep_x, att_x, rm_x);
+        let q_vdw = dimensionless_diameter_q_wca(t_x, rep_x, att_x);
+        let i_wca = correlation_integral_wca(
+            rho_x,
+            mean_field_constant_x,
+            D::one(),
+            rep_x,
+            att_x,
+            d_x,
+            q_vdw,
+            rm_x,
+        );
+        let delta_a1u = density / t_x * i_wca * 2.0 * PI * weighted_sigma3_ij;
+
+        let u_fraction = u_fraction_uvb3(rep_x, rho_x, t_x);
+
+        let b21u = delta_b12u(
+            t_x,
+            mean_field_constant_x,
+            D::one(),
+            weighted_sigma3_ij,
+            q_vdw,
+            rm_x,
+        );
+        let b2bar = residual_virial_coefficient(p, x, t);
+
+        let b31u = delta_b31u(t_x, weighted_sigma3_ij, rm_x, rep_x, att_x, d_x);
+        let b3bar = residual_third_virial_coefficient(p, x, t, &d);
+        let alpha = (-rho_x / rep_x * CU_WCA[0] * (t_x.powi(2).recip() * CU_WCA[1] + 1.0)).exp();
+
+        state.moles.sum()
+            * (delta_a1u
+                + (-u_fraction + 1.0)
+                    * ((b2bar - b21u) * density + density.powi(2) * alpha * (b3bar - b31u) * 0.5))
+    }
+}
+
+fn residual_virial_coefficient<D: DualNum<f64>>(p: &UVParameters, x: &Array1<D>, t: D) -> D {
+    let effective = p.effective_parameters(t);
+    let mut delta_b2bar = D::zero();
+    for i in 0..p.ncomponents {
+        for j in 0..p.ncomponents {
+            let t_ij = t / effective.eps_k_ij(i, j);
+            let q_ij = dimensionless_diameter_q_wca_ij(p, i, j, t_ij);
+            delta_b2bar += x[i]
+                * x[j]
+                * effective.sigma_ij(i, j).powi(3)
+                * delta_b2(t_ij, p.rep_ij[[i, j]], p.rm_ij[[i, j]], q_ij);
+        }
+    }
+    delta_b2bar
+}
+
+/// Residual third virial coefficient of the mixture.
+///
+/// The coefficient of a triplet `ijk` is approximated by the arithmetic mean of the
+/// pair coefficients `(B_ij + B_ik + B_jk) / 3`, so that the sum over all triplets
+/// reduces to a sum over pairs.
+fn residual_third_virial_coefficient<D: DualNum<f64>>(
+    p: &UVParameters,
+    x: &Array1<D>,
+    t: D,
+    d: &Array1<D>,
+) -> D {
+    let effective = p.effective_parameters(t);
+    let mut delta_b3bar = D::zero();
+    for i in 0..p.ncomponents {
+        for j in 0..p.ncomponents {
+            let t_ij = t / effective.eps_k_ij(i, j);
+            let rep_ij = p.rep_ij[[i, j]];
+            let rm_ij = p.rm_ij[[i, j]];
+            let q_ij = dimensionless_diameter_q_wca_ij(p, i, j, t_ij);
+            let d_ij = (d[i] / effective.sigma(i) + d[j] / effective.sigma(j)) * 0.5;
+            delta_b3bar += x[i]
+                * x[j]
+                * effective.sigma_ij(i, j).powi(6)
+                * delta_b3(t_ij, rm_ij, rep_ij, d_ij, q_ij);
+        }
+    }
+    delta_b3bar
+}
+
+/// U-fraction with low temperature correction.
+fn u_fraction_uvb3<D: DualNum<f64>>(rep_x: D, reduced_density: D, t_x: D) -> D {
+    // the correction vanishes at high temperatures (and coth overflows)
+    let omega = if t_x.re() < 175.0 {
+        (-t_x * CU_WCA[5] * (reduced_density - CU_WCA[6]).powi(2)).exp()
+            * ((t_x * CU_WCA[7]).tanh().recip() - 1.0).powi(2)
+    } else {
+        D::zero()
+    };
+    -(-(reduced_density.powi(2) * ((rep_x + CU_WCA[4]).recip() * CU_WCA[3] + CU_WCA[2]) + omega))
+        .exp()
+        + 1.0
+}
+
+fn factorial(n: u64) -> u64 {
+    (1..=n).product()
+}
+
+/// Residual second virial coefficient from the revised series approximation (RSAP).
+fn delta_b2<D: DualNum<f64>>(reduced_temperature: D, rep: f64, rm: f64, q: D) -> D {
+    let beta = reduced_temperature.recip();
+    let b20 = q.powi(3) * 2.0 / 3.0 * PI;
+    let y = beta.exp() - 1.0;
+    let c = C_B2_RSAP.map(|c| c[0] + c[1] / rep + c[2] / rep.powi(2) + c[3] / rep.powi(3));
+
+    let mut sum_beta = beta;
+    for i in 2..16 {
+        let k = factorial(i as u64) as f64 * i as f64;
+        sum_beta += beta.powi(i) / k
+    }
+
+    (b20 - rm.powi(3) * 2.0 / 3.0 * PI - c[0]) * y
+        - sum_beta * c[1]
+        - beta * c[2]
+        - beta.powi(2) * c[3]
+}
+
+fn delta_b31u<D: DualNum<f64>>(
+    t_x: D,
+    weighted_sigma3_ij: D,
+    rm_x: D,
+    rep_x: D,
+    att_x: D,
+    d_x: D,
+) -> D {
+    let nu = rep_x;
+    let tau = rm_x - d_x;
+    let k1 = nu.recip() * C_WCA[0][1]
+        + nu.powi(2).recip() * C_WCA[0][2]
+        + C_WCA[0][0]
+        + (nu.recip() * C_WCA[0][4] + nu.powi(2).recip() * C_WCA[0][5] + C_WCA[0][3]) * tau;
+    t_x.recip() * 4.0 * mie_prefactor(rep_x, att_x) * PI * k1 * weighted_sigma3_ij.powi(2)
+}
+
+/// Third virial coefficient of the Mie n-6 fluid (in units of sigma^6).
+fn third_virial_coefficient<D: DualNum<f64>>(t_x: D, rep_x: f64, q_x: D) -> D {
+    let beta = t_x.recip();
+    let b30 = (q_x.powi(3) * PI / 6.0).powi(2) * 10.0;
+
+    let b3k = |k: usize| {
+        let k_lj = &K_LJ_B3[4 * k..4 * k + 4];
+        (t_x + k_lj[2])
+            .powf(((rep_x - 12.0) / (rep_x - 6.0) * M_B3[k] + 1.0) * k_lj[3])
+            .recip()
+            * k_lj[1]
+            * ((rep_x - 12.0) / (rep_x - L_B3[k]) * P_B3[k] + 1.0)
+            + k_lj[0]
+    };
+    b30 + b3k(0) * beta + b3k(1) * beta.powi(2) + b3k(2) * beta.powi(3) + b3k(3) * beta.powi(4)
+}
+
+/// Third virial coefficient of the Mie n-6 fluid relative to the one of the reference fluid.
+fn delta_b3<D: DualNum<f64>>(t_x: D, rm_x: f64, rep_x: f64, d_x: D, q_x: D) -> D {
+    let b3 = third_virial_coefficient(t_x, rep_x, q_x);
+
+    // third virial coefficient of the reference fluid
+    let tau = -d_x + rm_x;
+    let tau2 = tau * tau;
+    let rep_inv = rep_x.recip();
+    let c1_eta_a = tau
+        * (rep_inv * WCA_CONSTANTS_ETA_A_UVB3[[0, 1]] + WCA_CONSTANTS_ETA_A_UVB3[[0, 0]])
+        + tau2 * (rep_inv * WCA_CONSTANTS_ETA_A_UVB3[[0, 3]] + WCA_CONSTANTS_ETA_A_UVB3[[0, 2]]);
+    let c1_eta_b = tau * WCA_CONSTANTS_ETA_B_UVB3[[0, 0]] + tau2 * WCA_CONSTANTS_ETA_B_UVB3[[0, 1]];
+    let b30_uv = (d_x.powi(3) * PI / 6.0).powi(2) * 10.0
+        - d_x.powi(3) * 5.0 / 9.0
+            * PI.powi(2)
+            * ((-q_x.powi(3) + rm_x.powi(3)) * (c1_eta_a + 1.0)
+                - (-d_x.powi(3) + rm_x.powi(3)) * (c1_eta_b + 1.0));
+
+    b3 - b30_uv
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::eos::{UVTheory, UVTheoryOptions, VirialOrder};
+    use crate::parameters::utils::{methane_parameters, test_parameters};
+    use crate::MiePotential;
+    use approx::assert_relative_eq;
+    use feos_core::EquationOfState;
+    use ndarray::arr1;
+    use quantity::si::*;
+
+    #[test]
+    fn test_attractive_perturbation_uvb3() {
+        // regression values of the individual terms of this implementation; the
+        // low density limit is compared to literature data in
+        // `virial_coefficients_lennard_jones`
+        let moles = arr1(&[2.0]);
+        let reduced_temperature = 4.0;
+        let reduced_density = 0.5;
+        let reduced_volume = moles[0] / reduced_density;
+
+        let p = methane_parameters(12.0, 6.0);
+        let pt = AttractivePerturbationUVB3 {
+            parameters: Arc::new(p.clone()),
+        };
+        let state = StateHD::new(
+            reduced_temperature * p.epsilon_k[0],
+            reduced_volume * p.sigma[0].powi(3),
+            moles.clone(),
+        );
+        let x = &state.molefracs;
+        let (rep_x, att_x, sigma_x, weighted_sigma3_ij, epsilon_k_x, d_x) =
+            one_fluid_properties(&p, x, state.temperature);
+        let t_x = state.temperature / epsilon_k_x;
+        let rho_x = state.partial_density.sum() * sigma_x.powi(3);
+        let rm_x = (rep_x / att_x).powd((rep_x - att_x).recip());
+        let mean_field_constant_x = mean_field_constant(rep_x, att_x, rm_x);
+
+        // effective diameters
+        let q_vdw = dimensionless_diameter_q_wca(t_x, rep_x, att_x);
+        assert_relative_eq!(q_vdw, 0.9606854684075393, epsilon = 1e-10);
+        assert_relative_eq!(d_x, 0.934655265184067, epsilon = 1e-10);
+
+        // u-fraction
+        let u_fraction = u_fraction_uvb3(rep_x, rho_x, t_x);
+        assert_relative_eq!(u_fraction, 0.8852775506870431, epsilon = 1e-10);
+
+        // delta a1u
+        let i_wca = correlation_integral_wca(
+            rho_x,
+            mean_field_constant_x,
+            1.0,
+            rep_x,
+            att_x,
+            d_x,
+            q_vdw,
+            rm_x,
+        );
+        let delta_a1u = state.partial_density.sum() / t_x * i_wca * 2.0 * PI * weighted_sigma3_ij;
+        assert_relative_eq!(delta_a1u, -0.8992910890819197, epsilon = 1e-12);
+
+        // virial coefficients
+        let b2bar = residual_virial_coefficient(&p, x, state.temperature) / p.sigma[0].powi(3);
+        assert_relative_eq!(b2bar, -1.6142316456384618, epsilon = 1e-12);
+        let b21u = delta_b12u(
+            t_x,
+            mean_field_constant_x,
+            1.0,
+            weighted_sigma3_ij,
+            q_vdw,
+            rm_x,
+        ) / p.sigma[0].powi(3);
+        assert_relative_eq!(b21u, -1.5103749286162982, epsilon = 1e-10);
+        let db3 = delta_b3(t_x, rm_x, rep_x, d_x, q_vdw);
+        assert_relative_eq!(db3, -0.6591980196661884, epsilon = 1e-10);
+
+        // full attractive perturbation
+        let a = pt.helmholtz_energy(&state) / moles[0];
+        assert_relative_eq!(a, -0.9027781694834115, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn third_virial_coefficient_exact() {
+        // the correlation reproduces the third virial coefficient of Mie n-6 fluids
+        for rep in [12.0, 20.0] {
+            for t_x in [1.0, 2.0, 4.0] {
+                let q = dimensionless_diameter_q_wca(t_x, rep, 6.0);
+                let b3 = third_virial_coefficient(t_x, rep, q);
+                let b3_exact = MiePotential::new(rep, 6.0).third_virial_coefficient(t_x);
+                assert_relative_eq!(b3, b3_exact, max_relative = 2e-2);
+            }
+        }
+    }
+
+    #[test]
+    fn virial_coefficients_lennard_jones() {
+        // B2 / b0 and B3 / b0^2 of the Lennard-Jones fluid with b0 = 2 pi / 3 sigma^3
+        // (Hirschfelder, Curtiss and Bird, Molecular Theory of Gases and Liquids
+        // (1954), Tables I-B and I-C)
+        let reference = [(1.0, -2.53809, 0.4297), (2.0, -0.62763, 0.4371)];
+        let options = UVTheoryOptions {
+            virial_order: VirialOrder::Third,
+            ..Default::default()
+        };
+        let eos = UVTheory::with_options(Arc::new(test_parameters(12.0, 6.0, 1.0, 1.0)), options)
+            .unwrap();
+        let b0 = 2.0 * PI / 3.0;
+        let b_unit = ANGSTROM.powi(3) * NAV;
+        for (t, b2, b3) in reference {
+            let b2_eos = eos
+                .second_virial_coefficient(t * KELVIN, None)
+                .unwrap()
+                .to_reduced(b_unit)
+                .unwrap();
+            let b3_eos = eos
+                .third_virial_coefficient(t * KELVIN, None)
+                .unwrap()
+                .to_reduced(b_unit * b_unit)
+                .unwrap();
+            assert_relative_eq!(b2_eos / b0, b2, max_relative = 1e-3);
+            assert_relative_eq!(b3_eos / b0.powi(2), b3, max_relative = 1e-2);
+        }
+    }
+}
@@ -1,11 +1,14 @@
-use super::hard_sphere_wca::{diameter_wca, dimensionless_diameter_q_wca};
+use super::hard_sphere_wca::{
+    diameter_wca, dimensionless_diameter_q_wca, dimensionless_diameter_q_wca_ij,
+};
+use super::{segment_state, Cutoff, MEAN_FIELD_RADIUS};
 use crate::parameters::*;
 use feos_core::{HelmholtzEnergyDual, StateHD};
 use ndarray::Array1;
 use num_dual::DualNum;
-use std::{f64::consts::PI, fmt, rc::Rc};
+use std::{f64::consts::PI, fmt, sync::Arc};
 
-const C_WCA: [[f64; 6]; 6] = [
+pub(super) const C_WCA: [[f64; 6]; 6] = [
     [
         -0.2622378162,
         0.6585817423,
@@ -68,7 +71,8 @@ const C2: [[f64; 2]; 3] = [
 
 #[derive(Debug, Clone)]
 pub struct AttractivePerturbationWCA {
-    pub parameters: Rc<UVParameters>,
+    pub parameters: Arc<UVParameters>,
+    pub cutoff: Cutoff,
 }
 
 impl fmt::Display for AttractivePerturbationWCA {
@@ -80,6 +84,7 @@ impl fmt::Display for AttractivePerturbationWCA {
 impl<D: DualNum<f64>> HelmholtzEnergyDual<D> for AttractivePerturbationWCA {
     /// Helmholtz energy for attractive perturbation, eq. 52
     fn helmholtz_energy(&self, state: &StateHD<D>) -> D {
+        let state = &segment_state(&self.parameters, state);
         let p = &self.parameters;
         let x = &state.molefracs;
         let t = state.temperature;
@@ -91,63 +96,80 @@ impl<D: DualNum<f64>> HelmholtzEnergyDual<D> for AttractivePerturbationWCA {
         let t_x = state.temperature / epsilon_k_x;
         let rho_x = density * sigma_x.powi(3);
         let rm_x = (rep_x / att_x).powd((rep_x - att_x).recip());
-        let mean_field_constant_x = mean_field_constant(rep_x, att_x, rm_x);
+        let mean_field_constant_x = self.cutoff.mean_field_constant(rep_x, att_x, rm_x);
+        let well_depth_x = self.cutoff.shift(rep_x, att_x) + 1.0;
         let q_vdw = dimensionless_diameter_q_wca(t_x, rep_x, att_x);
-        let i_wca =
-            correlation_integral_wca(rho_x, mean_field_constant_x, rep_x, att_x, d_x, q_vdw, rm_x);
+        let i_wca = correlation_integral_wca(
+            rho_x,
+            mean_field_constant_x,
+            well_depth_x,
+            rep_x,
+            att_x,
+            d_x,
+            q_vdw,
+            rm_x,
+        );
 
         let delta_a1u = state.partial_density.sum() / t_x * i_wca * 2.0 * PI * weighted_sigma3_ij;
 
         //                 state.partial_density.sum() / t_x * i_wca * 2.0 * PI * weighted_sigma3_ij;
-        let u_fraction_wca =
-            u_fraction_wca(rep_x, density * (x * &p.sigma.mapv(|s| s.powi(3))).sum());
-
-        let b21u = delta_b12u(t_x, mean_field_constant_x, weighted_sigma3_ij, q_vdw, rm_x);
-        let b2bar = residual_virial_coefficient(p, x, state.temperature);
+        let u_fraction_wca = u_fraction_wca(rep_x, rho_x);
+
+        let b21u = delta_b12u(
+            t_x,
+            mean_field_constant_x,
+            well_depth_x,
+            weighted_sigma3_ij,
+            q_vdw,
+            rm_x,
+        );
+        let b2bar = residual_virial_coefficient(p, x, state.temperature, &self.cutoff);
 
         state.moles.sum() * (delta_a1u + (-u_fraction_wca + 1.0) * (b2bar - b21u) * density)
     }
 }
 
 // (S43) & (S53)
-fn delta_b12u<D: DualNum<f64>>(
+pub(super) fn delta_b12u<D: DualNum<f64>>(
     t_x: D,
     mean_field_constant_x: D,
+    well_depth_x: D,
     weighted_sigma3_ij: D,
     q_x: D,
     rm_x: D,
 ) -> D {
-    (-mean_field_constant_x - (rm_x.powi(3) - q_x.powi(3)) * 1.0 / 3.0) / t_x
+    (-mean_field_constant_x - (rm_x.powi(3) - q_x.powi(3)) * well_depth_x / 3.0) / t_x
         * 2.0
         * PI
         * weighted_sigma3_ij
 }
 
-fn residual_virial_coefficient<D: DualNum<f64>>(p: &UVParameters, x: &Array1<D>, t: D) -> D {
+fn residual_virial_coefficient<D: DualNum<f64>>(
+    p: &UVParameters,
+    x: &Array1<D>,
+    t: D,
+    cutoff: &Cutoff,
+) -> D {
+    let effective = p.effective_parameters(t);
     let mut delta_b2bar = D::zero();
 
     for i in 0..p.ncomponents {
-        let xi = x[i];
-
         for j in 0..p.ncomponents {
-            //let q_ij = (q[i] / p.sigma[i] + q[j] / p.sigma[j]) * 0.5;
-            let t_ij = t / p.eps_k_ij[[i, j]];
-            let rep_ij = p.rep_ij[[i, j]];
-            let att_ij = p.att_ij[[i, j]];
-
-            let q_ij = dimensionless_diameter_q_wca(t_ij, D::from(rep_ij), D::from(att_ij));
-
-            // Recheck mixing rule!
-            delta_b2bar +=
-                xi * x[j] * p.sigma_ij[[i, j]].powi(3) * delta_b2(t_ij, rep_ij, att_ij, q_ij);
+            let t_ij = t / effective.eps_k_ij(i, j);
+            let q_ij = dimensionless_diameter_q_wca_ij(p, i, j, t_ij);
+            delta_b2bar += x[i]
+                * x[j]
+                * effective.sigma_ij(i, j).powi(3)
+                * delta_b2(p, i, j, t_ij, q_ij, cutoff);
         }
     }
     delta_b2bar
 }
 
-fn correlation_integral_wca<D: DualNum<f64>>(
+pub(super) fn correlation_integral_wca<D: DualNum<f64>>(
     rho_x: D,
     mean_field_constant_x: D,
+    well_depth_x: D,
     rep_x: D,
     att_x: D,
     d_x: D,
@@ -167,7 +189,7 @@ fn correlation_integral_wca<D: DualNum<f64>>(
     // dbg!(c[4].re());
     // dbg!(d_x.re());
 
-    (q_x.powi(3) - rm_x.powi(3)) * 1.0 / 3.0 - mean_field_constant_x
+    (q_x.powi(3) - rm_x.powi(3)) * well_depth_x / 3.0 - mean_field_constant_x
         + mie_prefactor(rep_x, att_x) * (c[0] * rho_x + c[1] * rho_x.powi(2) + c[2] * rho_x.powi(3))
             / (c[3] * rho_x + c[4] * rho_x.powi(2) + c[5] * rho_x.powi(3) + 1.0)
 }
@@ -180,12 +202,13 @@ fn u_fraction_wca<D: DualNum<f64>>(rep_x: D, reduced_density: D) -> D {
         .tanh()
 }
 
-fn one_fluid_properties<D: DualNum<f64>>(
+pub(super) fn one_fluid_properties<D: DualNum<f64>>(
     p: &UVParameters,
     x: &Array1<D>,
     t: D,
 ) -> (D, D, D, D, D, D) {
     let d = diameter_wca(p, t);
+    let effective = p.effective_parameters(t);
     // &p.sigma;
 
     let mut epsilon_k = D::zero();
@@ -199,9 +222,9 @@ fn one_fluid_properties<D: DualNum<f64>>(
 
         d_x_3 += x[i] * d[i].powi(3);
         for j in 0..p.ncomponents {
-            let _y = xi * x[j] * p.sigma_ij[[i, j]].powi(3);
+            let _y = xi * x[j] * effective.sigma_ij(i, j).powi(3);
             weighted_sigma3_ij += _y;
-            epsilon_k += _y * p.eps_k_ij[[i, j]];
+            epsilon_k += _y * effective.eps_k_ij(i, j);
 
             rep += xi * x[j] * p.rep_ij[[i, j]];
             att += xi * x[j] * p.att_ij[[i, j]];
@@ -209,7 +232,10 @@ fn one_fluid_properties<D: DualNum<f64>>(
     }
 
     //let dx = (x * &d.mapv(|v| v.powi(3))).sum().powf(1.0 / 3.0);
-    let sigma_x = (x * &p.sigma.mapv(|v| v.powi(3))).sum().powf(1.0 / 3.0);
+    let sigma_x = (0..p.ncomponents)
+        .map(|i| x[i] * effective.sigma(i).powi(3))
+        .sum::<D>()
+        .powf(1.0 / 3.0);
     let dx = d_x_3.powf(1.0 / 3.0) / sigma_x;
 
     (
@@ -255,21 +281,39 @@ fn coefficients_wca<D: DualNum<f64>>(rep: D, att: D, d: D) -> [D; 6] {
     [c1, c2, c3, c4, c5, c6]
 }
 
-fn delta_b2<D: DualNum<f64>>(reduced_temperature: D, rep: f64, att: f64, q: D) -> D {
-    let rm = (rep / att).powf(1.0 / (rep - att)); // Check mixing rule!!
-    let rc = 5.0;
-    let alpha = mean_field_constant(rep, att, rc);
+fn delta_b2<D: DualNum<f64>>(
+    p: &UVParameters,
+    i: usize,
+    j: usize,
+    reduced_temperature: D,
+    q: D,
+    cutoff: &Cutoff,
+) -> D {
+    let (rep, att) = (p.rep_ij[[i, j]], p.att_ij[[i, j]]);
+    let rm = p.rm_ij[[i, j]];
+    let rc = MEAN_FIELD_RADIUS;
+    let alpha = p.mean_field_constant_rc_ij[[i, j]];
     let beta = reduced_temperature.recip();
     let y = beta.exp() - 1.0;
-    let yeff = y_eff(reduced_temperature, rep, att);
-    -(yeff * (rc.powi(3) - rm.powi(3)) / 3.0 + y * (-q.powi(3) + rm.powi(3)) / 3.0 + beta * alpha)
+    let yeff = y_eff(reduced_temperature, &p.y_eff_wca_ij[[i, j]]);
+    let delta_b2 = -(yeff * (rc.powi(3) - rm.powi(3)) / 3.0
+        + y * (-q.powi(3) + rm.powi(3)) / 3.0
+        + beta * alpha)
         * 2.0
-        * PI
+        * PI;
+    cutoff.delta_b2(
+        delta_b2,
+        reduced_temperature,
+        rep,
+        att,
+        q.powi(3) * 2.0 * PI / 3.0,
+    )
 }
 
-fn y_eff<D: DualNum<f64>>(reduced_temperature: D, rep: f64, att: f64) -> D {
-    // optimize: move this part to parameter initialization
-    let rc = 5.0;
+/// Temperature independent constants of the effective inverse temperature
+/// of a pair with the given exponents.
+pub(crate) fn y_eff_constants(rep: f64, att: f64) -> [f64; 4] {
+    let rc = MEAN_FIELD_RADIUS;
     let rs = (rep / att).powf(1.0 / (rep - att));
     let c0 = 1.0
         - 3.0 * (mean_field_constant(rep, att, rs) - mean_field_constant(rep, att, rc))
@@ -277,6 +321,11 @@ fn y_eff<D: DualNum<f64>>(reduced_temperature: D, rep: f64, att: f64) -> D {
     let c1 = C2[0][0] + C2[0][1] / rep;
     let c2 = C2[1][0] + C2[1][1] / rep;
     let c3 = C2[2][0] + C2[2][1] / rep;
+    [c0, c1, c2, c3]
+}
+
+fn y_eff<D: DualNum<f64>>(reduced_temperature: D, constants: &[f64; 4]) -> D {
+    let [c0, c1, c2, c3] = *constants;
 
     //exponents
     let a = 1.05968091375869;
@@ -306,7 +355,8 @@ mod test {
 
         let p = methane_parameters(24.0, 6.0);
         let pt = AttractivePerturbationWCA {
-            parameters: Rc::new(p.clone()),
+            parameters: Arc::new(p.clone()),
+            cutoff: Cutoff::new(&Default::default()),
         };
         let state = StateHD::new(
             reduced_temperature * p.epsilon_k[0],
@@ -324,13 +374,27 @@ mod test {
         let mean_field_constant_x = mean_field_constant(rep_x, att_x, rm_x);
         dbg!(t_x);
         let q_vdw = dimensionless_diameter_q_wca(t_x, rep_x, att_x);
-        let b21u = delta_b12u(t_x, mean_field_constant_x, weighted_sigma3_ij, q_vdw, rm_x)
-            / p.sigma[0].powi(3);
+        let b21u = delta_b12u(
+            t_x,
+            mean_field_constant_x,
+            1.0,
+            weighted_sigma3_ij,
+            q_vdw,
+            rm_x,
+        ) / p.sigma[0].powi(3);
         //assert!(b21u.re() == -1.02233216);
         assert_relative_eq!(b21u.re(), -1.02233215790525, epsilon = 1e-12);
 
-        let i_wca =
-            correlation_integral_wca(rho_x, mean_field_constant_x, rep_x, att_x, d_x, q_vdw, rm_x);
+        let i_wca = correlation_integral_wca(
+            rho_x,
+            mean_field_constant_x,
+            1.0,
+            rep_x,
+            att_x,
+            d_x,
+            q_vdw,
+            rm_x,
+        );
 
         let delta_a1u = state.partial_density.sum() / t_x * i_wca * 2.0 * PI * weighted_sigma3_ij;
 
@@ -343,7 +407,12 @@ mod test {
             state.partial_density.sum() * (x * &p.sigma.mapv(|s| s.powi(3))).sum(),
         );
 
-        let b2bar = residual_virial_coefficient(&p, x, state.temperature) / p.sigma[0].powi(3);
+        let b2bar = residual_virial_coefficient(
+            &p,
+            x,
+            state.temperature,
+            &Cutoff::new(&Default::default()),
+        ) / p.sigma[0].powi(3);
         dbg!(b2bar);
         assert_relative_eq!(b2bar.re(), -1.09102560732964, epsilon = 1e-12);
         dbg!(u_fraction_wca);
@@ -395,15 +464,30 @@ mod test {
 
         let q_vdw = dimensionless_diameter_q_wca(t_x, rep_x, att_x);
         dbg!(q_vdw.re());
-        let delta_b21u = delta_b12u(t_x, mean_field_constant_x, weighted_sigma3_ij, q_vdw, rm_x);
+        let delta_b21u = delta_b12u(
+            t_x,
+            mean_field_constant_x,
+            1.0,
+            weighted_sigma3_ij,
+            q_vdw,
+            rm_x,
+        );
         dbg!(delta_b21u);
         assert_relative_eq!(delta_b21u, -3.9309384983526585, epsilon = 1e-6);
 
         // delta a1u
         let rho_x = state.partial_density.sum() * sigma_x.powi(3);
 
-        let i_wca =
-            correlation_integral_wca(rho_x, mean_field_constant_x, rep_x, att_x, d_x, q_vdw, rm_x);
+        let i_wca = correlation_integral_wca(
+            rho_x,
+            mean_field_constant_x,
+            1.0,
+            rep_x,
+            att_x,
+            d_x,
+            q_vdw,
+            rm_x,
+        );
 
         let delta_a1u = state.partial_density.sum() / state.temperature
             * i_wca
@@ -416,14 +500,19 @@ mod test {
 
         // Second virial coefficient
 
-        let delta_b2 = residual_virial_coefficient(&p, &state.molefracs, state.temperature)
-            / p.sigma[0].powi(3);
+        let delta_b2 = residual_virial_coefficient(
+            &p,
+            &state.molefracs,
+            state.temperature,
+            &Cutoff::new(&Default::default()),
+        ) / p.sigma[0].powi(3);
 
         dbg!(delta_b2);
         assert_relative_eq!(delta_b2, -4.7846399638747954, epsilon = 1e-6);
         // Full attractive contribution
         let pt = AttractivePerturbationWCA {
-            parameters: Rc::new(p),
+            parameters: Arc::new(p),
+            cutoff: Cutoff::new(&Default::default()),
         };
 
         let a = pt.helmholtz_energy(&state) / (moles[0] + moles[1]);
@@ -454,7 +543,12 @@ mod test {
         assert_relative_eq!(phi_u, 0.89210738762113795, epsilon = 1e-5);
         // delta b2
 
-        let b2bar = residual_virial_coefficient(&p, x, state.temperature) / p.sigma[0].powi(3);
+        let b2bar = residual_virial_coefficient(
+            &p,
+            x,
+            state.temperature,
+            &Cutoff::new(&Default::default()),
+        ) / p.sigma[0].powi(3);
         assert_relative_eq!(b2bar.re(), -12.106977583257606, epsilon = 1e-12);
 
         //delta b21u
@@ -462,7 +556,14 @@ mod test {
         let mean_field_constant_x = mean_field_constant(rep_x, att_x, rm_x);
         let t_x = state.temperature / epsilon_k_x;
         let q_vdw = dimensionless_diameter_q_wca(t_x, rep_x, att_x);
-        let delta_b21u = delta_b12u(t_x, mean_field_constant_x, weighted_sigma3_ij, q_vdw, rm_x);
+        let delta_b21u = delta_b12u(
+            t_x,
+            mean_field_constant_x,
+            1.0,
+            weighted_sigma3_ij,
+            q_vdw,
+            rm_x,
+        );
         assert_relative_eq!(delta_b21u, -10.841841323394299, epsilon = 1e-6);
 
         let a_ufrac = (-phi_u + 1.0) * (b2bar - delta_b21u) * density;
@@ -474,8 +575,16 @@ mod test {
         let rho_x = state.partial_density.sum() * sigma_x.powi(3);
         assert_relative_eq!(d_x, 0.95196953178057431, epsilon = 1e-6);
 
-        let i_wca =
-            correlation_integral_wca(rho_x, mean_field_constant_x, rep_x, att_x, d_x, q_vdw, rm_x);
+        let i_wca = correlation_integral_wca(
+            rho_x,
+            mean_field_constant_x,
+            1.0,
+            rep_x,
+            att_x,
+            d_x,
+            q_vdw,
+            rm_x,
+        );
         dbg!(weighted_sigma3_ij.re());
         dbg!(epsilon_k_x);
         let delta_a1u = state.partial_density.sum() / state.temperature
@@ -489,7 +598,8 @@ mod test {
 
         // Full attractive contribution
         let pt = AttractivePerturbationWCA {
-            parameters: Rc::new(p),
+            parameters: Arc::new(p),
+            cutoff: Cutoff::new(&Default::default()),
         };
         let a = pt.helmholtz_energy(&state) / (moles[0] + moles[1]);
         assert_relative_eq!(a, -1.3318659166866607, epsilon = 1e-5);
@@ -0,0 +1,80 @@
+use super::hard_sphere_wca::zeta;
+use super::{hs_diameter, segment_state, Perturbation};
+use crate::parameters::UVParameters;
+use feos_core::{HelmholtzEnergyDual, StateHD};
+use ndarray::Array;
+use num_dual::DualNum;
+use std::fmt;
+use std::sync::Arc;
+
+/// Chain contribution according to first order thermodynamic perturbation theory.
+///
+/// The pair correlation function of the reference fluid at contact is
+/// approximated by the one of the hard-sphere fluid with the temperature
+/// dependent diameter of the chosen division.
+#[derive(Debug, Clone)]
+pub struct HardChain {
+    pub parameters: Arc<UVParameters>,
+    pub perturbation: Perturbation,
+}
+
+impl<D: DualNum<f64>> HelmholtzEnergyDual<D> for HardChain {
+    /// Helmholtz energy for hard chains, TPT1
+    fn helmholtz_energy(&self, state: &StateHD<D>) -> D {
+        let p = &self.parameters;
+        let d = hs_diameter(p, &self.perturbation, state.temperature);
+        let segments = segment_state(p, state);
+        let zeta = zeta(&segments.partial_density, &d);
+        let frac_1mz3 = -(zeta[3] - 1.0).recip();
+        let c = zeta[2] * frac_1mz3 * frac_1mz3;
+        let g_hs =
+            d.mapv(|d| frac_1mz3 + d * c * 1.5 - d.powi(2) * c.powi(2) * (zeta[3] - 1.0) * 0.5);
+        Array::from_shape_fn(p.ncomponents, |i| {
+            state.partial_density[i] * (1.0 - p.m[i]) * g_hs[i].ln()
+        })
+        .sum()
+            * state.volume
+    }
+}
+
+impl fmt::Display for HardChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Hard Chain")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parameters::UVRecord;
+    use approx::assert_relative_eq;
+    use feos_core::parameter::{Identifier, Parameter, PureRecord};
+    use ndarray::arr1;
+
+    #[test]
+    fn test_hard_chain_pure() {
+        let identifier = Identifier::new("1", None, None, None, None, None);
+        let model_record = UVRecord::new(12.0, 6.0, 1.0, 1.0).with_segments(3.0);
+        let pr = PureRecord::new(identifier, 1.0, model_record, None);
+        let p = Arc::new(UVParameters::new_pure(pr));
+        let moles = arr1(&[2.0]);
+        let state = StateHD::new(1.5, 4.0, moles.clone());
+        for perturbation in [
+            Perturbation::BarkerHenderson,
+            Perturbation::WeeksChandlerAndersen,
+        ] {
+            let hc = HardChain {
+                parameters: p.clone(),
+                perturbation: perturbation.clone(),
+            };
+            let d = hs_diameter(&p, &perturbation, 1.5)[0];
+            let eta = std::f64::consts::FRAC_PI_6 * 3.0 * 0.5 * d.powi(3);
+            let g_hs = (1.0 - 0.5 * eta) / (1.0 - eta).powi(3);
+            assert_relative_eq!(
+                hc.helmholtz_energy(&state) / moles[0],
+                -2.0 * g_hs.ln(),
+                max_relative = 1e-12
+            );
+        }
+    }
+}
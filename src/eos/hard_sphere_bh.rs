@@ -1,10 +1,11 @@
-use crate::parameters::UVParameters;
+use super::segment_state;
+use crate::parameters::{EffectiveParameters, UVParameters};
 use feos_core::{HelmholtzEnergyDual, StateHD};
 use lazy_static::lazy_static;
 use ndarray::prelude::*;
 use num_dual::DualNum;
 use std::fmt;
-use std::rc::Rc;
+use std::sync::Arc;
 
 lazy_static! {
     static ref BH_CONSTANTS_ETA_B: Array2<f64> = arr2(&[
@@ -22,21 +23,21 @@ lazy_static! {
 
 #[derive(Debug, Clone)]
 pub struct HardSphere {
-    pub parameters: Rc<UVParameters>,
+    pub parameters: Arc<UVParameters>,
 }
 
 impl<D: DualNum<f64>> HelmholtzEnergyDual<D> for HardSphere {
     /// Helmholtz energy for hard spheres, eq. 19 (check Volume)
     fn helmholtz_energy(&self, state: &StateHD<D>) -> D {
+        let state = &segment_state(&self.parameters, state);
         let d = diameter_bh(&self.parameters, state.temperature);
         let zeta = zeta(&state.partial_density, &d);
         let frac_1mz3 = -(zeta[3] - 1.0).recip();
         let zeta_23 = zeta_23(&state.molefracs, &d);
-        let a_hs = state.volume * 6.0 / std::f64::consts::PI
+        state.volume * 6.0 / std::f64::consts::PI
             * (zeta[1] * zeta[2] * frac_1mz3 * 3.0
                 + zeta[2].powi(2) * frac_1mz3.powi(2) * zeta_23
-                + (zeta[2] * zeta_23.powi(2) - zeta[0]) * (zeta[3] * (-1.0)).ln_1p());
-        a_hs
+                + (zeta[2] * zeta_23.powi(2) - zeta[0]) * (zeta[3] * (-1.0)).ln_1p())
     }
 }
 
@@ -50,26 +51,25 @@ impl fmt::Display for HardSphere {
 /// Eq. S23 and S24.
 ///
 pub fn diameter_bh<D: DualNum<f64>>(parameters: &UVParameters, temperature: D) -> Array1<D> {
-    parameters
-        .cd_bh_pure
-        .iter()
-        .enumerate()
-        .map(|(i, c)| {
-            let t = temperature / parameters.epsilon_k[i];
-            let d = t.powf(0.25) * c[1] + t.powf(0.75) * c[2] + t.powf(1.25) * c[3];
-            (t * c[0] + d * (t + 1.0).ln() + t.powi(2) * c[4] + 1.0)
-                .powf(-0.5 / parameters.rep[i] as f64)
-                * parameters.sigma[i]
-        })
-        .collect()
+    Array1::from_shape_fn(parameters.ncomponents, |i| {
+        diameter_bh_i(parameters, i, temperature)
+    })
+}
+
+/// Hard-sphere diameter of component `i` according to Barker-Henderson division.
+pub fn diameter_bh_i<D: DualNum<f64>>(parameters: &UVParameters, i: usize, temperature: D) -> D {
+    let (sigma, epsilon_k) = parameters.effective_parameters_i(i, temperature);
+    let c = &parameters.cd_bh_pure[i];
+    let t = temperature / epsilon_k;
+    let d = t.powf(0.25) * c[1] + t.powf(0.75) * c[2] + t.powf(1.25) * c[3];
+    (t * c[0] + d * (t + 1.0).ln() + t.powi(2) * c[4] + 1.0).powf(-0.5 / parameters.rep[i]) * sigma
 }
 
 pub fn zeta<D: DualNum<f64>>(partial_density: &Array1<D>, diameter: &Array1<D>) -> [D; 4] {
     let mut zeta: [D; 4] = [D::zero(), D::zero(), D::zero(), D::zero()];
     for i in 0..partial_density.len() {
-        for k in 0..4 {
-            zeta[k] = zeta[k]
-                + partial_density[i] * diameter[i].powi(k as i32) * (std::f64::consts::PI / 6.0);
+        for (k, z) in zeta.iter_mut().enumerate() {
+            *z += partial_density[i] * diameter[i].powi(k as i32) * (std::f64::consts::PI / 6.0);
         }
     }
     zeta
@@ -84,8 +84,8 @@ pub fn packing_fraction<D: DualNum<f64>>(partial_density: &Array1<D>, diameter:
 pub fn zeta_23<D: DualNum<f64>>(molefracs: &Array1<D>, diameter: &Array1<D>) -> D {
     let mut zeta: [D; 2] = [D::zero(), D::zero()];
     for i in 0..molefracs.len() {
-        for k in 0..2 {
-            zeta[k] = zeta[k] + molefracs[i] * diameter[i].powi((k + 2) as i32);
+        for (k, z) in zeta.iter_mut().enumerate() {
+            *z += molefracs[i] * diameter[i].powi((k + 2) as i32);
         }
     }
     zeta[0] / zeta[1]
@@ -100,48 +100,41 @@ pub fn zeta_23<D: DualNum<f64>>(molefracs: &Array1<D>, diameter: &Array1<D>) ->
 //  -diameter_bh(parameters, temperature) + 1.0
 //}
 
-pub fn packing_fraction_b<D: DualNum<f64>>(
-    parameters: &UVParameters,
+/// Dimensionless length scale `tau_ij = 1 - d_ij / sigma_ij` of the pair `ij`.
+#[inline]
+pub fn dimensionless_length_scale_ij<D: DualNum<f64>>(
+    effective: &EffectiveParameters<D>,
     diameter: &Array1<D>,
-    eta: D,
-) -> Array2<D> {
-    let n = parameters.att.len();
-    Array2::from_shape_fn((n, n), |(i, j)| {
-        let tau =
-            -(diameter[i] / parameters.sigma[i] + diameter[j] / parameters.sigma[j]) * 0.5 + 1.0; //dimensionless
-        let tau2 = tau * tau;
-        let c = arr1(&[
-            tau * BH_CONSTANTS_ETA_B[[0, 0]] + tau2 * BH_CONSTANTS_ETA_B[[0, 1]],
-            tau * BH_CONSTANTS_ETA_B[[1, 0]] + tau2 * BH_CONSTANTS_ETA_B[[1, 1]],
-            tau * BH_CONSTANTS_ETA_B[[2, 0]] + tau2 * BH_CONSTANTS_ETA_B[[2, 1]],
-        ]);
-        eta + eta * c[0] + eta * eta * c[1] + eta.powi(3) * c[2]
-    })
+    i: usize,
+    j: usize,
+) -> D {
+    -(diameter[i] / effective.sigma(i) + diameter[j] / effective.sigma(j)) * 0.5 + 1.0
 }
 
-pub fn packing_fraction_a<D: DualNum<f64>>(
-    parameters: &UVParameters,
-    diameter: &Array1<D>,
-    eta: D,
-) -> Array2<D> {
-    let n = parameters.att.len();
-    Array2::from_shape_fn((n, n), |(i, j)| {
-        let tau =
-            -(diameter[i] / parameters.sigma[i] + diameter[j] / parameters.sigma[j]) * 0.5 + 1.0; //dimensionless//-(diameter[i] + diameter[j]) * 0.5 + 1.0;
-        let tau2 = tau * tau;
-        let rep_inv = 1.0 / parameters.rep_ij[[i, j]];
-        let c = arr1(&[
-            tau * (BH_CONSTANTS_ETA_A[[0, 0]] + BH_CONSTANTS_ETA_A[[0, 1]] * rep_inv)
-                + tau2 * (BH_CONSTANTS_ETA_A[[0, 2]] + BH_CONSTANTS_ETA_A[[0, 3]] * rep_inv),
-            tau * (BH_CONSTANTS_ETA_A[[1, 0]] + BH_CONSTANTS_ETA_A[[1, 1]] * rep_inv)
-                + tau2 * (BH_CONSTANTS_ETA_A[[1, 2]] + BH_CONSTANTS_ETA_A[[1, 3]] * rep_inv),
-            tau * (BH_CONSTANTS_ETA_A[[2, 0]] + BH_CONSTANTS_ETA_A[[2, 1]] * rep_inv)
-                + tau2 * (BH_CONSTANTS_ETA_A[[2, 2]] + BH_CONSTANTS_ETA_A[[2, 3]] * rep_inv),
-            tau * (BH_CONSTANTS_ETA_A[[3, 0]] + BH_CONSTANTS_ETA_A[[3, 1]] * rep_inv)
-                + tau2 * (BH_CONSTANTS_ETA_A[[3, 2]] + BH_CONSTANTS_ETA_A[[3, 3]] * rep_inv),
-        ]);
-        eta + eta * c[0] + eta * eta * c[1] + eta.powi(3) * c[2] + eta.powi(4) * c[3]
-    })
+pub fn packing_fraction_b<D: DualNum<f64>>(eta: D, tau: D) -> D {
+    let tau2 = tau * tau;
+    let c = [
+        tau * BH_CONSTANTS_ETA_B[[0, 0]] + tau2 * BH_CONSTANTS_ETA_B[[0, 1]],
+        tau * BH_CONSTANTS_ETA_B[[1, 0]] + tau2 * BH_CONSTANTS_ETA_B[[1, 1]],
+        tau * BH_CONSTANTS_ETA_B[[2, 0]] + tau2 * BH_CONSTANTS_ETA_B[[2, 1]],
+    ];
+    eta + eta * c[0] + eta * eta * c[1] + eta.powi(3) * c[2]
+}
+
+pub fn packing_fraction_a<D: DualNum<f64>>(eta: D, tau: D, rep: f64) -> D {
+    let tau2 = tau * tau;
+    let rep_inv = 1.0 / rep;
+    let c = [
+        tau * (BH_CONSTANTS_ETA_A[[0, 0]] + BH_CONSTANTS_ETA_A[[0, 1]] * rep_inv)
+            + tau2 * (BH_CONSTANTS_ETA_A[[0, 2]] + BH_CONSTANTS_ETA_A[[0, 3]] * rep_inv),
+        tau * (BH_CONSTANTS_ETA_A[[1, 0]] + BH_CONSTANTS_ETA_A[[1, 1]] * rep_inv)
+            + tau2 * (BH_CONSTANTS_ETA_A[[1, 2]] + BH_CONSTANTS_ETA_A[[1, 3]] * rep_inv),
+        tau * (BH_CONSTANTS_ETA_A[[2, 0]] + BH_CONSTANTS_ETA_A[[2, 1]] * rep_inv)
+            + tau2 * (BH_CONSTANTS_ETA_A[[2, 2]] + BH_CONSTANTS_ETA_A[[2, 3]] * rep_inv),
+        tau * (BH_CONSTANTS_ETA_A[[3, 0]] + BH_CONSTANTS_ETA_A[[3, 1]] * rep_inv)
+            + tau2 * (BH_CONSTANTS_ETA_A[[3, 2]] + BH_CONSTANTS_ETA_A[[3, 3]] * rep_inv),
+    ];
+    eta + eta * c[0] + eta * eta * c[1] + eta.powi(3) * c[2] + eta.powi(4) * c[3]
 }
 
 #[cfg(test)]
@@ -177,7 +170,7 @@ mod test {
     //     let reduced_temperature = 4.0;
 
     //     let hs = HardSphere {
-    //         parameters: Rc::new(p.clone()),
+    //         parameters: Arc::new(p.clone()),
     //     };
     //     let particles = arr1(&[1000.0]);
     //     let n = &particles / 6.02214076e23;
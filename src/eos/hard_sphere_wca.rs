@@ -1,11 +1,12 @@
-use crate::parameters::UVParameters;
+use super::segment_state;
+use crate::parameters::{EffectiveParameters, UVParameters};
 use feos_core::{HelmholtzEnergyDual, StateHD};
 use lazy_static::lazy_static;
 use ndarray::prelude::*;
 use num_dual::DualNum;
 use std::f64::consts::PI;
 use std::fmt;
-use std::rc::Rc;
+use std::sync::Arc;
 
 lazy_static! {
     static ref WCA_CONSTANTS_ETA_B: Array2<f64> = arr2(&[
@@ -19,6 +20,18 @@ lazy_static! {
         [-2.905719617, -1.778798984, -1.556827067, -4.308085347],
         [0.429154871, 20.765871545, 9.341250676, -33.787719418],
     ]);
+    /// Constants for the effective packing fractions of uv-B3-theory.
+    pub static ref WCA_CONSTANTS_ETA_B_UVB3: Array2<f64> = arr2(&[
+        [2.19821588, -20.45005484],
+        [-13.47050687, 56.65701375],
+        [12.90119266, -42.71680606],
+    ]);
+    pub static ref WCA_CONSTANTS_ETA_A_UVB3: Array2<f64> = arr2(&[
+        [2.64043218, -1.2184421, -22.90786387, 0.96433414],
+        [-16.75643936, 30.83929771, 73.08711814, -166.57701616],
+        [19.53170162, -88.87955657, -76.51387192, 443.68942745],
+        [-3.77740877, 83.04694547, 21.62502721, -304.8643176],
+    ]);
     pub static ref WCA_CONSTANTS_Q: Array2<f64> = arr2(&[
         [1.92840364363978, 4.43165896265079E-01, 0.0, 0.0],
         [
@@ -38,21 +51,21 @@ lazy_static! {
 
 #[derive(Debug, Clone)]
 pub struct HardSphereWCA {
-    pub parameters: Rc<UVParameters>,
+    pub parameters: Arc<UVParameters>,
 }
 
 impl<D: DualNum<f64>> HelmholtzEnergyDual<D> for HardSphereWCA {
     /// Helmholtz energy for hard spheres, eq. 19 (check Volume)
     fn helmholtz_energy(&self, state: &StateHD<D>) -> D {
+        let state = &segment_state(&self.parameters, state);
         let d = diameter_wca(&self.parameters, state.temperature);
         let zeta = zeta(&state.partial_density, &d);
         let frac_1mz3 = -(zeta[3] - 1.0).recip();
         let zeta_23 = zeta_23(&state.molefracs, &d);
-        let a_hs = state.volume * 6.0 / std::f64::consts::PI
+        state.volume * 6.0 / std::f64::consts::PI
             * (zeta[1] * zeta[2] * frac_1mz3 * 3.0
                 + zeta[2].powi(2) * frac_1mz3.powi(2) * zeta_23
-                + (zeta[2] * zeta_23.powi(2) - zeta[0]) * (zeta[3] * (-1.0)).ln_1p());
-        a_hs
+                + (zeta[2] * zeta_23.powi(2) - zeta[0]) * (zeta[3] * (-1.0)).ln_1p())
     }
 }
 
@@ -64,22 +77,23 @@ impl fmt::Display for HardSphereWCA {
 
 /// Dimensionless Hard-sphere diameter according to Weeks-Chandler-Andersen division.
 pub fn diameter_wca<D: DualNum<f64>>(parameters: &UVParameters, temperature: D) -> Array1<D> {
-    parameters
-        .sigma
-        .iter()
-        .enumerate()
-        .map(|(i, _b)| {
-            let t = temperature / parameters.epsilon_k[i];
-            let rm = (parameters.rep[i] / parameters.att[i])
-                .powf(1.0 / (parameters.rep[i] - parameters.att[i]));
-            let c = (parameters.rep[i] / 6.0)
-                .powf(-parameters.rep[i] / (12.0 - 2.0 * parameters.rep[i]))
-                - 1.0;
-
-            (((t.sqrt() * c + 1.0).powf(2.0 / parameters.rep[i])).recip() * rm)
-                * parameters.sigma[i]
-        })
-        .collect()
+    Array1::from_shape_fn(parameters.ncomponents, |i| {
+        diameter_wca_i(parameters, i, temperature)
+    })
+}
+
+/// Hard-sphere diameter of component `i` according to Weeks-Chandler-Andersen division.
+pub fn diameter_wca_i<D: DualNum<f64>>(parameters: &UVParameters, i: usize, temperature: D) -> D {
+    let (sigma, epsilon_k) = parameters.effective_parameters_i(i, temperature);
+    let t = temperature / epsilon_k;
+    (((t.sqrt() * parameters.c_wca[i] + 1.0).powf(2.0 / parameters.rep[i])).recip()
+        * parameters.rm[i])
+        * sigma
+}
+
+/// Constant of the WCA hard-sphere diameter, eq. (S26).
+pub(crate) fn diameter_wca_constant(rep: f64) -> f64 {
+    (rep / 6.0).powf(-rep / (12.0 - 2.0 * rep)) - 1.0
 }
 
 // Hard sphere diameter q for WCA division from eq. (S28)
@@ -121,11 +135,31 @@ pub fn diameter_wca<D: DualNum<f64>>(parameters: &UVParameters, temperature: D)
 //
 
 pub fn dimensionless_diameter_q_wca<D: DualNum<f64>>(t_x: D, rep_x: D, att_x: D) -> D {
-    let nu = rep_x;
-    let n = att_x;
-    let rs = (nu / n).powd((nu - n).recip());
-    let coeffs = arr1(&[
-        (nu * 2.0 * PI / n).sqrt(),
+    let rs = (rep_x / att_x).powd((rep_x - att_x).recip());
+    let coeffs = diameter_q_wca_coefficients(rep_x, att_x);
+    diameter_q_wca_from_coefficients(t_x, rep_x, rs, coeffs)
+}
+
+/// Dimensionless diameter q of the pair `ij` using the constants stored in the parameters.
+pub fn dimensionless_diameter_q_wca_ij<D: DualNum<f64>>(
+    parameters: &UVParameters,
+    i: usize,
+    j: usize,
+    t_ij: D,
+) -> D {
+    diameter_q_wca_from_coefficients(
+        t_ij,
+        D::from(parameters.rep_ij[[i, j]]),
+        D::from(parameters.rm_ij[[i, j]]),
+        parameters.q_wca_ij[[i, j]].map(D::from),
+    )
+}
+
+/// Temperature independent coefficients of the dimensionless diameter q, eq. (S28).
+pub(crate) fn diameter_q_wca_coefficients<D: DualNum<f64>>(rep: D, att: D) -> [D; 4] {
+    let nu = rep;
+    [
+        (nu * 2.0 * PI / att).sqrt(),
         (nu - 7.0) * WCA_CONSTANTS_Q[[0, 1]] + WCA_CONSTANTS_Q[[0, 0]],
         (nu - 7.0) * WCA_CONSTANTS_Q[[1, 1]]
             + (nu - 7.0).powi(2) * WCA_CONSTANTS_Q[[1, 2]]
@@ -135,8 +169,10 @@ pub fn dimensionless_diameter_q_wca<D: DualNum<f64>>(t_x: D, rep_x: D, att_x: D)
             + (nu - 7.0).powi(2) * WCA_CONSTANTS_Q[[2, 2]]
             + (nu - 7.0).powi(3) * WCA_CONSTANTS_Q[[2, 3]]
             + WCA_CONSTANTS_Q[[2, 0]],
-    ]);
+    ]
+}
 
+fn diameter_q_wca_from_coefficients<D: DualNum<f64>>(t_x: D, nu: D, rs: D, coeffs: [D; 4]) -> D {
     (t_x.powf(2.0) * coeffs[3]
         + t_x.powf(3.0 / 2.0) * coeffs[2]
         + t_x * coeffs[1]
@@ -149,9 +185,8 @@ pub fn dimensionless_diameter_q_wca<D: DualNum<f64>>(t_x: D, rep_x: D, att_x: D)
 pub fn zeta<D: DualNum<f64>>(partial_density: &Array1<D>, diameter: &Array1<D>) -> [D; 4] {
     let mut zeta: [D; 4] = [D::zero(), D::zero(), D::zero(), D::zero()];
     for i in 0..partial_density.len() {
-        for k in 0..4 {
-            zeta[k] = zeta[k]
-                + partial_density[i] * diameter[i].powi(k as i32) * (std::f64::consts::PI / 6.0);
+        for (k, z) in zeta.iter_mut().enumerate() {
+            *z += partial_density[i] * diameter[i].powi(k as i32) * (std::f64::consts::PI / 6.0);
         }
     }
     zeta
@@ -166,81 +201,75 @@ pub fn packing_fraction<D: DualNum<f64>>(partial_density: &Array1<D>, diameter:
 pub fn zeta_23<D: DualNum<f64>>(molefracs: &Array1<D>, diameter: &Array1<D>) -> D {
     let mut zeta: [D; 2] = [D::zero(), D::zero()];
     for i in 0..molefracs.len() {
-        for k in 0..2 {
-            zeta[k] = zeta[k] + molefracs[i] * diameter[i].powi((k + 2) as i32);
+        for (k, z) in zeta.iter_mut().enumerate() {
+            *z += molefracs[i] * diameter[i].powi((k + 2) as i32);
         }
     }
     zeta[0] / zeta[1]
 }
 
+/// Dimensionless length scale `tau_ij = (r_min - d) / sigma` of the pair `ij`.
 #[inline]
-pub fn dimensionless_length_scale<D: DualNum<f64>>(
+pub fn dimensionless_length_scale_ij<D: DualNum<f64>>(
     parameters: &UVParameters,
-    temperature: D,
-) -> Array1<D> {
-    parameters
-        .sigma
-        .iter()
-        .enumerate()
-        .map(|(i, _c)| {
-            let rs = (parameters.rep[i] / parameters.att[i])
-                .powf(1.0 / (parameters.rep[i] - parameters.att[i]));
-            -diameter_wca(parameters, temperature)[i] + rs * parameters.sigma[i]
-            // parameters.sigma[i]
-        })
-        .collect()
+    diameter: &Array1<D>,
+    effective: &EffectiveParameters<D>,
+    i: usize,
+    j: usize,
+) -> D {
+    let tau = |k: usize| -diameter[k] + effective.sigma(k) * parameters.rm[k];
+    (tau(i) + tau(j)) / effective.sigma_ij(i, j) * 0.5
 }
 
 #[inline]
+pub fn packing_fraction_b<D: DualNum<f64>>(eta: D, tau: D) -> D {
+    packing_fraction_b_with(&WCA_CONSTANTS_ETA_B, eta, tau)
+}
 
-pub fn packing_fraction_b<D: DualNum<f64>>(
-    parameters: &UVParameters,
-    eta: D,
-    temperature: D,
-) -> Array2<D> {
-    let n = parameters.att.len();
-    let dimensionless_lengths = dimensionless_length_scale(parameters, temperature);
-    Array2::from_shape_fn((n, n), |(i, j)| {
-        let tau = (dimensionless_lengths[i] + dimensionless_lengths[j])
-            / parameters.sigma_ij[[i, j]]
-            * 0.5; //dimensionless
-        let tau2 = tau * tau;
-
-        let c = arr1(&[
-            tau * WCA_CONSTANTS_ETA_B[[0, 0]] + tau2 * WCA_CONSTANTS_ETA_B[[0, 1]],
-            tau * WCA_CONSTANTS_ETA_B[[1, 0]] + tau2 * WCA_CONSTANTS_ETA_B[[1, 1]],
-            tau * WCA_CONSTANTS_ETA_B[[2, 0]] + tau2 * WCA_CONSTANTS_ETA_B[[2, 1]],
-        ]);
-        eta + eta * c[0] + eta * eta * c[1] + eta.powi(3) * c[2]
-    })
+#[inline]
+pub fn packing_fraction_b_uvb3<D: DualNum<f64>>(eta: D, tau: D) -> D {
+    packing_fraction_b_with(&WCA_CONSTANTS_ETA_B_UVB3, eta, tau)
 }
 
-pub fn packing_fraction_a<D: DualNum<f64>>(
-    parameters: &UVParameters,
+fn packing_fraction_b_with<D: DualNum<f64>>(constants: &Array2<f64>, eta: D, tau: D) -> D {
+    let tau2 = tau * tau;
+    let c = [
+        tau * constants[[0, 0]] + tau2 * constants[[0, 1]],
+        tau * constants[[1, 0]] + tau2 * constants[[1, 1]],
+        tau * constants[[2, 0]] + tau2 * constants[[2, 1]],
+    ];
+    eta + eta * c[0] + eta * eta * c[1] + eta.powi(3) * c[2]
+}
+
+#[inline]
+pub fn packing_fraction_a<D: DualNum<f64>>(eta: D, tau: D, rep: f64) -> D {
+    packing_fraction_a_with(&WCA_CONSTANTS_ETA_A, eta, tau, rep)
+}
+
+#[inline]
+pub fn packing_fraction_a_uvb3<D: DualNum<f64>>(eta: D, tau: D, rep: f64) -> D {
+    packing_fraction_a_with(&WCA_CONSTANTS_ETA_A_UVB3, eta, tau, rep)
+}
+
+fn packing_fraction_a_with<D: DualNum<f64>>(
+    constants: &Array2<f64>,
     eta: D,
-    temperature: D,
-) -> Array2<D> {
-    let dimensionless_lengths = dimensionless_length_scale(parameters, temperature);
-    let n = parameters.att.len();
-    Array2::from_shape_fn((n, n), |(i, j)| {
-        let tau = (dimensionless_lengths[i] + dimensionless_lengths[j])
-            / parameters.sigma_ij[[i, j]]
-            * 0.5; //dimensionless
-
-        let tau2 = tau * tau;
-        let rep_inv = 1.0 / parameters.rep_ij[[i, j]];
-        let c = arr1(&[
-            tau * (WCA_CONSTANTS_ETA_A[[0, 0]] + WCA_CONSTANTS_ETA_A[[0, 1]] * rep_inv)
-                + tau2 * (WCA_CONSTANTS_ETA_A[[0, 2]] + WCA_CONSTANTS_ETA_A[[0, 3]] * rep_inv),
-            tau * (WCA_CONSTANTS_ETA_A[[1, 0]] + WCA_CONSTANTS_ETA_A[[1, 1]] * rep_inv)
-                + tau2 * (WCA_CONSTANTS_ETA_A[[1, 2]] + WCA_CONSTANTS_ETA_A[[1, 3]] * rep_inv),
-            tau * (WCA_CONSTANTS_ETA_A[[2, 0]] + WCA_CONSTANTS_ETA_A[[2, 1]] * rep_inv)
-                + tau2 * (WCA_CONSTANTS_ETA_A[[2, 2]] + WCA_CONSTANTS_ETA_A[[2, 3]] * rep_inv),
-            tau * (WCA_CONSTANTS_ETA_A[[3, 0]] + WCA_CONSTANTS_ETA_A[[3, 1]] * rep_inv)
-                + tau2 * (WCA_CONSTANTS_ETA_A[[3, 2]] + WCA_CONSTANTS_ETA_A[[3, 3]] * rep_inv),
-        ]);
-        eta + eta * c[0] + eta * eta * c[1] + eta.powi(3) * c[2] + eta.powi(4) * c[3]
-    })
+    tau: D,
+    rep: f64,
+) -> D {
+    let tau2 = tau * tau;
+    let rep_inv = 1.0 / rep;
+    let c = [
+        tau * (constants[[0, 0]] + constants[[0, 1]] * rep_inv)
+            + tau2 * (constants[[0, 2]] + constants[[0, 3]] * rep_inv),
+        tau * (constants[[1, 0]] + constants[[1, 1]] * rep_inv)
+            + tau2 * (constants[[1, 2]] + constants[[1, 3]] * rep_inv),
+        tau * (constants[[2, 0]] + constants[[2, 1]] * rep_inv)
+            + tau2 * (constants[[2, 2]] + constants[[2, 3]] * rep_inv),
+        tau * (constants[[3, 0]] + constants[[3, 1]] * rep_inv)
+            + tau2 * (constants[[3, 2]] + constants[[3, 3]] * rep_inv),
+    ];
+    eta + eta * c[0] + eta * eta * c[1] + eta.powi(3) * c[2] + eta.powi(4) * c[3]
 }
 
 #[cfg(test)]
@@ -271,9 +300,21 @@ mod test {
             0.9751576149023506,
             epsilon = 1e-8
         );
+        assert_relative_eq!(
+            dimensionless_diameter_q_wca_ij(&p, 0, 0, temp),
+            dimensionless_diameter_q_wca(temp, p.rep[0], p.att[0]),
+            max_relative = 1e-14
+        );
 
+        let t = 4.0 * p.epsilon_k[0];
         assert_relative_eq!(
-            dimensionless_length_scale(&p, 4.0 * p.epsilon_k[0])[0] / p.sigma[0],
+            dimensionless_length_scale_ij(
+                &p,
+                &diameter_wca(&p, t),
+                &p.effective_parameters(t),
+                0,
+                0
+            ),
             0.11862717872596029,
             epsilon = 1e-8
         );
@@ -294,7 +335,7 @@ mod test {
         );
 
         let pt = HardSphereWCA {
-            parameters: Rc::new(p),
+            parameters: Arc::new(p),
         };
         let state = StateHD::new(reduced_temperature, reduced_volume, moles.clone());
         let a = pt.helmholtz_energy(&state) / (moles[0] + moles[1]);
@@ -1,33 +1,135 @@
-use crate::parameters::UVParameters;
-use feos_core::{parameter::Parameter, EquationOfState, HelmholtzEnergy};
+use crate::errors::{UVTheoryError, UVTheoryResult};
+use crate::parameters::{mean_field_constant, mie_prefactor, UVParameters};
+use feos_core::joback::Joback;
+use feos_core::parameter::Parameter;
+use feos_core::{EquationOfState, HelmholtzEnergy, IdealGasContribution, MolarWeight, StateHD};
 use ndarray::Array1;
-use std::f64::consts::FRAC_PI_6;
-use std::rc::Rc;
+use num_dual::DualNum;
+use quantity::si::*;
+use std::borrow::Cow;
+use std::f64::consts::{FRAC_PI_3, FRAC_PI_6, PI};
+use std::sync::Arc;
 
+pub(crate) mod association;
 pub(crate) mod attractive_perturbation_bh;
+pub(crate) mod attractive_perturbation_uvb3;
 pub(crate) mod attractive_perturbation_wca;
+pub(crate) mod hard_chain;
 pub(crate) mod hard_sphere_bh;
 pub(crate) mod hard_sphere_wca;
+pub(crate) mod polar;
 pub(crate) mod reference_perturbation_bh;
+pub(crate) mod reference_perturbation_uvb3;
 pub(crate) mod reference_perturbation_wca;
+pub(crate) mod validity;
+use association::{Association, CrossAssociation};
 use attractive_perturbation_bh::AttractivePerturbationBH;
+use attractive_perturbation_uvb3::AttractivePerturbationUVB3;
 use attractive_perturbation_wca::AttractivePerturbationWCA;
-use hard_sphere_bh::HardSphere;
-use hard_sphere_wca::HardSphereWCA;
+use hard_chain::HardChain;
+use hard_sphere_bh::{diameter_bh, HardSphere};
+use hard_sphere_wca::{diameter_wca, HardSphereWCA};
+use polar::{Dipole, DipoleQuadrupole, Quadrupole};
 use reference_perturbation_bh::ReferencePerturbationBH;
+use reference_perturbation_uvb3::ReferencePerturbationUVB3;
 use reference_perturbation_wca::ReferencePerturbationWCA;
+use validity::StrictContribution;
+pub use validity::{ValidityDomain, ValidityViolation};
 
-#[derive(Clone)]
+/// Distance (in units of the segment diameter) beyond which the second virial
+/// coefficient of the attractive perturbation is evaluated in mean-field approximation.
+pub(crate) const MEAN_FIELD_RADIUS: f64 = 5.0;
+
+#[derive(Clone, Debug)]
 #[cfg_attr(feature = "python", pyo3::pyclass)]
 pub enum Perturbation {
     BarkerHenderson,
     WeeksChandlerAndersen,
 }
 
+/// Order of the highest virial coefficient that is reproduced by the model.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "python", pyo3::pyclass)]
+pub enum VirialOrder {
+    /// uv-theory
+    Second,
+    /// uv-B3-theory, only available for the WCA division and full-range potentials
+    Third,
+}
+
 #[derive(Clone)]
 pub struct UVTheoryOptions {
     pub max_eta: f64,
     pub perturbation: Perturbation,
+    pub virial_order: VirialOrder,
+    pub max_iter_cross_assoc: usize,
+    pub tol_cross_assoc: f64,
+    /// Cutoff radius of the potential in units of the segment diameter.
+    /// Has to be larger than the position of the potential minimum.
+    /// If `None`, the full-range potential is used.
+    pub cutoff_radius: Option<f64>,
+    /// Shift the truncated potential to zero at the cutoff radius.
+    pub shift_potential: bool,
+    /// Strict mode: if given, the model refuses parameters and states outside of
+    /// the validity domain. Parameters outside of the domain are rejected on
+    /// construction. The residual Helmholtz energy of `UVTheory` is NaN for states
+    /// outside of the domain and [UVTheory::check_state] returns a typed error for
+    /// them. The perturbation contributions of `UVTheoryFunctional` are NaN at
+    /// weighted densities outside of the domain.
+    pub validity_domain: Option<ValidityDomain>,
+}
+
+impl UVTheoryOptions {
+    /// Checks that uv-B3-theory is only combined with the WCA division
+    /// and full-range potentials, and that the cutoff radius is finite and
+    /// larger than the segment diameter.
+    pub fn check(&self) -> UVTheoryResult<()> {
+        if let Some(rc) = self.cutoff_radius {
+            // the minimum of every Mie potential is beyond the segment diameter
+            if !(rc.is_finite() && rc > 1.0) {
+                return Err(UVTheoryError::IncompatibleOptions(format!(
+                    "the cutoff radius has to be finite and larger than the segment diameter, got {}",
+                    rc
+                )));
+            }
+        }
+        if let VirialOrder::Third = self.virial_order {
+            if let Perturbation::BarkerHenderson = self.perturbation {
+                return Err(UVTheoryError::IncompatibleOptions(
+                    "uv-B3-theory is only available for the WCA division".into(),
+                ));
+            }
+            if self.cutoff_radius.is_some() {
+                return Err(UVTheoryError::IncompatibleOptions(
+                    "uv-B3-theory is only available for full-range potentials".into(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the options, that the cutoff radius is beyond the minima of all
+    /// potentials and, in strict mode, that the parameters are inside of the
+    /// validity domain.
+    pub(crate) fn check_parameters(&self, parameters: &UVParameters) -> UVTheoryResult<()> {
+        self.check()?;
+        if let Some(rc) = self.cutoff_radius {
+            let rm = parameters.rm_ij.fold(0.0, |rm: f64, &rm_ij| rm.max(rm_ij));
+            if rc <= rm {
+                return Err(UVTheoryError::IncompatibleOptions(format!(
+                    "the cutoff radius ({}) has to be larger than the position of the potential minimum ({})",
+                    rc, rm
+                )));
+            }
+        }
+        if let Some(domain) = self.validity_domain {
+            let violations = domain.check_parameters(parameters);
+            if !violations.is_empty() {
+                return Err(UVTheoryError::OutsideValidityDomain(violations));
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Default for UVTheoryOptions {
@@ -35,91 +137,327 @@ impl Default for UVTheoryOptions {
         Self {
             max_eta: 0.5,
             perturbation: Perturbation::WeeksChandlerAndersen,
+            virial_order: VirialOrder::Second,
+            max_iter_cross_assoc: 50,
+            tol_cross_assoc: 1e-10,
+            cutoff_radius: None,
+            shift_potential: false,
+            validity_domain: None,
+        }
+    }
+}
+
+/// Truncation of the Mie potential as used in the attractive perturbation.
+///
+/// All lengths are reduced by the (combined) segment diameter and all energies by
+/// the (combined) energy parameter. Shifting the potential only modifies the
+/// perturbation, the WCA reference fluid is invariant under the shift.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Cutoff {
+    radius: Option<f64>,
+    shifted: bool,
+}
+
+impl Cutoff {
+    pub fn new(options: &UVTheoryOptions) -> Self {
+        Self {
+            radius: options.cutoff_radius,
+            shifted: options.shift_potential,
+        }
+    }
+
+    /// Potential energy at the cutoff radius, u(r_c) / epsilon, that is
+    /// subtracted from the shifted potential.
+    pub fn shift<D: DualNum<f64>>(&self, rep: D, att: D) -> D {
+        match self.radius {
+            Some(rc) if self.shifted => {
+                let rc = D::from(rc);
+                mie_prefactor(rep, att) * (rc.powd(-rep) - rc.powd(-att))
+            }
+            _ => D::zero(),
+        }
+    }
+
+    /// Mean-field constant of the truncated (and shifted) potential beyond `x`.
+    pub fn mean_field_constant<D: DualNum<f64>>(&self, rep: D, att: D, x: D) -> D {
+        match self.radius {
+            None => mean_field_constant(rep, att, x),
+            Some(rc) if x.re() < rc => {
+                let rc = D::from(rc);
+                mean_field_constant(rep, att, x) - mean_field_constant(rep, att, rc)
+                    + self.shift(rep, att) * (rc.powi(3) - x.powi(3)) / 3.0
+            }
+            Some(_) => D::zero(),
+        }
+    }
+
+    /// Residual second virial coefficient of the truncated (and shifted)
+    /// potential from the one of the full-range potential.
+    ///
+    /// The tail beyond the cutoff radius is removed in mean-field approximation.
+    /// The shift is accounted for exactly, given the reference virial coefficient.
+    pub fn delta_b2<D: DualNum<f64>>(
+        &self,
+        delta_b2: D,
+        reduced_temperature: D,
+        rep: f64,
+        att: f64,
+        b2_reference: D,
+    ) -> D {
+        let rc = match self.radius {
+            Some(rc) => rc,
+            None => return delta_b2,
+        };
+        let beta = reduced_temperature.recip();
+        let delta_b2 = delta_b2 + beta * mean_field_constant(rep, att, rc) * 2.0 * PI;
+        if !self.shifted {
+            return delta_b2;
         }
+        let f = (beta * self.shift(rep, att)).exp_m1();
+        delta_b2 * (f + 1.0) + f * (b2_reference - rc.powi(3) * 2.0 * FRAC_PI_3)
     }
 }
 
 pub struct UVTheory {
-    parameters: Rc<UVParameters>,
-    options: UVTheoryOptions,
+    pub(crate) parameters: Arc<UVParameters>,
+    pub(crate) options: UVTheoryOptions,
     contributions: Vec<Box<dyn HelmholtzEnergy>>,
+    joback: Joback,
 }
 
 impl UVTheory {
-    pub fn new(parameters: Rc<UVParameters>) -> Self {
-        Self::with_options(parameters, UVTheoryOptions::default())
+    pub fn new(parameters: Arc<UVParameters>) -> Self {
+        Self::with_checked_options(parameters, UVTheoryOptions::default())
     }
 
-    pub fn with_options(parameters: Rc<UVParameters>, options: UVTheoryOptions) -> Self {
-        let mut contributions: Vec<Box<dyn HelmholtzEnergy>> = Vec::with_capacity(3);
+    /// Returns an error if uv-B3-theory is combined with the Barker-Henderson
+    /// division or with a truncated potential, or if the parameters are outside
+    /// of the validity domain in strict mode.
+    pub fn with_options(
+        parameters: Arc<UVParameters>,
+        options: UVTheoryOptions,
+    ) -> UVTheoryResult<Self> {
+        options.check_parameters(&parameters)?;
+        Ok(Self::with_checked_options(parameters, options))
+    }
+
+    /// Equation of state for options that are known to be compatible with
+    /// each other and with the parameters, e.g., the options of an existing `UVTheory`.
+    pub(crate) fn with_checked_options(
+        parameters: Arc<UVParameters>,
+        options: UVTheoryOptions,
+    ) -> Self {
+        let mut contributions: Vec<Box<dyn HelmholtzEnergy>> = Vec::with_capacity(6);
 
         match options.perturbation {
             Perturbation::BarkerHenderson => {
                 contributions.push(Box::new(HardSphere {
                     parameters: parameters.clone(),
                 }));
-                contributions.push(Box::new(ReferencePerturbationBH {
-                    parameters: parameters.clone(),
-                }));
-                contributions.push(Box::new(AttractivePerturbationBH {
-                    parameters: parameters.clone(),
-                }));
             }
             Perturbation::WeeksChandlerAndersen => {
                 contributions.push(Box::new(HardSphereWCA {
                     parameters: parameters.clone(),
                 }));
-                contributions.push(Box::new(ReferencePerturbationWCA {
-                    parameters: parameters.clone(),
-                }));
-                contributions.push(Box::new(AttractivePerturbationWCA {
-                    parameters: parameters.clone(),
-                }));
             }
         }
+        if parameters.m.iter().any(|&mi| mi != 1.0) {
+            contributions.push(Box::new(HardChain {
+                parameters: parameters.clone(),
+                perturbation: options.perturbation.clone(),
+            }));
+        }
+        // the options have been checked before
+        contributions.append(&mut perturbation_contributions(&parameters, &options).unwrap());
+        if let Some(domain) = options.validity_domain {
+            contributions = contributions
+                .into_iter()
+                .map(|c| -> Box<dyn HelmholtzEnergy> {
+                    Box::new(StrictContribution::new(parameters.clone(), domain, c))
+                })
+                .collect();
+        }
+
+        let joback = match &parameters.joback_records {
+            Some(joback_records) => Joback::new(joback_records.clone()),
+            None => Joback::default(parameters.ncomponents),
+        };
 
         Self {
-            parameters: parameters.clone(),
+            parameters,
             options,
             contributions,
+            joback,
         }
     }
 }
 
+/// All residual Helmholtz energy contributions except the hard-sphere term.
+///
+/// The contributions are shared with the Helmholtz energy functional,
+/// which replaces the hard-sphere term by fundamental measure theory.
+/// Returns an error if the options are incompatible, see [UVTheoryOptions::check].
+pub(crate) fn perturbation_contributions(
+    parameters: &Arc<UVParameters>,
+    options: &UVTheoryOptions,
+) -> UVTheoryResult<Vec<Box<dyn HelmholtzEnergy>>> {
+    options.check()?;
+    let mut contributions: Vec<Box<dyn HelmholtzEnergy>> = Vec::with_capacity(3);
+    match (&options.perturbation, &options.virial_order) {
+        (Perturbation::BarkerHenderson, VirialOrder::Third) => {
+            return Err(UVTheoryError::IncompatibleOptions(
+                "uv-B3-theory is only available for the WCA division".into(),
+            ))
+        }
+        (Perturbation::WeeksChandlerAndersen, VirialOrder::Third) => {
+            contributions.push(Box::new(ReferencePerturbationUVB3 {
+                parameters: parameters.clone(),
+            }));
+            contributions.push(Box::new(AttractivePerturbationUVB3 {
+                parameters: parameters.clone(),
+            }));
+        }
+        (Perturbation::BarkerHenderson, VirialOrder::Second) => {
+            contributions.push(Box::new(ReferencePerturbationBH {
+                parameters: parameters.clone(),
+            }));
+            contributions.push(Box::new(AttractivePerturbationBH {
+                parameters: parameters.clone(),
+                cutoff: Cutoff::new(options),
+            }));
+        }
+        (Perturbation::WeeksChandlerAndersen, VirialOrder::Second) => {
+            contributions.push(Box::new(ReferencePerturbationWCA {
+                parameters: parameters.clone(),
+            }));
+            contributions.push(Box::new(AttractivePerturbationWCA {
+                parameters: parameters.clone(),
+                cutoff: Cutoff::new(options),
+            }));
+        }
+    }
+    if parameters.ndipole > 0 {
+        contributions.push(Box::new(Dipole::new(
+            parameters.clone(),
+            options.perturbation.clone(),
+        )));
+    }
+    if parameters.nquadpole > 0 {
+        contributions.push(Box::new(Quadrupole::new(
+            parameters.clone(),
+            options.perturbation.clone(),
+        )));
+    }
+    if parameters.ndipole > 0 && parameters.nquadpole > 0 {
+        contributions.push(Box::new(DipoleQuadrupole::new(
+            parameters.clone(),
+            options.perturbation.clone(),
+        )));
+    }
+    match parameters.nassoc {
+        0 => (),
+        1 => contributions.push(Box::new(Association {
+            parameters: parameters.clone(),
+            perturbation: options.perturbation.clone(),
+        })),
+        _ => contributions.push(Box::new(CrossAssociation {
+            parameters: parameters.clone(),
+            perturbation: options.perturbation.clone(),
+            max_iter: options.max_iter_cross_assoc,
+            tol: options.tol_cross_assoc,
+        })),
+    }
+    Ok(contributions)
+}
+
+/// State of the segment fluid, i.e., the moles of every component are
+/// multiplied by its segment number.
+///
+/// The mole fractions are transformed separately, so that they remain
+/// well-defined in the zero density limit (e.g. for virial coefficients).
+/// If all segment numbers are one, the state is borrowed.
+pub(crate) fn segment_state<'a, D: DualNum<f64>>(
+    parameters: &UVParameters,
+    state: &'a StateHD<D>,
+) -> Cow<'a, StateHD<D>> {
+    let m = &parameters.m;
+    if m.iter().all(|&mi| mi == 1.0) {
+        return Cow::Borrowed(state);
+    }
+    let n = parameters.ncomponents;
+    let segment_fractions = Array1::from_shape_fn(n, |i| state.molefracs[i] * m[i]);
+    let total = segment_fractions.sum();
+    Cow::Owned(StateHD {
+        temperature: state.temperature,
+        volume: state.volume,
+        moles: Array1::from_shape_fn(n, |i| state.moles[i] * m[i]),
+        molefracs: segment_fractions.mapv(|x| x / total),
+        partial_density: Array1::from_shape_fn(n, |i| state.partial_density[i] * m[i]),
+    })
+}
+
+/// Temperature dependent hard-sphere diameters of the chosen division.
+pub(crate) fn hs_diameter<D: DualNum<f64>>(
+    parameters: &UVParameters,
+    perturbation: &Perturbation,
+    temperature: D,
+) -> Array1<D> {
+    match perturbation {
+        Perturbation::BarkerHenderson => diameter_bh(parameters, temperature),
+        Perturbation::WeeksChandlerAndersen => diameter_wca(parameters, temperature),
+    }
+}
+
 impl EquationOfState for UVTheory {
     fn components(&self) -> usize {
         self.parameters.pure_records.len()
     }
 
     fn subset(&self, component_list: &[usize]) -> Self {
-        Self::with_options(
-            Rc::new(self.parameters.subset(component_list)),
+        Self::with_checked_options(
+            Arc::new(self.parameters.subset(component_list)),
             self.options.clone(),
         )
     }
 
     fn compute_max_density(&self, moles: &Array1<f64>) -> f64 {
         self.options.max_eta * moles.sum()
-            / (FRAC_PI_6 * self.parameters.sigma.mapv(|v| v.powi(3)) * moles).sum()
+            / (FRAC_PI_6 * &self.parameters.m * self.parameters.sigma.mapv(|v| v.powi(3)) * moles)
+                .sum()
     }
 
     fn residual(&self) -> &[Box<dyn HelmholtzEnergy>] {
         &self.contributions
     }
+
+    fn ideal_gas(&self) -> &dyn IdealGasContribution {
+        &self.joback
+    }
+}
+
+impl MolarWeight<SIUnit> for UVTheory {
+    fn molar_weight(&self) -> SIArray1 {
+        self.parameters.molarweight.clone() * GRAM / MOL
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    use crate::parameters::utils::test_parameters_mixture;
+    use crate::parameters::utils::{
+        dme_co2_methane_parameters, test_parameters, test_parameters_mixture,
+    };
 
     use crate::parameters::*;
+    use crate::MiePotential;
     use approx::assert_relative_eq;
+    use feos_core::joback::JobackRecord;
     use feos_core::parameter::{Identifier, Parameter, PureRecord};
-    use feos_core::{Contributions, State};
-    use ndarray::arr1;
-    use quantity::si::{ANGSTROM, KELVIN, MOL, NAV, RGAS};
+    use feos_core::{Contributions, HelmholtzEnergyDual, State};
+    use ndarray::{arr1, Array2};
+    use num_dual::Dual64;
+    use std::rc::Rc;
 
     #[test]
     fn helmholtz_energy_pure_wca() {
@@ -130,14 +468,14 @@ mod test {
         let i = Identifier::new("1", None, None, None, None, None);
         let pr = PureRecord::new(i, 1.0, r, None);
         let parameters = UVParameters::new_pure(pr);
-        let eos = Rc::new(UVTheory::new(Rc::new(parameters)));
+        let eos = Rc::new(UVTheory::new(Arc::new(parameters)));
 
         let reduced_temperature = 4.0;
         //let reduced_temperature = 1.0;
         let reduced_density = 1.0;
         //let reduced_density = 0.9;
         let temperature = reduced_temperature * eps_k * KELVIN;
-        let moles = arr1(&vec![2.0]) * MOL;
+        let moles = arr1(&[2.0]) * MOL;
         let volume = (sig * ANGSTROM).powi(3) / reduced_density * NAV * 2.0 * MOL;
         let s = State::new_nvt(&eos, temperature, volume, &moles).unwrap();
         let a = s
@@ -159,13 +497,14 @@ mod test {
         let options = UVTheoryOptions {
             max_eta: 0.5,
             perturbation: Perturbation::BarkerHenderson,
+            ..Default::default()
         };
-        let eos = Rc::new(UVTheory::with_options(Rc::new(parameters), options));
+        let eos = Rc::new(UVTheory::with_options(Arc::new(parameters), options).unwrap());
 
         let reduced_temperature = 4.0;
         let reduced_density = 1.0;
         let temperature = reduced_temperature * eps_k * KELVIN;
-        let moles = arr1(&vec![2.0]) * MOL;
+        let moles = arr1(&[2.0]) * MOL;
         let volume = (sig * ANGSTROM).powi(3) / reduced_density * NAV * 2.0 * MOL;
         let s = State::new_nvt(&eos, temperature, volume, &moles).unwrap();
         let a = s
@@ -176,6 +515,156 @@ mod test {
         assert_relative_eq!(a, 2.993577305779432, max_relative = 1e-12)
     }
 
+    #[test]
+    fn helmholtz_energy_chain() {
+        let parameters = |m| {
+            let r = UVRecord::new(12.0, 6.0, 3.7039, 150.03).with_segments(m);
+            let i = Identifier::new("1", None, None, None, None, None);
+            Arc::new(UVParameters::new_pure(PureRecord::new(i, 1.0, r, None)))
+        };
+        let monomer = parameters(1.0);
+        let dimer = parameters(2.0);
+
+        // apart from the chain contribution, a dimer behaves like two monomers
+        let t = 1.5 * 150.03;
+        let v = 3.7039f64.powi(3) / 0.4;
+        let residual = |eos: &UVTheory, n: f64| {
+            let state = StateHD::new(t, v, arr1(&[n]));
+            eos.residual()
+                .iter()
+                .map(|c| c.helmholtz_energy(&state))
+                .sum::<f64>()
+        };
+        for perturbation in [
+            Perturbation::BarkerHenderson,
+            Perturbation::WeeksChandlerAndersen,
+        ] {
+            let options = UVTheoryOptions {
+                max_eta: 0.5,
+                perturbation: perturbation.clone(),
+                ..Default::default()
+            };
+            let eos_monomer = UVTheory::with_options(monomer.clone(), options.clone()).unwrap();
+            let eos_dimer = UVTheory::with_options(dimer.clone(), options).unwrap();
+            assert_eq!(eos_monomer.residual().len(), 3);
+            assert_eq!(eos_dimer.residual().len(), 4);
+
+            let chain = HardChain {
+                parameters: dimer.clone(),
+                perturbation,
+            }
+            .helmholtz_energy(&StateHD::new(t, v, arr1(&[1.0])));
+            assert_relative_eq!(
+                residual(&eos_dimer, 1.0),
+                residual(&eos_monomer, 2.0) + chain,
+                max_relative = 1e-12
+            );
+        }
+    }
+
+    #[test]
+    fn polar_nonpolar_mixture() {
+        let eos = Rc::new(UVTheory::new(Arc::new(dme_co2_methane_parameters())));
+        let contributions: Vec<_> = eos.residual().iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            contributions,
+            [
+                "Hard Sphere",
+                "Hard Chain",
+                "Reference Perturbation",
+                "Attractive Perturbation",
+                "Dipole",
+                "Quadrupole",
+                "DipoleQuadrupole"
+            ]
+        );
+
+        let moles = arr1(&[0.3, 0.3, 0.4]) * MOL;
+        let s = State::new_nvt(&eos, 300.0 * KELVIN, 1e-3 * METER.powi(3), &moles).unwrap();
+        let p = s.pressure(Contributions::Total).to_reduced(PASCAL).unwrap();
+        assert!(p.is_finite());
+    }
+
+    #[test]
+    fn helmholtz_energy_uvb3() {
+        let eps_k = 150.03;
+        let sig = 3.7039;
+        let record = |name: &str| {
+            let r = UVRecord::new(12.0, 6.0, sig, eps_k);
+            PureRecord::new(
+                Identifier::new(name, None, None, None, None, None),
+                1.0,
+                r,
+                None,
+            )
+        };
+        let options = UVTheoryOptions {
+            virial_order: VirialOrder::Third,
+            ..Default::default()
+        };
+
+        let reduced_temperature = 4.0;
+        let reduced_density = 0.5;
+        let temperature = reduced_temperature * eps_k * KELVIN;
+        let moles = arr1(&[2.0]) * MOL;
+        let volume = (sig * ANGSTROM).powi(3) / reduced_density * NAV * 2.0 * MOL;
+        let parameters = UVParameters::new_pure(record("1"));
+        let eos = Rc::new(UVTheory::with_options(Arc::new(parameters), options.clone()).unwrap());
+        let s = State::new_nvt(&eos, temperature, volume, &moles).unwrap();
+        let a = s
+            .molar_helmholtz_energy(Contributions::ResidualNvt)
+            .to_reduced(RGAS * temperature)
+            .unwrap();
+        assert_relative_eq!(a, 0.37659379124271003, max_relative = 1e-12);
+
+        // mixture of equal components
+        let parameters = UVParameters::new_binary(vec![record("1"), record("2")], None);
+        let eos = Rc::new(UVTheory::with_options(Arc::new(parameters), options).unwrap());
+        let moles = arr1(&[1.7, 0.3]) * MOL;
+        let s = State::new_nvt(&eos, temperature, volume, &moles).unwrap();
+        let a_mix = s
+            .molar_helmholtz_energy(Contributions::ResidualNvt)
+            .to_reduced(RGAS * temperature)
+            .unwrap();
+        assert_relative_eq!(a_mix, a, max_relative = 1e-12);
+    }
+
+    #[test]
+    fn uvb3_barker_henderson() {
+        let options = UVTheoryOptions {
+            perturbation: Perturbation::BarkerHenderson,
+            virial_order: VirialOrder::Third,
+            ..Default::default()
+        };
+        assert!(matches!(
+            UVTheory::with_options(Arc::new(test_parameters(12.0, 6.0, 1.0, 1.0)), options),
+            Err(UVTheoryError::IncompatibleOptions(e)) if e.contains("only available for the WCA division")
+        ));
+    }
+
+    #[test]
+    fn invalid_cutoff_radius() {
+        let options = |rc| UVTheoryOptions {
+            cutoff_radius: Some(rc),
+            ..Default::default()
+        };
+        for rc in [0.0, -2.5, f64::NAN, f64::INFINITY, 0.9] {
+            assert!(matches!(
+                options(rc).check(),
+                Err(UVTheoryError::IncompatibleOptions(_))
+            ));
+        }
+
+        // the minimum of the Lennard-Jones potential is at 2^(1/6) sigma
+        let parameters = Arc::new(test_parameters(12.0, 6.0, 1.0, 1.0));
+        assert!(options(1.1).check().is_ok());
+        assert!(matches!(
+            UVTheory::with_options(parameters.clone(), options(1.1)),
+            Err(UVTheoryError::IncompatibleOptions(e)) if e.contains("potential minimum")
+        ));
+        assert!(UVTheory::with_options(parameters, options(1.2)).is_ok());
+    }
+
     #[test]
     fn helmholtz_energy_mixtures_bh() {
         // Mixture of equal components --> result must be the same as fpr pure fluid ///
@@ -203,7 +692,7 @@ mod test {
         let t_x = reduced_temperature * eps_k_x * KELVIN;
         let sig_x = (sig1 + sig2) / 2.0; // Check rule!!
         let reduced_density = 1.0;
-        let moles = arr1(&vec![1.7, 0.3]) * MOL;
+        let moles = arr1(&[1.7, 0.3]) * MOL;
         let total_moles = moles.sum();
         let volume = (sig_x * ANGSTROM).powi(3) / reduced_density * NAV * total_moles;
 
@@ -211,9 +700,10 @@ mod test {
         let options = UVTheoryOptions {
             max_eta: 0.5,
             perturbation: Perturbation::BarkerHenderson,
+            ..Default::default()
         };
 
-        let eos_bh = Rc::new(UVTheory::with_options(Rc::new(uv_parameters), options));
+        let eos_bh = Rc::new(UVTheory::with_options(Arc::new(uv_parameters), options).unwrap());
 
         let state_bh = State::new_nvt(&eos_bh, t_x, volume, &moles).unwrap();
         let a_bh = state_bh
@@ -236,12 +726,12 @@ mod test {
         let reduced_temperature = 1.0;
         let t_x = reduced_temperature * p.epsilon_k[0] * KELVIN;
         let reduced_density = 0.9;
-        let moles = arr1(&vec![0.4, 0.6]) * MOL;
+        let moles = arr1(&[0.4, 0.6]) * MOL;
         let total_moles = moles.sum();
         let volume = (p.sigma[0] * ANGSTROM).powi(3) / reduced_density * NAV * total_moles;
 
         // EoS
-        let eos_wca = Rc::new(UVTheory::new(Rc::new(p)));
+        let eos_wca = Rc::new(UVTheory::new(Arc::new(p)));
         let state_wca = State::new_nvt(&eos_wca, t_x, volume, &moles).unwrap();
         let a_wca = state_wca
             .molar_helmholtz_energy(Contributions::ResidualNvt)
@@ -265,12 +755,12 @@ mod test {
         let t_x = reduced_temperature * p.epsilon_k[0] * KELVIN;
         let sigma_x_3 = (0.4 + 0.6 * 8.0) * ANGSTROM.powi(3);
         let density = 0.52000000000000002 / sigma_x_3;
-        let moles = arr1(&vec![0.4, 0.6]) * MOL;
+        let moles = arr1(&[0.4, 0.6]) * MOL;
         let total_moles = moles.sum();
         let volume = NAV * total_moles / density;
 
         // EoS
-        let eos_wca = Rc::new(UVTheory::new(Rc::new(p)));
+        let eos_wca = Rc::new(UVTheory::new(Arc::new(p)));
         let state_wca = State::new_nvt(&eos_wca, t_x, volume, &moles).unwrap();
         let a_wca = state_wca
             .molar_helmholtz_energy(Contributions::ResidualNvt)
@@ -278,4 +768,201 @@ mod test {
             .unwrap();
         assert_relative_eq!(a_wca, -0.034206207363139396, max_relative = 1e-5)
     }
+
+    #[test]
+    fn second_virial_coefficient_truncated() {
+        // Lennard-Jones potential (full, truncated and truncated-shifted at 2.5 sigma)
+        let potentials = [
+            (None, false, MiePotential::new(12.0, 6.0)),
+            (
+                Some(2.5),
+                false,
+                MiePotential::new(12.0, 6.0).truncated(2.5, false),
+            ),
+            (
+                Some(2.5),
+                true,
+                MiePotential::new(12.0, 6.0).truncated(2.5, true),
+            ),
+        ];
+        let parameters = Arc::new(test_parameters(12.0, 6.0, 1.0, 1.0));
+        for (cutoff_radius, shift_potential, mie) in potentials {
+            let options = UVTheoryOptions {
+                cutoff_radius,
+                shift_potential,
+                ..Default::default()
+            };
+            let eos = UVTheory::with_options(parameters.clone(), options).unwrap();
+            for t in [0.8, 1.5, 4.0] {
+                let b2_eos = eos
+                    .second_virial_coefficient(t * KELVIN, None)
+                    .unwrap()
+                    .to_reduced(ANGSTROM.powi(3) * NAV)
+                    .unwrap();
+                assert_relative_eq!(
+                    b2_eos,
+                    mie.second_virial_coefficient(t),
+                    max_relative = 1e-2
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn second_virial_coefficient_exact() {
+        // the correlations of both divisions reproduce the second virial coefficient
+        // of Mie n-6 fluids
+        for perturbation in [
+            Perturbation::BarkerHenderson,
+            Perturbation::WeeksChandlerAndersen,
+        ] {
+            for rep in [10.0, 12.0, 24.0] {
+                let parameters = Arc::new(test_parameters(rep, 6.0, 1.0, 1.0));
+                let options = UVTheoryOptions {
+                    perturbation: perturbation.clone(),
+                    ..Default::default()
+                };
+                let eos = UVTheory::with_options(parameters, options).unwrap();
+                for t in [1.0, 2.0, 4.0] {
+                    let b2_eos = eos
+                        .second_virial_coefficient(t * KELVIN, None)
+                        .unwrap()
+                        .to_reduced(ANGSTROM.powi(3) * NAV)
+                        .unwrap();
+                    let b2 = MiePotential::new(rep, 6.0).second_virial_coefficient(t);
+                    assert_relative_eq!(b2_eos, b2, epsilon = 5e-2);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn feynman_hibbs_hydrogen() {
+        let b2 = |fh, t: f64| {
+            let identifier = Identifier::new("hydrogen", None, None, None, None, None);
+            let model_record = UVRecord {
+                fh,
+                ..UVRecord::new(9.0, 6.0, 3.0243, 26.706)
+            };
+            let pr = PureRecord::new(identifier, 2.0157309551872, model_record, None);
+            UVTheory::new(Arc::new(UVParameters::new_pure(pr)))
+                .second_virial_coefficient(t * KELVIN, None)
+                .unwrap()
+                .to_reduced(ANGSTROM.powi(3) * NAV)
+                .unwrap()
+        };
+        // the quantum corrections are repulsive at low temperatures ...
+        assert!(b2(Some(1), 30.0) > b2(None, 30.0) + 10.0);
+        assert!(b2(Some(2), 30.0) > b2(None, 30.0) + 10.0);
+        // ... and vanish in the classical limit
+        assert_relative_eq!(b2(Some(1), 3000.0), b2(None, 3000.0), max_relative = 1e-2);
+    }
+
+    #[test]
+    fn temperature_dependent_k_ij() {
+        let parameters = |binary_record: UVBinaryRecord| {
+            let record = |name: &str, rep, sigma, epsilon_k| {
+                let identifier = Identifier::new(name, None, None, None, None, None);
+                let model_record = UVRecord::new(rep, 6.0, sigma, epsilon_k);
+                PureRecord::new(identifier, 1.0, model_record, None)
+            };
+            let pure_records = vec![
+                record("1", 12.0, 3.7039, 150.03),
+                record("2", 16.0, 4.2, 250.0),
+            ];
+            let binary_records = Array2::from_elem((2, 2), binary_record);
+            Arc::new(UVParameters::from_records(pure_records, binary_records))
+        };
+        let (k_ij, k_ij_t, k_ij_ln_t) = (0.02, -15.0, 0.01);
+        let p_t = parameters(UVBinaryRecord {
+            k_ij,
+            k_ij_t,
+            k_ij_ln_t,
+            ..Default::default()
+        });
+
+        let t = 250.0;
+        let v = 1e4;
+        let moles = arr1(&[0.4, 0.6]);
+        let residual = |eos: &UVTheory, t: Dual64| {
+            let state = StateHD::new(t, Dual64::from(v), moles.mapv(Dual64::from));
+            eos.residual()
+                .iter()
+                .map(|c| c.helmholtz_energy(&state))
+                .sum::<Dual64>()
+        };
+        for perturbation in [
+            Perturbation::BarkerHenderson,
+            Perturbation::WeeksChandlerAndersen,
+        ] {
+            let options = UVTheoryOptions {
+                perturbation,
+                ..Default::default()
+            };
+            // constant k_ij evaluated at the same temperature
+            let p = parameters(UVBinaryRecord::from(k_ij + k_ij_t / t + k_ij_ln_t * t.ln()));
+            let eos = UVTheory::with_options(p, options.clone()).unwrap();
+            let eos_t = UVTheory::with_options(p_t.clone(), options).unwrap();
+            let a = residual(&eos_t, Dual64::from(t).derive());
+            assert_relative_eq!(
+                a.re,
+                residual(&eos, Dual64::from(t)).re,
+                max_relative = 1e-12
+            );
+
+            // temperature derivative includes the derivative of k_ij(T)
+            let h = 1e-4;
+            let da_dt = (residual(&eos_t, Dual64::from(t + h)).re
+                - residual(&eos_t, Dual64::from(t - h)).re)
+                / (2.0 * h);
+            assert_relative_eq!(a.eps[0], da_dt, max_relative = 1e-6);
+        }
+    }
+
+    #[test]
+    fn ideal_gas_heat_capacity() {
+        let record = r#"
+            {
+                "identifier": {
+                    "cas": "74-82-8",
+                    "name": "methane"
+                },
+                "model_record": {
+                    "m": 1.0,
+                    "rep": 12.0,
+                    "att": 6.0,
+                    "sigma": 3.7039,
+                    "epsilon_k": 150.03
+                },
+                "ideal_gas_record": {
+                    "a": 19.25,
+                    "b": 0.05213,
+                    "c": 1.197e-05,
+                    "d": -1.132e-08,
+                    "e": 0.0
+                },
+                "molarweight": 16.043
+            }
+        "#;
+        let record: PureRecord<UVRecord, JobackRecord> = serde_json::from_str(record).unwrap();
+        let eos = Rc::new(UVTheory::new(Arc::new(UVParameters::new_pure(record))));
+
+        // at low densities, the heat capacity approaches the ideal gas heat capacity
+        let t = 300.0 * KELVIN;
+        let s = State::new_pure(&eos, t, 1e-6 * MOL / METER.powi(3)).unwrap();
+        let tr = 300.0;
+        let c_p_ig = 19.25 + 0.05213 * tr + 1.197e-05 * tr.powi(2) - 1.132e-08 * tr.powi(3);
+        assert_relative_eq!(
+            s.c_p(Contributions::Total)
+                .to_reduced(JOULE / MOL / KELVIN)
+                .unwrap(),
+            c_p_ig,
+            max_relative = 1e-6
+        );
+        assert_relative_eq!(
+            s.mass_density().to_reduced(GRAM / METER.powi(3)).unwrap(),
+            16.043e-6,
+            max_relative = 1e-12
+        );
+    }
 }
@@ -1,15 +1,17 @@
 use super::hard_sphere_bh::{
-    diameter_bh, packing_fraction, packing_fraction_a, packing_fraction_b,
+    diameter_bh, dimensionless_length_scale_ij, packing_fraction, packing_fraction_a,
+    packing_fraction_b,
 };
+use super::segment_state;
 use crate::parameters::*;
 use feos_core::{HelmholtzEnergyDual, StateHD};
 use num_dual::DualNum;
 use std::fmt;
-use std::{f64::consts::PI, rc::Rc};
+use std::{f64::consts::PI, sync::Arc};
 
 #[derive(Debug, Clone)]
 pub struct ReferencePerturbationBH {
-    pub parameters: Rc<UVParameters>,
+    pub parameters: Arc<UVParameters>,
 }
 
 impl fmt::Display for ReferencePerturbationBH {
@@ -21,22 +23,25 @@ impl fmt::Display for ReferencePerturbationBH {
 impl<D: DualNum<f64>> HelmholtzEnergyDual<D> for ReferencePerturbationBH {
     /// Helmholtz energy for perturbation reference (Mayer-f), eq. 29
     fn helmholtz_energy(&self, state: &StateHD<D>) -> D {
+        let state = &segment_state(&self.parameters, state);
         let p = &self.parameters;
         let n = p.sigma.len();
         let x = &state.molefracs;
-        let d = diameter_bh(&p, state.temperature);
+        let effective = p.effective_parameters(state.temperature);
+        let d = diameter_bh(p, state.temperature);
         let eta = packing_fraction(&state.partial_density, &d);
-        let eta_a = packing_fraction_a(p, &d, eta);
-        let eta_b = packing_fraction_b(p, &d, eta);
         let mut a = D::zero();
         for i in 0..n {
             for j in 0..n {
                 let d_ij = (d[i] + d[j]) * 0.5; // (d[i] * p.sigma[i] + d[j] * p.sigma[j]) * 0.5;
+                let tau_ij = dimensionless_length_scale_ij(&effective, &d, i, j);
+                let eta_a = packing_fraction_a(eta, tau_ij, p.rep_ij[[i, j]]);
+                let eta_b = packing_fraction_b(eta, tau_ij);
                 a += x[i]
                     * x[j]
-                    * (((-eta_a[[i, j]] * 0.5 + 1.0) / (-eta_a[[i, j]] + 1.0).powi(3))
-                        - ((-eta_b[[i, j]] * 0.5 + 1.0) / (-eta_b[[i, j]] + 1.0).powi(3)))
-                    * (-d_ij.powi(3) + p.sigma_ij[[i, j]].powi(3))
+                    * (((-eta_a * 0.5 + 1.0) / (-eta_a + 1.0).powi(3))
+                        - ((-eta_b * 0.5 + 1.0) / (-eta_b + 1.0).powi(3)))
+                    * (-d_ij.powi(3) + effective.sigma_ij(i, j).powi(3))
             }
         }
 
@@ -62,7 +67,7 @@ mod test {
 
         let p = test_parameters(24.0, 6.0, 1.0, 1.0);
         let pt = ReferencePerturbationBH {
-            parameters: Rc::new(p),
+            parameters: Arc::new(p),
         };
         let state = StateHD::new(reduced_temperature, reduced_volume, moles.clone());
         let a = pt.helmholtz_energy(&state) / moles[0];
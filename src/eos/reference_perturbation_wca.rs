@@ -1,18 +1,17 @@
 use super::hard_sphere_wca::{
-
-    diameter_wca, dimensionless_diameter_q_wca, packing_fraction, packing_fraction_a,
-    packing_fraction_b,
-
+    diameter_wca, dimensionless_diameter_q_wca_ij, dimensionless_length_scale_ij, packing_fraction,
+    packing_fraction_a, packing_fraction_b,
 };
+use super::segment_state;
 use crate::parameters::*;
 use feos_core::{HelmholtzEnergyDual, StateHD};
 use num_dual::DualNum;
 use std::fmt;
-use std::{f64::consts::PI, rc::Rc};
+use std::{f64::consts::PI, sync::Arc};
 
 #[derive(Debug, Clone)]
 pub struct ReferencePerturbationWCA {
-    pub parameters: Rc<UVParameters>,
+    pub parameters: Arc<UVParameters>,
 }
 
 impl fmt::Display for ReferencePerturbationWCA {
@@ -24,36 +23,35 @@ impl fmt::Display for ReferencePerturbationWCA {
 impl<D: DualNum<f64>> HelmholtzEnergyDual<D> for ReferencePerturbationWCA {
     /// Helmholtz energy for perturbation reference (Mayer-f), eq. 29
     fn helmholtz_energy(&self, state: &StateHD<D>) -> D {
+        let state = &segment_state(&self.parameters, state);
         let p = &self.parameters;
         let n = p.sigma.len();
         let x = &state.molefracs;
-        let d = diameter_wca(&p, state.temperature);
-        //let q = diameter_q_wca(&p, state.temperature);
+        let d = diameter_wca(p, state.temperature);
+        let effective = p.effective_parameters(state.temperature);
+        //let q = diameter_q_wca(p, state.temperature);
         let eta = packing_fraction(&state.partial_density, &d);
-        let eta_a = packing_fraction_a(p, eta, state.temperature);
-        let eta_b = packing_fraction_b(p, eta, state.temperature);
         let mut a = D::zero();
 
         for i in 0..n {
             for j in 0..n {
-                let rs_ij = ((p.rep[i] / p.att[i]).powf(1.0 / (p.rep[i] - p.att[i]))
-                    + (p.rep[j] / p.att[j]).powf(1.0 / (p.rep[j] - p.att[j])))
-                    * 0.5; // MIXING RULE not clear!!!
                 let d_ij = (d[i] + d[j]) * 0.5; // (d[i] * p.sigma[i] + d[j] * p.sigma[j]) * 0.5;
 
-                let t_ij = state.temperature / p.eps_k_ij[[i, j]];
-                let rep_ij = p.rep_ij[[i, j]];
-                let att_ij = p.att_ij[[i, j]];
-                let q_ij = dimensionless_diameter_q_wca(t_ij, D::from(rep_ij), D::from(att_ij))
-                    * p.sigma_ij[[i, j]];
-
+                let t_ij = state.temperature / effective.eps_k_ij(i, j);
+                let tau_ij = dimensionless_length_scale_ij(p, &d, &effective, i, j);
+                let eta_a = packing_fraction_a(eta, tau_ij, p.rep_ij[[i, j]]);
+                let eta_b = packing_fraction_b(eta, tau_ij);
+                // position of the minimum of the cross potential
+                let rs_ij = p.rm_ij[[i, j]];
+                let q_ij =
+                    dimensionless_diameter_q_wca_ij(p, i, j, t_ij) * effective.sigma_ij(i, j);
 
                 a += x[i]
                     * x[j]
-                    * ((-eta_a[[i, j]] * 0.5 + 1.0) / (-eta_a[[i, j]] + 1.0).powi(3)
-                        * (-q_ij.powi(3) + (rs_ij * p.sigma_ij[[i, j]]).powi(3))
-                        - ((-eta_b[[i, j]] * 0.5 + 1.0) / (-eta_b[[i, j]] + 1.0).powi(3))
-                            * (-d_ij.powi(3) + (rs_ij * p.sigma_ij[[i, j]]).powi(3)))
+                    * ((-eta_a * 0.5 + 1.0) / (-eta_a + 1.0).powi(3)
+                        * (-q_ij.powi(3) + (effective.sigma_ij(i, j) * rs_ij).powi(3))
+                        - ((-eta_b * 0.5 + 1.0) / (-eta_b + 1.0).powi(3))
+                            * (-d_ij.powi(3) + (effective.sigma_ij(i, j) * rs_ij).powi(3)))
             }
         }
 
@@ -65,6 +63,7 @@ impl<D: DualNum<f64>> HelmholtzEnergyDual<D> for ReferencePerturbationWCA {
 mod test {
     use super::*;
     use crate::parameters::utils::{test_parameters, test_parameters_mixture};
+    use crate::virial::MiePotential;
     use approx::assert_relative_eq;
     use ndarray::arr1;
 
@@ -80,7 +79,7 @@ mod test {
 
         let p = test_parameters(24.0, 6.0, 1.0, 1.0);
         let pt = ReferencePerturbationWCA {
-            parameters: Rc::new(p),
+            parameters: Arc::new(p),
         };
         let state = StateHD::new(reduced_temperature, reduced_volume, moles.clone());
         let a = pt.helmholtz_energy(&state) / moles[0];
@@ -88,7 +87,6 @@ mod test {
     }
     #[test]
     fn test_delta_a0_wca_mixture() {
-
         let moles = arr1(&[0.40000000000000002, 0.59999999999999998]);
         let reduced_temperature = 1.0;
         let reduced_density = 0.90000000000000002;
@@ -102,12 +100,36 @@ mod test {
         );
 
         let pt = ReferencePerturbationWCA {
-            parameters: Rc::new(p),
+            parameters: Arc::new(p),
         };
         let state = StateHD::new(reduced_temperature, reduced_volume, moles.clone());
         let a = pt.helmholtz_energy(&state) / (moles[0] + moles[1]);
 
         assert_relative_eq!(a, 0.308268896386771, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_delta_a0_wca_cross_minimum() {
+        // the cross pair is cut at the minimum of the cross potential, which
+        // differs from the mean of the pure component minima for rep_i != rep_j
+        let p = test_parameters_mixture(
+            arr1(&[12.0, 24.0]),
+            arr1(&[6.0, 6.0]),
+            arr1(&[1.0, 1.0]),
+            arr1(&[1.0, 1.0]),
+        );
+        let mie = MiePotential::new(p.rep_ij[[0, 1]], 6.0);
+        let (rm, h) = (p.rm_ij[[0, 1]], 1e-5);
+        assert!((mie.potential(rm + h) - mie.potential(rm - h)).abs() / h < 1e-6);
+        assert!((rm - (p.rm[0] + p.rm[1]) * 0.5).abs() > 1e-3);
 
+        let moles = arr1(&[0.4, 0.6]);
+        let state = StateHD::new(1.0, moles.sum() / 0.9, moles.clone());
+        let pt = ReferencePerturbationWCA {
+            parameters: Arc::new(p),
+        };
+        let a = pt.helmholtz_energy(&state) / moles.sum();
+        // the mean of the pure component minima results in 0.2578793452521468
+        assert_relative_eq!(a, 0.2580146837496113, epsilon = 1e-10);
     }
 }
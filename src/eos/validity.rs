@@ -0,0 +1,348 @@
+use super::UVTheory;
+use crate::errors::{UVTheoryError, UVTheoryResult};
+use crate::parameters::UVParameters;
+use feos_core::{EosResult, HelmholtzEnergy, HelmholtzEnergyDual, State, StateHD};
+use ndarray::Array1;
+use num_dual::DualNum;
+use quantity::si::*;
+use std::fmt;
+use std::sync::Arc;
+
+/// Range of parameters and states covered by the simulation data to which
+/// the correlations of uv-theory were fitted.
+///
+/// The temperature is reduced by the energy parameter of every component and
+/// the density is the segment density reduced by the segment diameters,
+/// `rho* = rho sum_i x_i m_i sigma_i^3`. The default values enclose the
+/// molecular dynamics data of Mie n-6 fluids to which the correlations were
+/// fitted (van Westen and Gross, J. Chem. Phys. 155, 244501 (2021),
+/// doi:10.1063/5.0073572): repulsive exponents from 9 to 48 and fluid states
+/// from the low temperature liquid at `T* = 0.5` up to `T* = 10`, with
+/// segment densities up to the freezing line. Results outside of the domain
+/// are extrapolations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ValidityDomain {
+    /// Minimum and maximum repulsive exponent.
+    pub rep: [f64; 2],
+    /// Minimum and maximum reduced temperature `T* = T / (epsilon / k)`.
+    pub reduced_temperature: [f64; 2],
+    /// Maximum reduced segment density.
+    pub max_reduced_density: f64,
+}
+
+impl Default for ValidityDomain {
+    fn default() -> Self {
+        Self {
+            rep: [9.0, 48.0],
+            reduced_temperature: [0.5, 10.0],
+            max_reduced_density: 1.2,
+        }
+    }
+}
+
+/// Parameter or state variable outside of the [ValidityDomain].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidityViolation {
+    RepulsiveExponent {
+        component: usize,
+        rep: f64,
+    },
+    Temperature {
+        component: usize,
+        reduced_temperature: f64,
+    },
+    Density {
+        reduced_density: f64,
+    },
+}
+
+impl fmt::Display for ValidityViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RepulsiveExponent { component, rep } => {
+                write!(f, "repulsive exponent of component {}: {}", component, rep)
+            }
+            Self::Temperature {
+                component,
+                reduced_temperature,
+            } => write!(
+                f,
+                "reduced temperature of component {}: {}",
+                component, reduced_temperature
+            ),
+            Self::Density { reduced_density } => {
+                write!(f, "reduced segment density: {}", reduced_density)
+            }
+        }
+    }
+}
+
+impl ValidityDomain {
+    fn contains(range: [f64; 2], value: f64) -> bool {
+        value >= range[0] && value <= range[1]
+    }
+
+    /// Parameters outside of the domain.
+    pub fn check_parameters(&self, parameters: &UVParameters) -> Vec<ValidityViolation> {
+        parameters
+            .rep
+            .iter()
+            .enumerate()
+            .filter(|&(_, &rep)| !Self::contains(self.rep, rep))
+            .map(|(component, &rep)| ValidityViolation::RepulsiveExponent { component, rep })
+            .collect()
+    }
+
+    /// Parameters and state variables outside of the domain.
+    ///
+    /// The temperature is given in units of Kelvin and the partial densities
+    /// in units of molecules per cubic Angstrom.
+    fn check_reduced_state(
+        &self,
+        parameters: &UVParameters,
+        temperature: f64,
+        partial_density: &Array1<f64>,
+    ) -> Vec<ValidityViolation> {
+        let mut violations = self.check_parameters(parameters);
+        violations.append(&mut self.check_state_variables(
+            parameters,
+            temperature,
+            partial_density,
+        ));
+        violations
+    }
+
+    /// State variables outside of the domain, see [ValidityDomain::check_reduced_state].
+    pub(crate) fn check_state_variables(
+        &self,
+        parameters: &UVParameters,
+        temperature: f64,
+        partial_density: &Array1<f64>,
+    ) -> Vec<ValidityViolation> {
+        let mut violations = Vec::new();
+        for (component, &epsilon_k) in parameters.epsilon_k.iter().enumerate() {
+            let reduced_temperature = temperature / epsilon_k;
+            if !Self::contains(self.reduced_temperature, reduced_temperature) {
+                violations.push(ValidityViolation::Temperature {
+                    component,
+                    reduced_temperature,
+                });
+            }
+        }
+        let reduced_density =
+            (partial_density * &parameters.m * &parameters.sigma.mapv(|s| s.powi(3))).sum();
+        if reduced_density > self.max_reduced_density {
+            violations.push(ValidityViolation::Density { reduced_density });
+        }
+        violations
+    }
+
+    /// Parameters and state variables outside of the domain.
+    pub fn check_state(
+        &self,
+        parameters: &UVParameters,
+        temperature: SINumber,
+        density: SINumber,
+        molefracs: &Array1<f64>,
+    ) -> EosResult<Vec<ValidityViolation>> {
+        let density = (density * NAV * ANGSTROM.powi(3)).into_value()?;
+        Ok(self.check_reduced_state(
+            parameters,
+            temperature.to_reduced(KELVIN)?,
+            &(molefracs * density),
+        ))
+    }
+}
+
+impl UVTheory {
+    /// Parameters and state variables of `state` outside of the [ValidityDomain]
+    /// of the options or the default domain.
+    pub fn validity_violations(&self, state: &State<SIUnit, UVTheory>) -> Vec<ValidityViolation> {
+        self.options
+            .validity_domain
+            .unwrap_or_default()
+            .check_reduced_state(
+                &self.parameters,
+                state.temperature.to_reduced(KELVIN).unwrap(),
+                &(state.partial_density.to_reduced(ANGSTROM.powi(-3) / NAV)).unwrap(),
+            )
+    }
+
+    /// Checks `state` in strict mode.
+    ///
+    /// Returns the state, or [UVTheoryError::OutsideValidityDomain] if strict mode
+    /// is enabled and the state is outside of the [ValidityDomain] of the options.
+    /// Without strict mode, every state is returned unchanged.
+    pub fn check_state(
+        &self,
+        state: State<SIUnit, UVTheory>,
+    ) -> UVTheoryResult<State<SIUnit, UVTheory>> {
+        if self.options.validity_domain.is_some() {
+            let violations = self.validity_violations(&state);
+            if !violations.is_empty() {
+                return Err(UVTheoryError::OutsideValidityDomain(violations));
+            }
+        }
+        Ok(state)
+    }
+}
+
+/// Residual Helmholtz energy contribution in strict mode.
+///
+/// The contribution is NaN for states outside of the validity domain, so that
+/// every property and phase equilibrium evaluated at such a state fails.
+pub(crate) struct StrictContribution {
+    parameters: Arc<UVParameters>,
+    domain: ValidityDomain,
+    contribution: Box<dyn HelmholtzEnergy>,
+}
+
+impl StrictContribution {
+    pub fn new(
+        parameters: Arc<UVParameters>,
+        domain: ValidityDomain,
+        contribution: Box<dyn HelmholtzEnergy>,
+    ) -> Self {
+        Self {
+            parameters,
+            domain,
+            contribution,
+        }
+    }
+}
+
+impl<D: DualNum<f64>> HelmholtzEnergyDual<D> for StrictContribution
+where
+    dyn HelmholtzEnergy: HelmholtzEnergyDual<D>,
+{
+    fn helmholtz_energy(&self, state: &StateHD<D>) -> D {
+        let violations = self.domain.check_state_variables(
+            &self.parameters,
+            state.temperature.re(),
+            &state.partial_density.mapv(|rho| rho.re()),
+        );
+        if violations.is_empty() {
+            self.contribution.helmholtz_energy(state)
+        } else {
+            D::from(f64::NAN)
+        }
+    }
+}
+
+impl fmt::Display for StrictContribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.contribution)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::eos::UVTheoryOptions;
+    use crate::parameters::utils::test_parameters;
+    use crate::UVTheoryFunctional;
+    use feos_core::{Contributions, PhaseEquilibrium};
+    use feos_dft::fundamental_measure_theory::FMTVersion;
+    use ndarray::arr1;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    fn strict() -> UVTheoryOptions {
+        UVTheoryOptions {
+            validity_domain: Some(ValidityDomain::default()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parameters_outside_domain() {
+        let parameters = Arc::new(test_parameters(60.0, 6.0, 1.0, 1.0));
+        assert_eq!(
+            ValidityDomain::default().check_parameters(&parameters),
+            vec![ValidityViolation::RepulsiveExponent {
+                component: 0,
+                rep: 60.0
+            }]
+        );
+        assert!(matches!(
+            UVTheory::with_options(parameters.clone(), strict()),
+            Err(UVTheoryError::OutsideValidityDomain(_))
+        ));
+        assert!(matches!(
+            UVTheoryFunctional::with_options(parameters.clone(), FMTVersion::WhiteBear, strict()),
+            Err(UVTheoryError::OutsideValidityDomain(_))
+        ));
+        assert!(UVTheory::with_options(parameters, Default::default()).is_ok());
+    }
+
+    #[test]
+    fn strict_mode() {
+        let parameters = Arc::new(test_parameters(12.0, 6.0, 1.0, 1.0));
+        let eos = Rc::new(UVTheory::with_options(parameters.clone(), strict()).unwrap());
+        let density = 0.6 / (NAV * ANGSTROM.powi(3));
+
+        let state = State::new_pure(&eos, 1.5 * KELVIN, density).unwrap();
+        assert!(eos.validity_violations(&state).is_empty());
+        let state = eos.check_state(state).unwrap();
+        let p = state.pressure(Contributions::Total);
+        let eos_default = Rc::new(UVTheory::new(parameters));
+        let state_default = State::new_pure(&eos_default, 1.5 * KELVIN, density).unwrap();
+        assert_eq!(p, state_default.pressure(Contributions::Total));
+        // no additional contribution in strict mode
+        assert_eq!(
+            state.helmholtz_energy_contributions().len(),
+            state_default.helmholtz_energy_contributions().len()
+        );
+
+        let state = State::new_pure(&eos, 0.3 * KELVIN, density).unwrap();
+        let violation = ValidityViolation::Temperature {
+            component: 0,
+            reduced_temperature: 0.3,
+        };
+        assert_eq!(eos.validity_violations(&state), vec![violation.clone()]);
+        // strict mode refuses to evaluate the state
+        assert!(state
+            .pressure(Contributions::Total)
+            .to_reduced(PASCAL)
+            .unwrap()
+            .is_nan());
+        assert!(PhaseEquilibrium::pure(&eos, 0.3 * KELVIN, None, Default::default()).is_err());
+        assert!(matches!(
+            eos.check_state(state),
+            Err(UVTheoryError::OutsideValidityDomain(v)) if v == vec![violation]
+        ));
+        // the same state is an extrapolation without strict mode
+        let state = State::new_pure(&eos_default, 0.3 * KELVIN, density).unwrap();
+        assert!(!eos_default.validity_violations(&state).is_empty());
+        let state = eos_default.check_state(state).unwrap();
+        assert!(state
+            .pressure(Contributions::Total)
+            .to_reduced(PASCAL)
+            .unwrap()
+            .is_finite());
+
+        let violations = ValidityDomain::default()
+            .check_state(&eos.parameters, 2.0 * KELVIN, 2.5 * density, &arr1(&[1.0]))
+            .unwrap();
+        assert_eq!(violations.len(), 1);
+        assert!(matches!(violations[0], ValidityViolation::Density { .. }));
+    }
+
+    #[test]
+    fn strict_mode_functional() {
+        let parameters = Arc::new(test_parameters(12.0, 6.0, 1.0, 1.0));
+        let func = Rc::new(
+            UVTheoryFunctional::with_options(parameters, FMTVersion::WhiteBear, strict()).unwrap(),
+        );
+        let density = 0.6 / (NAV * ANGSTROM.powi(3));
+        let pressure = |t: f64| {
+            State::new_pure(&func, t * KELVIN, density)
+                .unwrap()
+                .pressure(Contributions::Total)
+                .to_reduced(PASCAL)
+                .unwrap()
+        };
+        assert!(pressure(1.5).is_finite());
+        assert!(pressure(0.3).is_nan());
+    }
+}
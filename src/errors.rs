@@ -0,0 +1,88 @@
+use crate::eos::ValidityViolation;
+use feos_core::parameter::ParameterError;
+use feos_core::EosError;
+use quantity::QuantityError;
+use thiserror::Error;
+
+/// Error type for invalid parameters and options of uv-theory.
+#[derive(Error, Debug)]
+pub enum UVTheoryError {
+    /// A parameter of a pure component or a pair of components is invalid.
+    #[error("invalid parameter {field}={value}{}: {reason}", component_suffix(.component))]
+    InvalidParameter {
+        /// Identifier of the component or pair of components, if known.
+        component: Option<String>,
+        field: &'static str,
+        value: String,
+        reason: String,
+    },
+    /// The parameters are valid on their own, but cannot be combined.
+    #[error("incompatible parameters: {0}")]
+    IncompatibleParameters(String),
+    #[error("incompatible options: {0}")]
+    IncompatibleOptions(String),
+    #[error("outside of the validity domain of uv-theory: {}", join(.0))]
+    OutsideValidityDomain(Vec<ValidityViolation>),
+    /// Experimental data or a parameter table that is inconsistent or given in the wrong units.
+    #[error("invalid data: {0}")]
+    InvalidData(String),
+    #[error(transparent)]
+    ParameterError(#[from] ParameterError),
+    #[error(transparent)]
+    EosError(#[from] EosError),
+}
+
+fn join(violations: &[ValidityViolation]) -> String {
+    violations
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn component_suffix(component: &Option<String>) -> String {
+    component
+        .as_ref()
+        .map_or_else(String::new, |c| format!(" of {}", c))
+}
+
+impl UVTheoryError {
+    pub(crate) fn invalid_parameter<V: ToString>(
+        field: &'static str,
+        value: V,
+        reason: &str,
+    ) -> Self {
+        Self::InvalidParameter {
+            component: None,
+            field,
+            value: value.to_string(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Attach the identifier of the component to an [UVTheoryError::InvalidParameter].
+    pub(crate) fn of_component<C: ToString>(self, name: C) -> Self {
+        match self {
+            Self::InvalidParameter {
+                component: None,
+                field,
+                value,
+                reason,
+            } => Self::InvalidParameter {
+                component: Some(name.to_string()),
+                field,
+                value,
+                reason,
+            },
+            e => e,
+        }
+    }
+}
+
+impl From<QuantityError> for UVTheoryError {
+    fn from(e: QuantityError) -> Self {
+        Self::EosError(e.into())
+    }
+}
+
+pub type UVTheoryResult<T> = Result<T, UVTheoryError>;
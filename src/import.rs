@@ -0,0 +1,551 @@
+//! Import of parameter tables of published Mie force fields.
+use crate::errors::{UVTheoryError, UVTheoryResult};
+use crate::{CombiningRule, UVBinaryRecord, UVParameters, UVRecord};
+use feos_core::joback::JobackRecord;
+use feos_core::parameter::{
+    BinaryRecord, Identifier, IdentifierOption, ParameterError, PureRecord,
+};
+use ndarray::Array2;
+use quantity::si::{JOULE, KELVIN, MOL, RGAS};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Published Mie force field.
+///
+/// The force fields differ in the names of the columns of their parameter
+/// tables, in the default exponents and in the combining rules for the cross
+/// interactions. Units are inferred from the column names:
+///
+/// |quantity|columns|
+/// |-|-|
+/// |name|`name`, `molecule`, `compound`, `pseudo_atom`, `site`, `group`|
+/// |molar weight in g/mol|`molarweight`, `molar_mass`, `mw`|
+/// |segment diameter in Å|`sigma`, `sigma_a`, `sigma_angstrom`, `σ`|
+/// |segment diameter in nm|`sigma_nm`|
+/// |energy parameter in K|`epsilon_k`, `eps_k`, `ε_k`|
+/// |energy parameter in kJ/mol, kcal/mol or J/mol|`epsilon_kj_mol`, `epsilon_kcal_mol`, `epsilon_j_mol`|
+///
+/// Column names are case insensitive and spaces, dashes and slashes are read
+/// as underscores, i.e., `epsilon/k` is the same as `epsilon_k`. Optional
+/// columns are `cas`, `smiles`, `iupac_name`, `inchi` and `formula`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ForceField {
+    /// SAFT-γ Mie (Papaioannou et al., 2014) and SAFT-VR Mie (Lafitte et al., 2013)
+    /// with the number of segments `m_s`, the exponents `lambda_r` and
+    /// `lambda_a` and the combining rules of Lafitte et al.
+    SaftGammaMie,
+    /// Mie potentials of Potoff and Bernard-Brunel (2009) with the repulsive
+    /// exponent `n`, an attractive exponent of 6 and Lorentz-Berthelot combining rules.
+    MiePotoff,
+    /// TraPPE-like single-site Lennard-Jones models (repulsive exponent 12) with
+    /// Lorentz-Berthelot combining rules.
+    TraPPE,
+}
+
+impl ForceField {
+    /// Combining rule for the cross interactions used by the force field.
+    pub fn combining_rule(&self) -> CombiningRule {
+        match self {
+            Self::SaftGammaMie => CombiningRule::Lafitte,
+            Self::MiePotoff | Self::TraPPE => CombiningRule::LorentzBerthelot,
+        }
+    }
+
+    fn rep_columns(&self) -> &'static [&'static str] {
+        match self {
+            Self::SaftGammaMie => &["lambda_r", "lr", "λ_r", "λr"],
+            Self::MiePotoff => &["n", "lambda_r", "λ_r", "λr"],
+            Self::TraPPE => &[],
+        }
+    }
+
+    fn att_columns(&self) -> &'static [&'static str] {
+        match self {
+            Self::SaftGammaMie => &["lambda_a", "la", "λ_a", "λa"],
+            Self::MiePotoff | Self::TraPPE => &[],
+        }
+    }
+
+    fn m_columns(&self) -> &'static [&'static str] {
+        match self {
+            Self::SaftGammaMie => &["m_s", "ms", "m"],
+            Self::MiePotoff | Self::TraPPE => &["m"],
+        }
+    }
+
+    /// Repulsive exponent of force fields that do not list it.
+    fn default_rep(&self) -> Option<f64> {
+        match self {
+            Self::TraPPE => Some(12.0),
+            Self::SaftGammaMie | Self::MiePotoff => None,
+        }
+    }
+}
+
+const NAME: &[&str] = &[
+    "name",
+    "molecule",
+    "compound",
+    "pseudo_atom",
+    "site",
+    "group",
+];
+const MOLARWEIGHT: &[&str] = &["molarweight", "molar_mass", "mw"];
+
+/// Energy per mole in units of `epsilon / k`.
+fn kelvin_per(energy_per_mole: f64) -> f64 {
+    (energy_per_mole * JOULE / MOL / RGAS)
+        .to_reduced(KELVIN)
+        .unwrap()
+}
+
+/// A row of a parameter table with normalized column names.
+struct Row<'a> {
+    index: usize,
+    values: &'a HashMap<String, String>,
+}
+
+impl<'a> Row<'a> {
+    fn invalid(&self, reason: String) -> UVTheoryError {
+        UVTheoryError::InvalidData(format!("row {}: {}", self.index + 1, reason))
+    }
+
+    fn string(&self, columns: &[&str]) -> Option<&'a str> {
+        columns
+            .iter()
+            .find_map(|c| self.values.get(*c))
+            .map(|s| s.as_str())
+            .filter(|s| !s.is_empty())
+    }
+
+    fn number(&self, columns: &[&str]) -> UVTheoryResult<Option<f64>> {
+        columns
+            .iter()
+            .find_map(|&c| self.values.get(c).filter(|s| !s.is_empty()).map(|s| (c, s)))
+            .map(|(c, s)| {
+                s.parse()
+                    .map_err(|_| self.invalid(format!("could not parse {}='{}'", c, s)))
+            })
+            .transpose()
+    }
+
+    fn required(&self, columns: &[&str]) -> UVTheoryResult<f64> {
+        self.number(columns)?
+            .ok_or_else(|| self.invalid(format!("missing column '{}'", columns[0])))
+    }
+
+    fn sigma(&self) -> UVTheoryResult<Option<f64>> {
+        if let Some(sigma) = self.number(&["sigma_nm"])? {
+            return Ok(Some(10.0 * sigma));
+        }
+        self.number(&["sigma", "sigma_a", "sigma_angstrom", "σ"])
+    }
+
+    fn epsilon_k(&self) -> UVTheoryResult<Option<f64>> {
+        self.energy(
+            ["epsilon_kj_mol", "epsilon_kcal_mol", "epsilon_j_mol"],
+            &["epsilon_k", "eps_k", "ε_k"],
+        )
+    }
+
+    fn epsilon_k_ij(&self) -> UVTheoryResult<Option<f64>> {
+        self.energy(
+            [
+                "epsilon_ij_kj_mol",
+                "epsilon_ij_kcal_mol",
+                "epsilon_ij_j_mol",
+            ],
+            &["epsilon_k_ij", "eps_k_ij", "ε_k_ij"],
+        )
+    }
+
+    /// Energy parameter in K from the columns in kJ/mol, kcal/mol and J/mol
+    /// or, if none of them is present, from the columns in K.
+    fn energy(&self, per_mole: [&str; 3], kelvin: &[&str]) -> UVTheoryResult<Option<f64>> {
+        for (column, factor) in per_mole.iter().zip([1000.0, 4184.0, 1.0]) {
+            if let Some(epsilon) = self.number(&[column])? {
+                return Ok(Some(kelvin_per(epsilon * factor)));
+            }
+        }
+        self.number(kelvin)
+    }
+
+    fn identifier(&self) -> UVTheoryResult<Identifier> {
+        let name = self
+            .string(NAME)
+            .ok_or_else(|| self.invalid("missing column 'name'".into()))?;
+        Ok(Identifier::new(
+            self.string(&["cas"]).unwrap_or_default(),
+            Some(name),
+            self.string(&["iupac_name"]),
+            self.string(&["smiles"]),
+            self.string(&["inchi"]),
+            self.string(&["formula"]),
+        ))
+    }
+}
+
+/// Normalized column name.
+fn normalize(column: &str) -> String {
+    column.trim().to_lowercase().replace([' ', '-', '/'], "_")
+}
+
+/// Read the rows of a csv file with a header line. Fields are separated by
+/// commas or semicolons and can be enclosed in double quotes, lines starting
+/// with `#` are ignored.
+fn read_csv<P: AsRef<Path>>(file: P) -> UVTheoryResult<Vec<HashMap<String, String>>> {
+    let content = fs::read_to_string(file).map_err(ParameterError::from)?;
+    let mut lines = content
+        .lines()
+        .filter(|l| !(l.trim().is_empty() || l.trim_start().starts_with('#')));
+    let header = match lines.next() {
+        Some(header) => header,
+        None => return Ok(Vec::new()),
+    };
+    let delimiter = if header.contains(';') { ';' } else { ',' };
+    let split = |line: &str| {
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        for c in line.chars() {
+            match c {
+                '"' => quoted = !quoted,
+                c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+                c => field.push(c),
+            }
+        }
+        fields.push(field);
+        fields
+    };
+    let columns: Vec<String> = split(header).iter().map(|c| normalize(c)).collect();
+    lines
+        .enumerate()
+        .map(|(i, line)| {
+            let fields = split(line);
+            if fields.len() != columns.len() {
+                return Err(UVTheoryError::InvalidData(format!(
+                    "row {}: expected {} fields, got {}",
+                    i + 1,
+                    columns.len(),
+                    fields.len()
+                )));
+            }
+            Ok(columns
+                .iter()
+                .cloned()
+                .zip(fields.into_iter().map(|f| f.trim().to_string()))
+                .collect())
+        })
+        .collect()
+}
+
+/// Read the rows of a json file containing a list of objects.
+fn read_json<P: AsRef<Path>>(file: P) -> UVTheoryResult<Vec<HashMap<String, String>>> {
+    let content = fs::read_to_string(file).map_err(ParameterError::from)?;
+    let rows: Vec<HashMap<String, Value>> =
+        serde_json::from_str(&content).map_err(ParameterError::from)?;
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|(k, v)| {
+                    let v = match v {
+                        Value::String(s) => s,
+                        Value::Null => String::new(),
+                        v => v.to_string(),
+                    };
+                    (normalize(&k), v)
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// Pure and binary records of a [ForceField] read from a parameter table.
+#[derive(Clone, Debug)]
+pub struct ForceFieldParameters {
+    pub force_field: ForceField,
+    pub pure_records: Vec<PureRecord<UVRecord, JobackRecord>>,
+    pub binary_records: Vec<BinaryRecord<Identifier, UVBinaryRecord>>,
+}
+
+impl ForceFieldParameters {
+    /// Read the pure and, optionally, the binary parameters from csv files.
+    ///
+    /// The binary table identifies the components by their names in the columns
+    /// `name1` and `name2` (or `id1`/`id2`, `component_1`/`component_2`), and
+    /// contains the columns `k_ij` and `l_ij` and/or explicit cross parameters
+    /// `sigma_ij` (or `sigma_ij_nm`), `epsilon_k_ij` (or `epsilon_ij_kj_mol`,
+    /// `epsilon_ij_kcal_mol`, `epsilon_ij_j_mol`) and `lambda_r_ij` with the same
+    /// units as the corresponding columns of the pure table.
+    pub fn from_csv<P: AsRef<Path>>(
+        force_field: ForceField,
+        file_pure: P,
+        file_binary: Option<P>,
+    ) -> UVTheoryResult<Self> {
+        let binary = file_binary.map(read_csv).transpose()?;
+        Self::from_rows(force_field, &read_csv(file_pure)?, binary.as_deref())
+    }
+
+    /// Read the pure and, optionally, the binary parameters from json files
+    /// containing a list of objects with the columns of [ForceFieldParameters::from_csv].
+    pub fn from_json<P: AsRef<Path>>(
+        force_field: ForceField,
+        file_pure: P,
+        file_binary: Option<P>,
+    ) -> UVTheoryResult<Self> {
+        let binary = file_binary.map(read_json).transpose()?;
+        Self::from_rows(force_field, &read_json(file_pure)?, binary.as_deref())
+    }
+
+    fn from_rows(
+        force_field: ForceField,
+        pure: &[HashMap<String, String>],
+        binary: Option<&[HashMap<String, String>]>,
+    ) -> UVTheoryResult<Self> {
+        let pure_records = pure
+            .iter()
+            .enumerate()
+            .map(|(index, values)| Self::pure_record(force_field, &Row { index, values }))
+            .collect::<UVTheoryResult<Vec<_>>>()?;
+        let binary_records = binary
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .map(|(index, values)| Self::binary_record(&pure_records, &Row { index, values }))
+            .collect::<UVTheoryResult<_>>()?;
+        Ok(Self {
+            force_field,
+            pure_records,
+            binary_records,
+        })
+    }
+
+    fn pure_record(
+        force_field: ForceField,
+        row: &Row,
+    ) -> UVTheoryResult<PureRecord<UVRecord, JobackRecord>> {
+        let identifier = row.identifier()?;
+        let sigma = row
+            .sigma()?
+            .ok_or_else(|| row.invalid("missing column 'sigma'".into()))?;
+        let epsilon_k = row
+            .epsilon_k()?
+            .ok_or_else(|| row.invalid("missing column 'epsilon_k'".into()))?;
+        let rep = match force_field.default_rep() {
+            Some(rep) => row.number(&["rep"])?.unwrap_or(rep),
+            None => row.required(force_field.rep_columns())?,
+        };
+        let att = row.number(force_field.att_columns())?.unwrap_or(6.0);
+        let m = row.number(force_field.m_columns())?.unwrap_or(1.0);
+        let molarweight = row.required(MOLARWEIGHT)?;
+        let model_record = UVRecord::new(rep, att, sigma, epsilon_k).with_segments(m);
+        Ok(PureRecord::new(identifier, molarweight, model_record, None))
+    }
+
+    fn binary_record(
+        pure_records: &[PureRecord<UVRecord, JobackRecord>],
+        row: &Row,
+    ) -> UVTheoryResult<BinaryRecord<Identifier, UVBinaryRecord>> {
+        let identifier = |columns: &[&str]| {
+            let name = row
+                .string(columns)
+                .ok_or_else(|| row.invalid(format!("missing column '{}'", columns[0])))?;
+            pure_records
+                .iter()
+                .map(|pr| &pr.identifier)
+                .find(|id| id.name.as_deref() == Some(name) || id.cas == name)
+                .cloned()
+                .ok_or_else(|| row.invalid(format!("unknown component '{}'", name)))
+        };
+        let id1 = identifier(&[
+            "name1",
+            "name_1",
+            "id1",
+            "id_1",
+            "component_1",
+            "component1",
+        ])?;
+        let id2 = identifier(&[
+            "name2",
+            "name_2",
+            "id2",
+            "id_2",
+            "component_2",
+            "component2",
+        ])?;
+        let model_record = UVBinaryRecord {
+            k_ij: row.number(&["k_ij", "kij"])?.unwrap_or_default(),
+            l_ij: row.number(&["l_ij", "lij"])?.unwrap_or_default(),
+            sigma_ij: match row.number(&["sigma_ij_nm"])? {
+                Some(sigma) => Some(10.0 * sigma),
+                None => row.number(&["sigma_ij", "σ_ij"])?,
+            },
+            epsilon_k_ij: row.epsilon_k_ij()?,
+            rep_ij: row.number(&["lambda_r_ij", "n_ij", "λ_r_ij"])?,
+            ..Default::default()
+        };
+        Ok(BinaryRecord::new(id1, id2, model_record))
+    }
+
+    /// Parameters of the given substances with the combining rule of the force field.
+    pub fn parameters(
+        &self,
+        substances: &[&str],
+        search_option: IdentifierOption,
+    ) -> UVTheoryResult<UVParameters> {
+        let position = |substance: &str| {
+            self.pure_records
+                .iter()
+                .position(|pr| pr.identifier.as_string(search_option).as_deref() == Some(substance))
+                .ok_or_else(|| ParameterError::ComponentsNotFound(format!("{:?}", [substance])))
+        };
+        let indices = substances
+            .iter()
+            .map(|s| position(s))
+            .collect::<Result<Vec<_>, _>>()?;
+        let pure_records: Vec<_> = indices
+            .iter()
+            .map(|&i| self.pure_records[i].clone())
+            .collect();
+        let n = pure_records.len();
+        let binary_records = Array2::from_shape_fn([n, n], |(i, j)| {
+            let (id1, id2) = (&pure_records[i].identifier, &pure_records[j].identifier);
+            self.binary_records
+                .iter()
+                .find(|br| (&br.id1, &br.id2) == (id1, id2) || (&br.id1, &br.id2) == (id2, id1))
+                .filter(|_| i != j)
+                .map(|br| br.model_record.clone())
+                .unwrap_or_default()
+        });
+        UVParameters::try_from_records_with_combining_rule(
+            pure_records,
+            binary_records,
+            self.force_field.combining_rule(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::path::PathBuf;
+
+    fn write(name: &str, content: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("uvtheory_import_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join(name);
+        fs::write(&file, content).unwrap();
+        file
+    }
+
+    #[test]
+    fn saft_gamma_mie_csv() {
+        let pure = write(
+            "sgm_pure.csv",
+            "# SAFT-gamma Mie\n\
+             Name,CAS,m_s,sigma,epsilon/k,lambda_r,lambda_a,MW\n\
+             methane,74-82-8,1.0,3.7412,153.36,12.650,6.0,16.043\n\
+             \"n-decane\",124-18-5,2.0,4.8,300.0,16.0,6.0,142.29\n",
+        );
+        let binary = write(
+            "sgm_binary.csv",
+            "name1,name2,epsilon_k_ij\nmethane,n-decane,220.0\n",
+        );
+        let ff =
+            ForceFieldParameters::from_csv(ForceField::SaftGammaMie, &pure, Some(&binary)).unwrap();
+        assert_eq!(ff.pure_records.len(), 2);
+        let decane = &ff.pure_records[1];
+        assert_eq!(decane.identifier.name.as_deref(), Some("n-decane"));
+        assert_eq!(decane.identifier.cas, "124-18-5");
+        assert_eq!(decane.model_record.m, 2.0);
+        assert_eq!(decane.model_record.rep, 16.0);
+        assert_eq!(ff.binary_records[0].id2, decane.identifier);
+
+        let p = ff
+            .parameters(&["n-decane", "methane"], IdentifierOption::Name)
+            .unwrap();
+        assert_eq!(p.combining_rule, CombiningRule::Lafitte);
+        assert_eq!(p.sigma[1], 3.7412);
+        assert_eq!(p.eps_k_ij[[0, 1]], 220.0);
+        assert_eq!(p.eps_k_ij[[1, 0]], 220.0);
+    }
+
+    #[test]
+    fn trappe_units() {
+        let pure = write(
+            "trappe.csv",
+            "pseudo_atom;sigma_nm;epsilon_kJ_mol;molar_mass\nCH4;0.373;1.2305;16.043\n",
+        );
+        let ff = ForceFieldParameters::from_csv(ForceField::TraPPE, &pure, None).unwrap();
+        let record = &ff.pure_records[0].model_record;
+        assert_relative_eq!(record.sigma, 3.73, max_relative = 1e-14);
+        assert_relative_eq!(record.epsilon_k, 148.0, max_relative = 1e-3);
+        assert_eq!(record.rep, 12.0);
+        assert_eq!(record.att, 6.0);
+    }
+
+    #[test]
+    fn cross_energy_units() {
+        let pure = write(
+            "trappe_pure.csv",
+            "pseudo_atom,sigma_nm,epsilon_kJ_mol,molar_mass\n\
+             CH4,0.373,1.2305,16.043\n\
+             Ar,0.3405,0.9961,39.948\n",
+        );
+        let epsilon_k_ij = |columns: &str, values: &str| {
+            let binary = write(
+                "trappe_binary.csv",
+                &format!("name1,name2,{}\nCH4,Ar,{}\n", columns, values),
+            );
+            let ff =
+                ForceFieldParameters::from_csv(ForceField::TraPPE, &pure, Some(&binary)).unwrap();
+            ff.binary_records[0].model_record.epsilon_k_ij.unwrap()
+        };
+        let epsilon_k = 130.0;
+        let epsilon_j_mol = epsilon_k * 8.31446261815324;
+        assert_eq!(epsilon_k_ij("epsilon_k_ij", "130.0"), epsilon_k);
+        for (column, value) in [
+            ("epsilon_ij_kJ_mol", epsilon_j_mol / 1000.0),
+            ("epsilon_ij_kcal_mol", epsilon_j_mol / 4184.0),
+            ("epsilon_ij_J_mol", epsilon_j_mol),
+        ] {
+            assert_relative_eq!(
+                epsilon_k_ij(column, &value.to_string()),
+                epsilon_k,
+                max_relative = 1e-12
+            );
+        }
+    }
+
+    #[test]
+    fn mie_potoff_json() {
+        let pure = write(
+            "potoff.json",
+            r#"[{"name": "CH4", "sigma": 3.74, "epsilon_k": 161.0, "n": 14, "molarweight": 16.043},
+                {"name": "Ne", "sigma": 2.78, "epsilon_k": 34.0, "molarweight": 20.18}]"#,
+        );
+        let result = ForceFieldParameters::from_json(ForceField::MiePotoff, &pure, None);
+        assert!(
+            matches!(result, Err(UVTheoryError::InvalidData(e)) if e == "row 2: missing column 'n'")
+        );
+
+        let pure = write(
+            "potoff.json",
+            r#"[{"name": "CH4", "sigma": 3.74, "epsilon_k": 161.0, "n": 14, "molarweight": 16.043}]"#,
+        );
+        let ff = ForceFieldParameters::from_json(ForceField::MiePotoff, &pure, None).unwrap();
+        assert_eq!(ff.pure_records[0].model_record.rep, 14.0);
+        let p = ff.parameters(&["CH4"], IdentifierOption::Name).unwrap();
+        assert_eq!(p.epsilon_k[0], 161.0);
+        assert!(matches!(
+            ff.parameters(&["CO2"], IdentifierOption::Name),
+            Err(UVTheoryError::ParameterError(
+                ParameterError::ComponentsNotFound(_)
+            ))
+        ));
+    }
+}
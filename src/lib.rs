@@ -1,8 +1,34 @@
-mod parameters;
+#![warn(clippy::all)]
+#![allow(clippy::excessive_precision)]
+#![allow(clippy::too_many_arguments)]
+mod batch;
+mod dft;
 mod eos;
+mod errors;
+mod import;
+mod parallel;
+mod parameters;
+mod reduced;
+mod regression;
+mod solid;
+mod virial;
 
-pub use parameters::{UVBinaryRecord, UVRecord, UVParameters};
-pub use eos::{UVTheory, UVTheoryOptions, Perturbation};
+pub use batch::ResidualBatch;
+pub use dft::UVTheoryFunctional;
+pub use eos::{
+    Perturbation, UVTheory, UVTheoryOptions, ValidityDomain, ValidityViolation, VirialOrder,
+};
+pub use errors::{UVTheoryError, UVTheoryResult};
+pub use import::{ForceField, ForceFieldParameters};
+pub use parallel::PhaseDiagramPure;
+pub use parameters::{CombiningRule, EffectiveParameters, UVBinaryRecord, UVParameters, UVRecord};
+pub use reduced::{ReducedProperties, ReducedUVTheory};
+pub use regression::{
+    BinaryData, BinaryDeviations, BinaryParameter, BinaryRegression, BinaryRegressionResult,
+    PureData, PureParameter, PureRegression, PureRegressionResult,
+};
+pub use solid::{SolidFluidEquilibrium, TriplePoint, UVTheorySolid};
+pub use virial::MiePotential;
 
 #[cfg(feature = "python")]
-pub mod python;
\ No newline at end of file
+pub mod python;
@@ -0,0 +1,209 @@
+use crate::UVTheory;
+use feos_core::{Contributions, EosError, EosResult, PhaseEquilibrium, SolverOptions, State};
+use ndarray::Array1;
+use quantity::si::*;
+use rayon::prelude::*;
+use std::rc::Rc;
+
+/// Vapor-liquid equilibria of a pure component calculated in parallel.
+///
+/// In contrast to `PhaseDiagram`, only the properties of the coexisting
+/// phases are stored, so that the results can be sent between threads.
+#[derive(Clone, Debug)]
+pub struct PhaseDiagramPure {
+    pub temperature: SIArray1,
+    pub pressure: SIArray1,
+    pub liquid_density: SIArray1,
+    pub vapor_density: SIArray1,
+}
+
+impl UVTheory {
+    /// Evaluate `f` for every input in parallel.
+    ///
+    /// `UVTheory` is neither `Send` nor `Sync`, because the Helmholtz energy
+    /// contributions of feos-core are trait objects without these bounds, and
+    /// `State` and `PhaseEquilibrium` require an `Rc` of the equation of state.
+    /// Therefore, every rayon job builds its own `UVTheory` from the shared
+    /// parameters and options and passes it to `f`.
+    pub fn par_map<I, T, F>(&self, inputs: &[I], f: F) -> Vec<T>
+    where
+        I: Sync,
+        T: Send,
+        F: Fn(&Rc<Self>, &I) -> T + Sync + Send,
+    {
+        let (parameters, options) = (&self.parameters, &self.options);
+        inputs
+            .par_iter()
+            .map_init(
+                || {
+                    Rc::new(Self::with_checked_options(
+                        parameters.clone(),
+                        options.clone(),
+                    ))
+                },
+                |eos, input| f(eos, input),
+            )
+            .collect()
+    }
+
+    /// Calculate the phase diagram of a pure component in parallel.
+    ///
+    /// The temperatures are distributed in contiguous chunks over the threads,
+    /// within a chunk every equilibrium is used as initial value for the next.
+    /// As for `PhaseDiagram::pure`, temperatures at which the calculation of
+    /// the equilibrium fails are skipped and the critical point is the last entry.
+    /// Returns an error if `npoints` is smaller than 2.
+    pub fn par_phase_diagram_pure(
+        &self,
+        min_temperature: SINumber,
+        npoints: usize,
+        critical_temperature: Option<SINumber>,
+        options: SolverOptions,
+    ) -> EosResult<PhaseDiagramPure> {
+        if npoints < 2 {
+            return Err(EosError::InvalidState(
+                "par_phase_diagram_pure".into(),
+                "npoints".into(),
+                npoints as f64,
+            ));
+        }
+        let eos = Rc::new(Self::with_checked_options(
+            self.parameters.clone(),
+            self.options.clone(),
+        ));
+        let sc = State::critical_point(&eos, None, critical_temperature, SolverOptions::default())?;
+        let (tc, pc, rhoc) = (
+            sc.temperature,
+            sc.pressure(Contributions::Total),
+            sc.density,
+        );
+
+        let max_temperature = min_temperature
+            + (tc - min_temperature) * ((npoints - 2) as f64 / (npoints - 1) as f64);
+        let temperatures: Vec<SINumber> = Array1::linspace(0.0, 1.0, npoints - 1)
+            .iter()
+            .map(|&i| min_temperature + (max_temperature - min_temperature) * i)
+            .collect();
+        let chunk_size = ((npoints - 1) / rayon::current_num_threads()).max(1);
+        let chunks: Vec<&[SINumber]> = temperatures.chunks(chunk_size).collect();
+
+        let mut points: Vec<[SINumber; 4]> = self
+            .par_map(&chunks, |eos, chunk| {
+                let mut vle: Option<PhaseEquilibrium<SIUnit, UVTheory, 2>> = None;
+                let mut points = Vec::with_capacity(chunk.len());
+                for &t in chunk.iter() {
+                    vle = PhaseEquilibrium::pure(eos, t, vle.as_ref(), options).ok();
+                    if let Some(vle) = vle.as_ref() {
+                        points.push([
+                            t,
+                            vle.vapor().pressure(Contributions::Total),
+                            vle.liquid().density,
+                            vle.vapor().density,
+                        ]);
+                    }
+                }
+                points
+            })
+            .into_iter()
+            .flatten()
+            .collect();
+        points.push([tc, pc, rhoc, rhoc]);
+
+        let column = |k: usize| SIArray1::from_shape_fn(points.len(), |i| points[i][k]);
+        Ok(PhaseDiagramPure {
+            temperature: column(0),
+            pressure: column(1),
+            liquid_density: column(2),
+            vapor_density: column(3),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parameters::utils::test_parameters;
+    use crate::{ResidualBatch, UVParameters, UVTheoryOptions};
+    use approx::assert_relative_eq;
+    use feos_core::PhaseDiagram;
+    use std::sync::Arc;
+
+    fn argon() -> UVTheory {
+        let parameters = test_parameters(12.0, 6.0, 3.4, 120.0);
+        UVTheory::new(Arc::new(parameters))
+    }
+
+    #[test]
+    fn thread_safety() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        // everything that is shared between the rayon jobs
+        assert_send_sync::<Arc<UVParameters>>();
+        assert_send_sync::<UVTheoryOptions>();
+        assert_send_sync::<PhaseDiagramPure>();
+        assert_send_sync::<ResidualBatch>();
+    }
+
+    #[test]
+    fn par_map_states() {
+        let eos = argon();
+        let temperatures: Vec<f64> = (0..16).map(|i| 100.0 + 10.0 * i as f64).collect();
+        let density = 5.0 * KILO * MOL / METER.powi(3);
+        let pressures = eos.par_map(&temperatures, |eos, &t| {
+            State::new_pure(eos, t * KELVIN, density)
+                .unwrap()
+                .pressure(Contributions::Total)
+        });
+
+        let eos = Rc::new(eos);
+        for (&t, &p) in temperatures.iter().zip(pressures.iter()) {
+            let state = State::new_pure(&eos, t * KELVIN, density).unwrap();
+            assert_eq!(p, state.pressure(Contributions::Total));
+        }
+    }
+
+    #[test]
+    fn par_phase_diagram() {
+        let eos = argon();
+        let par = eos
+            .par_phase_diagram_pure(100.0 * KELVIN, 20, None, Default::default())
+            .unwrap();
+        for npoints in [0, 1] {
+            assert!(matches!(
+                eos.par_phase_diagram_pure(100.0 * KELVIN, npoints, None, Default::default()),
+                Err(EosError::InvalidState(..))
+            ));
+        }
+        let seq = PhaseDiagram::pure(&Rc::new(eos), 100.0 * KELVIN, 20, None, Default::default())
+            .unwrap();
+        assert_eq!(par.temperature.len(), seq.states.len());
+        for (i, vle) in seq.states.iter().enumerate() {
+            assert_relative_eq!(
+                par.pressure.get(i).to_reduced(PASCAL).unwrap(),
+                vle.vapor()
+                    .pressure(Contributions::Total)
+                    .to_reduced(PASCAL)
+                    .unwrap(),
+                max_relative = 1e-8
+            );
+            assert_relative_eq!(
+                par.liquid_density
+                    .get(i)
+                    .to_reduced(MOL / METER.powi(3))
+                    .unwrap(),
+                vle.liquid()
+                    .density
+                    .to_reduced(MOL / METER.powi(3))
+                    .unwrap(),
+                max_relative = 1e-8
+            );
+            assert_relative_eq!(
+                par.vapor_density
+                    .get(i)
+                    .to_reduced(MOL / METER.powi(3))
+                    .unwrap(),
+                vle.vapor().density.to_reduced(MOL / METER.powi(3)).unwrap(),
+                max_relative = 1e-8
+            );
+        }
+    }
+}
@@ -1,60 +1,462 @@
-use feos_core::parameter::{Parameter, PureRecord};
+use crate::eos::hard_sphere_wca::{diameter_q_wca_coefficients, diameter_wca_constant};
+use crate::eos::{attractive_perturbation_bh, attractive_perturbation_wca, MEAN_FIELD_RADIUS};
+use crate::errors::{UVTheoryError, UVTheoryResult};
+use feos_core::joback::JobackRecord;
+use feos_core::parameter::{
+    BinaryRecord, ChemicalRecord, FromSegments, FromSegmentsBinary, IdentifierOption, Parameter,
+    PureRecord, SegmentRecord,
+};
+use feos_core::{EosError, EosResult};
 use lazy_static::lazy_static;
 use ndarray::concatenate;
 use ndarray::prelude::*;
 use ndarray::Array2;
-use num_dual::DualNum;
+use num_dual::{Dual64, DualNum};
+use quantity::si::{ANGSTROM, GRAM, JOULE, KB, KELVIN, MOL, NAV, PLANCK};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fmt;
+use std::f64::consts::TAU;
 use std::fmt::Write;
+use std::path::Path;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct NoRecord;
+/// Attractive exponent of the Mie potential for which the correlations of
+/// uv-theory (hard-sphere diameters, u-fractions and correlation integrals)
+/// are parametrized.
+pub const ATTRACTIVE_EXPONENT: f64 = 6.0;
 
-impl fmt::Display for NoRecord {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "")
-    }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UVRecord {
+    /// Number of segments
+    #[serde(default = "one")]
+    pub(crate) m: f64,
+    pub(crate) rep: f64,
+    pub(crate) att: f64,
+    pub(crate) sigma: f64,
+    pub(crate) epsilon_k: f64,
+    /// Dipole moment in units of Debye
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) mu: Option<f64>,
+    /// Quadrupole moment in units of Debye
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) q: Option<f64>,
+    /// Association volume parameter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) kappa_ab: Option<f64>,
+    /// Association energy parameter in units of Kelvin
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) epsilon_k_ab: Option<f64>,
+    /// \# of association sites of type A
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) na: Option<f64>,
+    /// \# of association sites of type B
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) nb: Option<f64>,
+    /// Order of the Feynman-Hibbs correction of the potential (1 or 2)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) fh: Option<usize>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct UVRecord {
-    rep: f64,
-    att: f64,
-    sigma: f64,
-    epsilon_k: f64,
+fn one() -> f64 {
+    1.0
+}
+
+impl Default for UVRecord {
+    fn default() -> Self {
+        Self::new(0.0, 0.0, 0.0, 0.0)
+    }
 }
 
 impl UVRecord {
+    /// Record of a single spherical Mie segment without polar or
+    /// associating interactions. Further interactions are added with
+    /// the `with_*` methods.
     pub fn new(rep: f64, att: f64, sigma: f64, epsilon_k: f64) -> Self {
         Self {
+            m: 1.0,
             rep,
             att,
             sigma,
             epsilon_k,
+            mu: None,
+            q: None,
+            kappa_ab: None,
+            epsilon_k_ab: None,
+            na: None,
+            nb: None,
+            fh: None,
+        }
+    }
+
+    /// Set the number of segments.
+    pub fn with_segments(mut self, m: f64) -> Self {
+        self.m = m;
+        self
+    }
+
+    /// Set the dipole moment in units of Debye.
+    pub fn with_dipole(mut self, mu: f64) -> Self {
+        self.mu = Some(mu);
+        self
+    }
+
+    /// Set the quadrupole moment in units of Debye.
+    pub fn with_quadrupole(mut self, q: f64) -> Self {
+        self.q = Some(q);
+        self
+    }
+
+    /// Set the association volume and energy (in units of Kelvin) and the
+    /// numbers of association sites of type A and B.
+    pub fn with_association(mut self, kappa_ab: f64, epsilon_k_ab: f64, na: f64, nb: f64) -> Self {
+        self.kappa_ab = Some(kappa_ab);
+        self.epsilon_k_ab = Some(epsilon_k_ab);
+        self.na = Some(na);
+        self.nb = Some(nb);
+        self
+    }
+
+    /// Set the order of the Feynman-Hibbs correction of the potential (1 or 2).
+    pub fn with_feynman_hibbs(mut self, order: usize) -> Self {
+        self.fh = Some(order);
+        self
+    }
+
+    /// Checks that the parameters are physically meaningful and that the
+    /// potential can be described by the correlations of uv-theory.
+    pub fn check(&self) -> UVTheoryResult<()> {
+        let finite = |field, value: Option<f64>| match value {
+            Some(v) if !v.is_finite() => {
+                Err(UVTheoryError::invalid_parameter(field, v, "must be finite"))
+            }
+            _ => Ok(()),
+        };
+
+        if !(self.m >= 1.0 && self.m.is_finite()) {
+            return Err(UVTheoryError::invalid_parameter(
+                "m",
+                self.m,
+                "the segment number must be at least 1",
+            ));
+        }
+        check_exponents(("rep", self.rep), ("att", self.att))?;
+        check_positive("sigma", self.sigma)?;
+        check_positive("epsilon_k", self.epsilon_k)?;
+        finite("mu", self.mu)?;
+        finite("q", self.q)?;
+        check_non_negative("kappa_ab", self.kappa_ab)?;
+        check_non_negative("epsilon_k_ab", self.epsilon_k_ab)?;
+        check_non_negative("na", self.na)?;
+        check_non_negative("nb", self.nb)?;
+        // sites of type B require sites of type A (na and nb default to 1)
+        if self.na == Some(0.0) && self.nb.unwrap_or(1.0) > 0.0 {
+            return Err(UVTheoryError::invalid_parameter(
+                "na",
+                0.0,
+                "components with sites of type B must have sites of type A (swap na and nb)",
+            ));
+        }
+        if let Some(fh) = self.fh {
+            if fh > 2 {
+                return Err(UVTheoryError::invalid_parameter(
+                    "fh",
+                    fh,
+                    "Feynman-Hibbs corrections are available up to second order",
+                ));
+            }
         }
+        Ok(())
     }
 }
 
+fn check_positive(field: &'static str, value: f64) -> UVTheoryResult<()> {
+    if value > 0.0 && value.is_finite() {
+        Ok(())
+    } else {
+        Err(UVTheoryError::invalid_parameter(
+            field,
+            value,
+            "must be positive and finite",
+        ))
+    }
+}
+
+fn check_non_negative(field: &'static str, value: Option<f64>) -> UVTheoryResult<()> {
+    match value {
+        Some(v) if !(v >= 0.0 && v.is_finite()) => Err(UVTheoryError::invalid_parameter(
+            field,
+            v,
+            "must be non-negative and finite",
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Checks the (named) exponents of a Mie potential.
+fn check_exponents(
+    (rep_field, rep): (&'static str, f64),
+    (att_field, att): (&'static str, f64),
+) -> UVTheoryResult<()> {
+    if att != ATTRACTIVE_EXPONENT {
+        return Err(UVTheoryError::invalid_parameter(
+            att_field,
+            att,
+            &format!(
+                "uv-theory is parametrized for Mie potentials with an attractive exponent of {}",
+                ATTRACTIVE_EXPONENT
+            ),
+        ));
+    }
+    if !(rep > att && rep.is_finite()) {
+        return Err(UVTheoryError::invalid_parameter(
+            rep_field,
+            rep,
+            "the repulsive exponent must be finite and larger than the attractive exponent",
+        ));
+    }
+    Ok(())
+}
+
 impl std::fmt::Display for UVRecord {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "UVRecord(m={}", self.rep)?;
+        write!(f, "UVRecord(m={}", self.m)?;
+        write!(f, ", rep={}", self.rep)?;
         write!(f, ", att={}", self.att)?;
         write!(f, ", sigma={}", self.sigma)?;
         write!(f, ", epsilon_k={}", self.epsilon_k)?;
+        if let Some(n) = &self.mu {
+            write!(f, ", mu={}", n)?;
+        }
+        if let Some(n) = &self.q {
+            write!(f, ", q={}", n)?;
+        }
+        if let Some(n) = &self.kappa_ab {
+            write!(f, ", kappa_ab={}", n)?;
+        }
+        if let Some(n) = &self.epsilon_k_ab {
+            write!(f, ", epsilon_k_ab={}", n)?;
+        }
+        if let Some(n) = &self.na {
+            write!(f, ", na={}", n)?;
+        }
+        if let Some(n) = &self.nb {
+            write!(f, ", nb={}", n)?;
+        }
+        if let Some(n) = &self.fh {
+            write!(f, ", fh={}", n)?;
+        }
         write!(f, ")")
     }
 }
 
+/// Homosegmented group contribution method.
+///
+/// The segment number is the sum of the segment numbers of all groups. The
+/// segment diameters (as `sigma^3`), the energy parameters and the exponents
+/// are averaged over the segments of all groups. Association sites are added
+/// up and the association parameters are averaged over the associating groups.
+/// The order of the Feynman-Hibbs correction is the highest order of all groups.
+///
+/// Groups with dipole or quadrupole moments are not supported: the multipole
+/// moment of a molecule depends on the orientation of the moments of its groups
+/// and can not be obtained from their sum.
+///
+/// # Panics
+///
+/// Panics if a group has a dipole or quadrupole moment, see
+/// [UVParameters::try_from_segments] for a fallible alternative.
+impl FromSegments for UVRecord {
+    fn from_segments(segments: &[(Self, f64)]) -> Self {
+        let groups = segments.iter().map(|(s, _)| (s, s));
+        check_group_multipoles(groups).unwrap_or_else(|e| panic!("{}", e));
+        let m: f64 = segments.iter().map(|(s, n)| s.m * n).sum();
+        let mean =
+            |f: fn(&Self) -> f64| segments.iter().map(|(s, n)| s.m * n * f(s)).sum::<f64>() / m;
+        let sum = |f: fn(&Self) -> Option<f64>| {
+            segments
+                .iter()
+                .filter_map(|(s, n)| f(s).map(|v| v * n))
+                .reduce(|a, b| a + b)
+        };
+        let mean_associating = |f: fn(&Self) -> Option<f64>| {
+            let (sum, count) = segments
+                .iter()
+                .filter_map(|(s, n)| f(s).map(|v| (v * n, *n)))
+                .fold((0.0, 0.0), |(sum, count), (v, n)| (sum + v, count + n));
+            (count > 0.0).then(|| sum / count)
+        };
+        // keep the attractive exponent exact if all groups agree (as they should)
+        let att = match segments.first() {
+            Some((s0, _)) if segments.iter().all(|(s, _)| s.att == s0.att) => s0.att,
+            _ => mean(|s| s.att),
+        };
+        Self {
+            m,
+            rep: mean(|s| s.rep),
+            att,
+            sigma: mean(|s| s.sigma.powi(3)).cbrt(),
+            epsilon_k: mean(|s| s.epsilon_k),
+            mu: None,
+            q: None,
+            kappa_ab: mean_associating(|s| s.kappa_ab),
+            epsilon_k_ab: mean_associating(|s| s.epsilon_k_ab),
+            na: sum(|s| s.na),
+            nb: sum(|s| s.nb),
+            fh: segments.iter().filter_map(|(s, _)| s.fh).max(),
+        }
+    }
+}
+
+/// Multipole moments of groups can not be combined, see [UVRecord::from_segments].
+fn check_group_multipoles<'a, G: std::fmt::Display>(
+    groups: impl IntoIterator<Item = (G, &'a UVRecord)>,
+) -> UVTheoryResult<()> {
+    match groups
+        .into_iter()
+        .find(|(_, r)| r.mu.is_some() || r.q.is_some())
+    {
+        Some((group, _)) => Err(UVTheoryError::IncompatibleParameters(format!(
+            "cannot combine the multipole moments of groups, group {} has a dipole or quadrupole moment",
+            group
+        ))),
+        None => Ok(()),
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Default, Debug)]
 pub struct UVBinaryRecord {
+    /// Binary interaction parameter for the energy parameter,
+    /// k_ij(T) = k_ij + k_ij_t / T + k_ij_ln_t ln(T)
+    #[serde(default)]
     pub k_ij: f64,
+    /// Coefficient of the 1/T term of the binary interaction parameter in units of Kelvin
+    #[serde(default)]
+    pub k_ij_t: f64,
+    /// Coefficient of the ln(T) term of the binary interaction parameter
+    #[serde(default)]
+    pub k_ij_ln_t: f64,
+    /// Binary interaction parameter for the segment diameter
+    #[serde(default)]
+    pub l_ij: f64,
+    /// Cross segment diameter in units of Angstrom, overwrites the combining rule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sigma_ij: Option<f64>,
+    /// Cross energy parameter in units of Kelvin, overwrites the combining rule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epsilon_k_ij: Option<f64>,
+    /// Cross repulsive exponent, overwrites the combining rule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rep_ij: Option<f64>,
+    /// Cross attractive exponent, overwrites the combining rule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub att_ij: Option<f64>,
+    /// Cross-association volume parameter, overwrites the combining rule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kappa_ab: Option<f64>,
+    /// Cross-association energy parameter in units of Kelvin, overwrites the combining rule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epsilon_k_ab: Option<f64>,
 }
 
 impl From<f64> for UVBinaryRecord {
     fn from(k_ij: f64) -> Self {
-        Self { k_ij }
+        Self {
+            k_ij,
+            ..Default::default()
+        }
+    }
+}
+
+/// The binary interaction parameters of two molecules are the averages of the
+/// parameters of all pairs of groups weighted with the numbers of the groups.
+/// Explicit cross parameters of groups are not used.
+impl FromSegmentsBinary for UVBinaryRecord {
+    fn from_segments_binary(segments: &[(Self, f64, f64)]) -> Self {
+        let n: f64 = segments.iter().map(|(_, n1, n2)| n1 * n2).sum();
+        let mean = |f: fn(&Self) -> f64| {
+            segments
+                .iter()
+                .map(|(b, n1, n2)| f(b) * n1 * n2)
+                .sum::<f64>()
+                / n
+        };
+        Self {
+            k_ij: mean(|b| b.k_ij),
+            k_ij_t: mean(|b| b.k_ij_t),
+            k_ij_ln_t: mean(|b| b.k_ij_ln_t),
+            l_ij: mean(|b| b.l_ij),
+            ..Default::default()
+        }
+    }
+}
+
+/// Combining rules for the cross interaction of two Mie potentials.
+///
+/// The binary interaction parameters `k_ij` and `l_ij` are applied on top of
+/// the combining rule. Cross parameters given explicitly in [UVBinaryRecord]
+/// are used as they are.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "python", pyo3::pyclass)]
+pub enum CombiningRule {
+    /// Arithmetic mean of the diameters, geometric mean of the energies and exponents.
+    #[default]
+    LorentzBerthelot,
+    /// Arithmetic mean of the diameters, volume weighted geometric mean of the energies
+    /// and `(λ_ij - 3) = sqrt((λ_i - 3)(λ_j - 3))` for the exponents (Lafitte et al., 2013).
+    Lafitte,
+    /// Arithmetic mean of the diameters and geometric mean of the coefficients of the
+    /// attractive term for the energies (Hudson and McCoubrey, 1960).
+    ///
+    /// The original rule contains an additional factor of the ionization potentials,
+    /// `2 sqrt(I_i I_j) / (I_i + I_j)`, which is set to 1 (equal ionization potentials).
+    /// The exponents are combined as for [CombiningRule::LorentzBerthelot].
+    HudsonMcCoubrey,
+    /// Geometric mean of the coefficients of the attractive term and Kong's rule for
+    /// the coefficients of the repulsive term (Kong, 1973).
+    ///
+    /// Kong's rule is derived for potentials with equal exponents. The coefficients
+    /// are evaluated as `epsilon sigma^n` with the cross exponents of
+    /// [CombiningRule::LorentzBerthelot] for both components, i.e., without the
+    /// prefactor `C(n, 6)` of the Mie potential and with the geometric mean of the
+    /// repulsive exponents instead of the exponent of each component. For
+    /// components with equal exponents the rule is exact, otherwise it is an
+    /// approximation of Kong's rule.
+    Kong,
+}
+
+impl CombiningRule {
+    /// Cross parameters `(rep, att, sigma, epsilon_k)` of two Mie potentials.
+    fn apply(&self, i: (f64, f64, f64, f64), j: (f64, f64, f64, f64)) -> (f64, f64, f64, f64) {
+        let (rep_i, att_i, sigma_i, epsilon_i) = i;
+        let (rep_j, att_j, sigma_j, epsilon_j) = j;
+        let sigma_lb = 0.5 * (sigma_i + sigma_j);
+        let epsilon_lb = (epsilon_i * epsilon_j).sqrt();
+        let rep = (rep_i * rep_j).sqrt();
+        let att = (att_i * att_j).sqrt();
+        match self {
+            Self::LorentzBerthelot => (rep, att, sigma_lb, epsilon_lb),
+            Self::Lafitte => (
+                3.0 + ((rep_i - 3.0) * (rep_j - 3.0)).sqrt(),
+                3.0 + ((att_i - 3.0) * (att_j - 3.0)).sqrt(),
+                sigma_lb,
+                epsilon_lb * (sigma_i * sigma_j).powf(1.5) / sigma_lb.powi(3),
+            ),
+            Self::HudsonMcCoubrey => (
+                rep,
+                att,
+                sigma_lb,
+                epsilon_lb * (sigma_i * sigma_j).powf(0.5 * att) / sigma_lb.powf(att),
+            ),
+            Self::Kong => {
+                // epsilon_ij * sigma_ij^att and epsilon_ij * sigma_ij^rep
+                let c_att = epsilon_lb * (sigma_i * sigma_j).powf(0.5 * att);
+                let c_rep_i = epsilon_i * sigma_i.powf(rep);
+                let c_rep_j = epsilon_j * sigma_j.powf(rep);
+                let c_rep = c_rep_i / 2f64.powf(rep + 1.0)
+                    * (1.0 + (c_rep_j / c_rep_i).powf((rep + 1.0).recip())).powf(rep + 1.0);
+                let sigma = (c_rep / c_att).powf((rep - att).recip());
+                (rep, att, sigma, c_att / sigma.powf(att))
+            }
+        }
     }
 }
 
@@ -89,6 +491,7 @@ pub fn mean_field_constant<D: DualNum<f64>>(rep: D, att: D, x: D) -> D {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UVParameters {
     pub ncomponents: usize,
+    pub m: Array1<f64>,
     pub rep: Array1<f64>,
     pub att: Array1<f64>,
     pub sigma: Array1<f64>,
@@ -99,15 +502,46 @@ pub struct UVParameters {
     pub att_ij: Array2<f64>,
     pub sigma_ij: Array2<f64>,
     pub eps_k_ij: Array2<f64>,
+    pub eps_k_ij_t: Array2<f64>,
+    pub eps_k_ij_ln_t: Array2<f64>,
+    pub fh_ij: Array2<usize>,
+    pub quantum_d_ij: Array2<f64>,
+    pub fh_slope_ij: Array2<[f64; 2]>,
+    pub e_k_ij: Array2<f64>,
+    pub mu: Array1<f64>,
+    pub q: Array1<f64>,
+    pub mu2: Array1<f64>,
+    pub q2: Array1<f64>,
+    pub ndipole: usize,
+    pub nquadpole: usize,
+    pub dipole_comp: Array1<usize>,
+    pub quadpole_comp: Array1<usize>,
+    pub kappa_ab: Array1<f64>,
+    pub epsilon_k_ab: Array1<f64>,
+    pub na: Array1<f64>,
+    pub nb: Array1<f64>,
+    pub kappa_aibj: Array2<f64>,
+    pub epsilon_k_aibj: Array2<f64>,
+    pub nassoc: usize,
+    pub assoc_comp: Array1<usize>,
     pub cd_bh_pure: Vec<Array1<f64>>,
     pub cd_bh_binary: Array2<Array1<f64>>,
-    pub pure_records: Vec<PureRecord<UVRecord, NoRecord>>,
+    pub rm: Array1<f64>,
+    pub rm_ij: Array2<f64>,
+    pub c_wca: Array1<f64>,
+    pub q_wca_ij: Array2<[f64; 4]>,
+    pub mean_field_constant_rc_ij: Array2<f64>,
+    pub y_eff_wca_ij: Array2<[f64; 4]>,
+    pub y_eff_bh_ij: Array2<[f64; 4]>,
+    pub pure_records: Vec<PureRecord<UVRecord, JobackRecord>>,
     pub binary_records: Array2<UVBinaryRecord>,
+    pub combining_rule: CombiningRule,
+    pub joback_records: Option<Vec<JobackRecord>>,
 }
 
 // impl UVParameters {
 //     pub fn subset(&self, component_list: &[usize]) -> Self {
-//         let pure_records: Vec<PureRecord<UVRecord, ()>> = component_list
+//         let pure_records: Vec<PureRecord<UVRecord, JobackRecord>> = component_list
 //             .iter()
 //             .map(|&i| self.pure_records[i].clone())
 //             .collect();
@@ -117,29 +551,242 @@ pub struct UVParameters {
 
 impl Parameter for UVParameters {
     type Pure = UVRecord;
-    type IdealGas = NoRecord;
+    type IdealGas = JobackRecord;
     type Binary = UVBinaryRecord;
 
+    /// Parameters with cross interactions from the Lorentz-Berthelot combining rule.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the records are invalid, see [UVParameters::try_from_records].
     fn from_records(
         pure_records: Vec<PureRecord<Self::Pure, Self::IdealGas>>,
         binary_records: Array2<Self::Binary>,
     ) -> Self {
+        Self::try_from_records(pure_records, binary_records).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn records(
+        &self,
+    ) -> (
+        &[PureRecord<UVRecord, JobackRecord>],
+        &Array2<UVBinaryRecord>,
+    ) {
+        (&self.pure_records, &self.binary_records)
+    }
+
+    fn subset(&self, component_list: &[usize]) -> Self {
+        let pure_records = component_list
+            .iter()
+            .map(|&i| self.pure_records[i].clone())
+            .collect();
+        let n = component_list.len();
+        let binary_records = Array2::from_shape_fn([n, n], |(i, j)| {
+            self.binary_records[(component_list[i], component_list[j])].clone()
+        });
+        // the records of a subset have been validated with the full set
+        Self::try_from_records_with_combining_rule(
+            pure_records,
+            binary_records,
+            self.combining_rule,
+        )
+        .unwrap()
+    }
+}
+
+/// Pure and binary records as read by the default implementations of [Parameter].
+///
+/// Used to validate the records of `new_pure`, `new_binary` and `from_json`
+/// before the parameters are constructed.
+struct UVRecords {
+    pure_records: Vec<PureRecord<UVRecord, JobackRecord>>,
+    binary_records: Array2<UVBinaryRecord>,
+}
+
+impl Parameter for UVRecords {
+    type Pure = UVRecord;
+    type IdealGas = JobackRecord;
+    type Binary = UVBinaryRecord;
+
+    fn from_records(
+        pure_records: Vec<PureRecord<UVRecord, JobackRecord>>,
+        binary_records: Array2<UVBinaryRecord>,
+    ) -> Self {
+        Self {
+            pure_records,
+            binary_records,
+        }
+    }
+
+    fn records(
+        &self,
+    ) -> (
+        &[PureRecord<UVRecord, JobackRecord>],
+        &Array2<UVBinaryRecord>,
+    ) {
+        (&self.pure_records, &self.binary_records)
+    }
+}
+
+impl UVParameters {
+    /// Parameters with cross interactions from the Lorentz-Berthelot combining rule.
+    ///
+    /// Returns an error if a record is invalid (see [UVRecord::check]), if the
+    /// shape of `binary_records` does not match the number of components, if the
+    /// cross interactions are invalid or if Feynman-Hibbs corrections of first
+    /// and second order are combined.
+    pub fn try_from_records(
+        pure_records: Vec<PureRecord<UVRecord, JobackRecord>>,
+        binary_records: Array2<UVBinaryRecord>,
+    ) -> UVTheoryResult<Self> {
+        Self::try_from_records_with_combining_rule(
+            pure_records,
+            binary_records,
+            CombiningRule::default(),
+        )
+    }
+
+    /// Parameters of a pure component, see [UVParameters::try_from_records].
+    pub fn try_new_pure(pure_record: PureRecord<UVRecord, JobackRecord>) -> UVTheoryResult<Self> {
+        let records = UVRecords::new_pure(pure_record);
+        Self::try_from_records(records.pure_records, records.binary_records)
+    }
+
+    /// Parameters of a binary mixture, see [UVParameters::try_from_records].
+    pub fn try_new_binary(
+        pure_records: Vec<PureRecord<UVRecord, JobackRecord>>,
+        binary_record: Option<UVBinaryRecord>,
+    ) -> UVTheoryResult<Self> {
+        let records = UVRecords::new_binary(pure_records, binary_record);
+        Self::try_from_records(records.pure_records, records.binary_records)
+    }
+
+    /// Parameters from json files, see [Parameter::from_json] and [UVParameters::try_from_records].
+    pub fn try_from_json<P: AsRef<Path>>(
+        substances: Vec<&str>,
+        file_pure: P,
+        file_binary: Option<P>,
+        search_option: IdentifierOption,
+    ) -> UVTheoryResult<Self> {
+        let records = UVRecords::from_json(substances, file_pure, file_binary, search_option)?;
+        Self::try_from_records(records.pure_records, records.binary_records)
+    }
+
+    /// Parameters from multiple json files, see [Parameter::from_multiple_json]
+    /// and [UVParameters::try_from_records].
+    pub fn try_from_multiple_json<P: AsRef<Path>>(
+        input: &[(Vec<&str>, P)],
+        file_binary: Option<P>,
+        search_option: IdentifierOption,
+    ) -> UVTheoryResult<Self> {
+        let records = UVRecords::from_multiple_json(input, file_binary, search_option)?;
+        Self::try_from_records(records.pure_records, records.binary_records)
+    }
+
+    /// Parameters from a homosegmented group contribution method, see
+    /// [Parameter::from_segments] and [UVParameters::try_from_records].
+    ///
+    /// The chemical records contain either a list of the groups of the molecule
+    /// (e.g., derived from its SMILES) or the number of every group. The pure
+    /// records are obtained from the segment records as described for
+    /// [UVRecord::from_segments], the binary records from the binary segment
+    /// records as described for [UVBinaryRecord::from_segments_binary].
+    ///
+    /// Returns an error if one of the segment records has a dipole or
+    /// quadrupole moment.
+    pub fn try_from_segments(
+        chemical_records: Vec<ChemicalRecord>,
+        segment_records: Vec<SegmentRecord<UVRecord, JobackRecord>>,
+        binary_segment_records: Option<Vec<BinaryRecord<String, UVBinaryRecord>>>,
+    ) -> UVTheoryResult<Self> {
+        check_group_multipoles(
+            segment_records
+                .iter()
+                .map(|s| (s.identifier.as_str(), &s.model_record)),
+        )?;
+        let records =
+            UVRecords::from_segments(chemical_records, segment_records, binary_segment_records)?;
+        Self::try_from_records(records.pure_records, records.binary_records)
+    }
+
+    /// Parameters from a homosegmented group contribution method with chemical
+    /// records, segment records and binary segment records from json files, see
+    /// [Parameter::from_json_segments] and [UVParameters::try_from_segments].
+    ///
+    /// Returns an error if one of the segment records has a dipole or
+    /// quadrupole moment.
+    pub fn try_from_json_segments<P: AsRef<Path>>(
+        substances: &[&str],
+        file_pure: P,
+        file_segments: P,
+        file_binary: Option<P>,
+        search_option: IdentifierOption,
+    ) -> UVTheoryResult<Self> {
+        let segment_records = SegmentRecord::<UVRecord, JobackRecord>::from_json(&file_segments)?;
+        check_group_multipoles(
+            segment_records
+                .iter()
+                .map(|s| (s.identifier.as_str(), &s.model_record)),
+        )?;
+        let records = UVRecords::from_json_segments(
+            substances,
+            file_pure,
+            file_segments,
+            file_binary,
+            search_option,
+        )?;
+        Self::try_from_records(records.pure_records, records.binary_records)
+    }
+
+    /// Parameters with cross interactions from the given combining rule,
+    /// see [UVParameters::try_from_records].
+    pub fn try_from_records_with_combining_rule(
+        pure_records: Vec<PureRecord<UVRecord, JobackRecord>>,
+        binary_records: Array2<UVBinaryRecord>,
+        combining_rule: CombiningRule,
+    ) -> UVTheoryResult<Self> {
         let n = pure_records.len();
+        if binary_records.shape() != [n, n] {
+            return Err(UVTheoryError::IncompatibleParameters(format!(
+                "expected binary records of shape [{}, {}] for {} components, got {:?}",
+                n,
+                n,
+                n,
+                binary_records.shape()
+            )));
+        }
 
         let mut molarweight = Array::zeros(n);
+        let mut m = Array::zeros(n);
         let mut rep = Array::zeros(n);
         let mut att = Array::zeros(n);
         let mut sigma = Array::zeros(n);
         let mut epsilon_k = Array::zeros(n);
+        let mut mu = Array::zeros(n);
+        let mut q = Array::zeros(n);
+        let mut na = Array::zeros(n);
+        let mut nb = Array::zeros(n);
+        let mut kappa_ab = Array::zeros(n);
+        let mut epsilon_k_ab = Array::zeros(n);
+        let mut fh = vec![0; n];
         let mut component_index = HashMap::with_capacity(n);
 
         for (i, record) in pure_records.iter().enumerate() {
             component_index.insert(record.identifier.clone(), i);
             let r = &record.model_record;
+            r.check().map_err(|e| e.of_component(&record.identifier))?;
+            m[i] = r.m;
             rep[i] = r.rep;
             att[i] = r.att;
             sigma[i] = r.sigma;
             epsilon_k[i] = r.epsilon_k;
+            mu[i] = r.mu.unwrap_or(0.0);
+            q[i] = r.q.unwrap_or(0.0);
+            na[i] = r.na.unwrap_or(1.0);
+            nb[i] = r.nb.unwrap_or(1.0);
+            kappa_ab[i] = r.kappa_ab.unwrap_or(0.0);
+            epsilon_k_ab[i] = r.epsilon_k_ab.unwrap_or(0.0);
+            fh[i] = r.fh.unwrap_or(0);
             // construction of molar weights for GC methods, see Builder
             molarweight[i] = record.molarweight;
         }
@@ -148,6 +795,8 @@ impl Parameter for UVParameters {
         let mut att_ij = Array2::zeros((n, n));
         let mut sigma_ij = Array2::zeros((n, n));
         let mut eps_k_ij = Array2::zeros((n, n));
+        let mut eps_k_ij_t = Array2::zeros((n, n));
+        let mut eps_k_ij_ln_t = Array2::zeros((n, n));
         let k_ij = binary_records.map(|br| br.k_ij);
 
         for i in 0..n {
@@ -156,24 +805,172 @@ impl Parameter for UVParameters {
             sigma_ij[[i, i]] = sigma[i];
             eps_k_ij[[i, i]] = epsilon_k[i];
             for j in i + 1..n {
-                rep_ij[[i, j]] = (rep[i] * rep[j]).sqrt();
+                let (rep_c, att_c, sigma_c, epsilon_k_c) = combining_rule.apply(
+                    (rep[i], att[i], sigma[i], epsilon_k[i]),
+                    (rep[j], att[j], sigma[j], epsilon_k[j]),
+                );
+                let br = &binary_records[[i, j]];
+                rep_ij[[i, j]] = br.rep_ij.unwrap_or(rep_c);
+                att_ij[[i, j]] = br.att_ij.unwrap_or(att_c);
+                sigma_ij[[i, j]] = br.sigma_ij.unwrap_or((1.0 - br.l_ij) * sigma_c);
+                eps_k_ij[[i, j]] = br.epsilon_k_ij.unwrap_or((1.0 - br.k_ij) * epsilon_k_c);
+                if br.epsilon_k_ij.is_none() {
+                    eps_k_ij_t[[i, j]] = -br.k_ij_t * epsilon_k_c;
+                    eps_k_ij_ln_t[[i, j]] = -br.k_ij_ln_t * epsilon_k_c;
+                }
+                let pair = || {
+                    format!(
+                        "{}/{}",
+                        pure_records[i].identifier, pure_records[j].identifier
+                    )
+                };
+                check_exponents(("rep_ij", rep_ij[[i, j]]), ("att_ij", att_ij[[i, j]]))
+                    .and_then(|_| check_positive("sigma_ij", sigma_ij[[i, j]]))
+                    .and_then(|_| check_positive("epsilon_k_ij", eps_k_ij[[i, j]]))
+                    .and_then(|_| check_non_negative("kappa_ab", br.kappa_ab))
+                    .and_then(|_| check_non_negative("epsilon_k_ab", br.epsilon_k_ab))
+                    .map_err(|e| e.of_component(pair()))?;
+                for (field, value) in [("k_ij_t", br.k_ij_t), ("k_ij_ln_t", br.k_ij_ln_t)] {
+                    if !value.is_finite() {
+                        return Err(UVTheoryError::invalid_parameter(
+                            field,
+                            value,
+                            "must be finite",
+                        )
+                        .of_component(pair()));
+                    }
+                }
                 rep_ij[[j, i]] = rep_ij[[i, j]];
-                att_ij[[i, j]] = (att[i] * att[j]).sqrt();
                 att_ij[[j, i]] = att_ij[[i, j]];
-                sigma_ij[[i, j]] = 0.5 * (sigma[i] + sigma[j]);
                 sigma_ij[[j, i]] = sigma_ij[[i, j]];
-                eps_k_ij[[i, j]] = (1.0 - k_ij[[i, j]]) * (epsilon_k[i] * epsilon_k[j]).sqrt();
                 eps_k_ij[[j, i]] = eps_k_ij[[i, j]];
+                eps_k_ij_t[[j, i]] = eps_k_ij_t[[i, j]];
+                eps_k_ij_ln_t[[j, i]] = eps_k_ij_ln_t[[i, j]];
+            }
+        }
+
+        // Feynman-Hibbs corrections, D_ij(T) = quantum_d_ij / T
+        let mut fh_ij = Array2::zeros((n, n));
+        let mut quantum_d_ij = Array2::zeros((n, n));
+        for i in 0..n {
+            for j in 0..n {
+                if fh[i] * fh[j] == 2 {
+                    return Err(UVTheoryError::IncompatibleParameters(format!(
+                        "cannot combine Feynman-Hibbs corrections of first and second order ({}, {})",
+                        pure_records[i].identifier, pure_records[j].identifier
+                    )));
+                }
+                fh_ij[[i, j]] = fh[i].max(fh[j]);
+                if fh_ij[[i, j]] > 0 {
+                    let mass_ij = 2.0 * molarweight[i] * molarweight[j]
+                        / (molarweight[i] + molarweight[j])
+                        * GRAM
+                        / MOL
+                        / NAV;
+                    quantum_d_ij[[i, j]] = ((PLANCK / TAU).powi(2) / (12.0 * KB * mass_ij))
+                        .to_reduced(ANGSTROM.powi(2) * KELVIN)
+                        .unwrap();
+                }
+            }
+        }
+
+        // reduced multipole moments
+        let mu2 = &mu * &mu / (&m * &sigma * &sigma * &sigma * &epsilon_k)
+            * 1e-19
+            * (JOULE / KELVIN / KB).into_value().unwrap();
+        let q2 = &q * &q / (&m * &sigma.mapv(|s| s.powi(5)) * &epsilon_k)
+            * 1e-19
+            * (JOULE / KELVIN / KB).into_value().unwrap();
+        let dipole_comp: Array1<usize> = mu2
+            .iter()
+            .enumerate()
+            .filter(|(_, &mu2)| mu2.abs() > 0.0)
+            .map(|(i, _)| i)
+            .collect();
+        let ndipole = dipole_comp.len();
+        let quadpole_comp: Array1<usize> = q2
+            .iter()
+            .enumerate()
+            .filter(|(_, &q2)| q2.abs() > 0.0)
+            .map(|(i, _)| i)
+            .collect();
+        let nquadpole = quadpole_comp.len();
+        let e_k_ij = Array2::from_shape_fn((n, n), |(i, j)| (epsilon_k[i] * epsilon_k[j]).sqrt());
+
+        // cross-association parameters from combining rules, if not given explicitly
+        let assoc_comp: Array1<usize> = kappa_ab
+            .iter()
+            .enumerate()
+            .filter(|(_, &k)| k.abs() > 0.0)
+            .map(|(i, _)| i)
+            .collect();
+        let nassoc = assoc_comp.len();
+
+        let mut kappa_aibj = Array::zeros([n, n]);
+        let mut epsilon_k_aibj = Array::zeros([n, n]);
+        for &ai in assoc_comp.iter() {
+            for &bj in assoc_comp.iter() {
+                kappa_aibj[[ai, bj]] = (kappa_ab[ai] * kappa_ab[bj]).sqrt()
+                    * (2.0 * (sigma[ai] * sigma[bj]).sqrt() / (sigma[ai] + sigma[bj])).powi(3);
+                epsilon_k_aibj[[ai, bj]] = 0.5 * (epsilon_k_ab[ai] + epsilon_k_ab[bj]);
+                if ai != bj {
+                    let br = &binary_records[[ai, bj]];
+                    if let Some(k) = br.kappa_ab {
+                        kappa_aibj[[ai, bj]] = k;
+                    }
+                    if let Some(e) = br.epsilon_k_ab {
+                        epsilon_k_aibj[[ai, bj]] = e;
+                    }
+                }
             }
         }
 
         // BH temperature dependent HS diameter, eq. 21
-        let cd_bh_pure: Vec<Array1<f64>> = rep.iter().map(|&mi| bh_coefficients(mi, 6.0)).collect();
-        let cd_bh_binary =
-            Array2::from_shape_fn((n, n), |(i, j)| bh_coefficients(rep_ij[[i, j]], 6.0));
+        let cd_bh_pure: Vec<Array1<f64>> = rep
+            .iter()
+            .map(|&mi| bh_coefficients(mi, ATTRACTIVE_EXPONENT))
+            .collect();
+        let cd_bh_binary = Array2::from_shape_fn((n, n), |(i, j)| {
+            bh_coefficients(rep_ij[[i, j]], ATTRACTIVE_EXPONENT)
+        });
 
-        Self {
+        // temperature independent constants of the perturbation theory
+        let rm_ij = Array2::from_shape_fn((n, n), |(i, j)| {
+            (rep_ij[[i, j]] / att_ij[[i, j]]).powf(1.0 / (rep_ij[[i, j]] - att_ij[[i, j]]))
+        });
+        let rm = rm_ij.diag().to_owned();
+        let fh_slope_ij = Array2::from_shape_fn((n, n), |(i, j)| match fh_ij[[i, j]] {
+            0 => [0.0; 2],
+            order => feynman_hibbs_slopes(
+                rep_ij[[i, j]],
+                att_ij[[i, j]],
+                sigma_ij[[i, j]],
+                rm_ij[[i, j]],
+                order,
+            ),
+        });
+        let c_wca = rep.mapv(diameter_wca_constant);
+        let q_wca_ij = Array2::from_shape_fn((n, n), |(i, j)| {
+            diameter_q_wca_coefficients(rep_ij[[i, j]], att_ij[[i, j]])
+        });
+        let mean_field_constant_rc_ij = Array2::from_shape_fn((n, n), |(i, j)| {
+            mean_field_constant(rep_ij[[i, j]], att_ij[[i, j]], MEAN_FIELD_RADIUS)
+        });
+        let y_eff_wca_ij = Array2::from_shape_fn((n, n), |(i, j)| {
+            attractive_perturbation_wca::y_eff_constants(rep_ij[[i, j]], att_ij[[i, j]])
+        });
+        let y_eff_bh_ij = Array2::from_shape_fn((n, n), |(i, j)| {
+            attractive_perturbation_bh::y_eff_constants(rep_ij[[i, j]], att_ij[[i, j]])
+        });
+
+        let joback_records = pure_records
+            .iter()
+            .map(|r| r.ideal_gas_record.clone())
+            .collect();
+
+        Ok(Self {
             ncomponents: n,
+            m,
             rep,
             att,
             sigma,
@@ -184,25 +981,154 @@ impl Parameter for UVParameters {
             att_ij,
             sigma_ij,
             eps_k_ij,
+            eps_k_ij_t,
+            eps_k_ij_ln_t,
+            fh_ij,
+            quantum_d_ij,
+            fh_slope_ij,
+            e_k_ij,
+            mu,
+            q,
+            mu2,
+            q2,
+            ndipole,
+            nquadpole,
+            dipole_comp,
+            quadpole_comp,
+            kappa_ab,
+            epsilon_k_ab,
+            na,
+            nb,
+            kappa_aibj,
+            epsilon_k_aibj,
+            nassoc,
+            assoc_comp,
             cd_bh_pure,
             cd_bh_binary,
+            rm,
+            rm_ij,
+            c_wca,
+            q_wca_ij,
+            mean_field_constant_rc_ij,
+            y_eff_wca_ij,
+            y_eff_bh_ij,
             pure_records,
             binary_records,
+            combining_rule,
+            joback_records,
+        })
+    }
+
+    /// Cross energy parameter of the pair `(i, j)` in units of Kelvin at the given
+    /// temperature, including the temperature dependence of the binary interaction parameters.
+    pub fn eps_k_ij_at<D: DualNum<f64>>(&self, i: usize, j: usize, temperature: D) -> D {
+        temperature.recip() * self.eps_k_ij_t[[i, j]]
+            + temperature.ln() * self.eps_k_ij_ln_t[[i, j]]
+            + self.eps_k_ij[[i, j]]
+    }
+
+    /// Temperature dependent size and energy parameters of the interaction potentials.
+    ///
+    /// For pairs with a Feynman-Hibbs correction, these are the distance at which the
+    /// quantum corrected potential vanishes and the depth of its minimum. Otherwise,
+    /// the classical parameters are returned.
+    pub fn effective_parameters<D: DualNum<f64>>(
+        &self,
+        temperature: D,
+    ) -> EffectiveParameters<'_, D> {
+        let quantum = self.fh_ij.iter().any(|&fh| fh > 0).then(|| {
+            Array2::from_shape_fn(self.fh_ij.raw_dim(), |(i, j)| {
+                if self.fh_ij[[i, j]] > 0 {
+                    self.feynman_hibbs_ij(i, j, temperature)
+                } else {
+                    (D::from(self.sigma_ij[[i, j]]), D::one())
+                }
+            })
+        });
+        let temperature_dependent = self
+            .eps_k_ij_t
+            .iter()
+            .chain(self.eps_k_ij_ln_t.iter())
+            .any(|&e| e != 0.0);
+        EffectiveParameters {
+            parameters: self,
+            temperature_terms: temperature_dependent
+                .then(|| (temperature.recip(), temperature.ln())),
+            quantum,
         }
     }
 
-    fn records(&self) -> (&[PureRecord<UVRecord, NoRecord>], &Array2<UVBinaryRecord>) {
-        (&self.pure_records, &self.binary_records)
+    /// Temperature dependent size and energy parameter of component `i`.
+    ///
+    /// Equivalent to the diagonal of [UVParameters::effective_parameters] without
+    /// evaluating the parameters of all pairs.
+    pub fn effective_parameters_i<D: DualNum<f64>>(&self, i: usize, temperature: D) -> (D, D) {
+        if self.fh_ij[[i, i]] > 0 {
+            let (sigma_eff, depth) = self.feynman_hibbs_ij(i, i, temperature);
+            (sigma_eff, depth * self.epsilon_k[i])
+        } else {
+            (D::from(self.sigma[i]), D::from(self.epsilon_k[i]))
+        }
+    }
+
+    /// Effective diameter and well depth (relative to the classical well depth)
+    /// of the Feynman-Hibbs corrected potential of the pair `(i, j)`.
+    ///
+    /// The Newton iterations start from the linear expansion of the root and the
+    /// minimum of the potential in `D_ij(T)` that is precomputed in `fh_slope_ij`.
+    /// If the iterations do not converge, the parameters are `NaN`.
+    fn feynman_hibbs_ij<D: DualNum<f64>>(&self, i: usize, j: usize, temperature: D) -> (D, D) {
+        self.try_feynman_hibbs_ij(i, j, temperature)
+            .unwrap_or((D::from(f64::NAN), D::from(f64::NAN)))
+    }
+
+    fn try_feynman_hibbs_ij<D: DualNum<f64>>(
+        &self,
+        i: usize,
+        j: usize,
+        temperature: D,
+    ) -> EosResult<(D, D)> {
+        let rep = self.rep_ij[[i, j]];
+        let att = self.att_ij[[i, j]];
+        let sigma = self.sigma_ij[[i, j]];
+        let order = self.fh_ij[[i, j]];
+        let [slope_sigma, slope_min] = self.fh_slope_ij[[i, j]];
+        let d = temperature.recip() * self.quantum_d_ij[[i, j]];
+        let u = |r| feynman_hibbs_potential(rep, att, sigma, d, r, order);
+
+        // distance at which the potential vanishes
+        let sigma_eff = newton(d * slope_sigma + sigma, sigma, |r| {
+            let u = u(r);
+            (u[0], u[1])
+        })
+        .map_err(|_| EosError::NotConverged("Feynman-Hibbs diameter".into()))?;
+
+        // position and depth of the minimum
+        let r_min = newton(d * slope_min + sigma * self.rm_ij[[i, j]], sigma, |r| {
+            let u = u(r);
+            (u[1], u[2])
+        })
+        .map_err(|_| EosError::NotConverged("Feynman-Hibbs well depth".into()))?;
+        Ok((sigma_eff, -u(r_min)[0]))
+    }
+
+    /// Parameters of the same components with cross interactions from the given combining rule.
+    ///
+    /// Returns an error if the combined cross parameters are invalid.
+    pub fn with_combining_rule(&self, combining_rule: CombiningRule) -> UVTheoryResult<Self> {
+        Self::try_from_records_with_combining_rule(
+            self.pure_records.clone(),
+            self.binary_records.clone(),
+            combining_rule,
+        )
     }
-}
 
-impl UVParameters {
     pub fn to_markdown(&self) -> String {
         let mut output = String::new();
         let o = &mut output;
         write!(
             o,
-            "|component|molarweight|$\\sigma$|$\\varepsilon$|$m$|$n$|\n|-|-|-|-|-|-|"
+            "|component|molarweight|$m$|$\\sigma$|$\\varepsilon$|$\\lambda_r$|$\\lambda_a$|\n|-|-|-|-|-|-|-|"
         )
         .unwrap();
         for i in 0..self.pure_records.len() {
@@ -210,9 +1136,10 @@ impl UVParameters {
             let component = component.unwrap_or(format!("Component {}", i + 1));
             write!(
                 o,
-                "\n|{}|{}|{}|{}|{}|{}|",
+                "\n|{}|{}|{}|{}|{}|{}|{}|",
                 component,
                 self.molarweight[i],
+                self.m[i],
                 self.sigma[i],
                 self.epsilon_k[i],
                 self.rep[i],
@@ -224,6 +1151,122 @@ impl UVParameters {
     }
 }
 
+/// Temperature dependent potential parameters, see [UVParameters::effective_parameters].
+///
+/// The parameters are evaluated when they are accessed, only the parameters
+/// of Feynman-Hibbs corrected potentials are stored.
+#[derive(Clone, Debug)]
+pub struct EffectiveParameters<'a, D> {
+    parameters: &'a UVParameters,
+    /// `1 / T` and `ln(T)`, if any binary interaction parameter is temperature dependent.
+    temperature_terms: Option<(D, D)>,
+    /// Effective diameters and relative well depths of all pairs, if any pair
+    /// has a Feynman-Hibbs correction.
+    quantum: Option<Array2<(D, D)>>,
+}
+
+impl<'a, D: DualNum<f64>> EffectiveParameters<'a, D> {
+    /// Size parameter of component `i`.
+    #[inline]
+    pub fn sigma(&self, i: usize) -> D {
+        self.sigma_ij(i, i)
+    }
+
+    /// Energy parameter of component `i` in units of Kelvin.
+    #[inline]
+    pub fn epsilon_k(&self, i: usize) -> D {
+        self.eps_k_ij(i, i)
+    }
+
+    /// Size parameter of the pair `(i, j)`.
+    #[inline]
+    pub fn sigma_ij(&self, i: usize, j: usize) -> D {
+        match &self.quantum {
+            Some(quantum) => quantum[[i, j]].0,
+            None => D::from(self.parameters.sigma_ij[[i, j]]),
+        }
+    }
+
+    /// Energy parameter of the pair `(i, j)` in units of Kelvin.
+    #[inline]
+    pub fn eps_k_ij(&self, i: usize, j: usize) -> D {
+        let p = self.parameters;
+        let eps_k_ij = match self.temperature_terms {
+            Some((t_inv, ln_t)) => {
+                t_inv * p.eps_k_ij_t[[i, j]] + ln_t * p.eps_k_ij_ln_t[[i, j]] + p.eps_k_ij[[i, j]]
+            }
+            None => D::from(p.eps_k_ij[[i, j]]),
+        };
+        match &self.quantum {
+            Some(quantum) => eps_k_ij * quantum[[i, j]].1,
+            None => eps_k_ij,
+        }
+    }
+}
+
+/// Feynman-Hibbs corrected Mie potential divided by the energy parameter
+/// and its first and second derivative with respect to the distance `r`.
+///
+/// `d` is the quantum correction parameter hbar^2 / (12 m_ij k_B T) in units of Angstrom^2,
+/// with m_ij twice the reduced mass of the pair.
+fn feynman_hibbs_potential<D: DualNum<f64>>(
+    rep: f64,
+    att: f64,
+    sigma: f64,
+    d: D,
+    r: D,
+    order: usize,
+) -> [D; 3] {
+    let mut u = [D::zero(); 3];
+    for (lambda, sign) in [(rep, 1.0), (att, -1.0)] {
+        // prefactors of the terms r^-(lambda + 2k) from the k-th order correction
+        let q = [
+            1.0,
+            lambda * (lambda - 1.0),
+            0.5 * (lambda + 2.0) * (lambda + 1.0) * lambda * (lambda - 1.0),
+        ];
+        let mut dk = D::one();
+        for (k, &qk) in q.iter().enumerate().take(order + 1) {
+            let n = lambda + 2.0 * k as f64;
+            let c = dk * (sign * qk * sigma.powf(lambda));
+            let r_n = r.powf(-n);
+            u[0] += c * r_n;
+            u[1] -= c * r_n * n / r;
+            u[2] += c * r_n * n * (n + 1.0) / (r * r);
+            dk *= d;
+        }
+    }
+    u.map(|u| u * mie_prefactor(rep, att))
+}
+
+/// Maximum number of Newton iterations for the parameters of Feynman-Hibbs corrected potentials.
+const MAX_ITER_FH: usize = 50;
+
+/// Newton's method for the root of `f`, which returns the function value and its derivative.
+/// Converged if the step is smaller than `1e-12 * scale`.
+fn newton<D: DualNum<f64>, F: Fn(D) -> (D, D)>(mut x: D, scale: f64, f: F) -> EosResult<D> {
+    for _ in 0..MAX_ITER_FH {
+        let (f, df) = f(x);
+        let dx = -f / df;
+        x += dx;
+        if dx.re().abs() < 1e-12 * scale {
+            return Ok(x);
+        }
+    }
+    Err(EosError::NotConverged("newton".into()))
+}
+
+/// Derivatives of the root and the position of the minimum of the Feynman-Hibbs
+/// corrected potential with respect to `D_ij(T)` in the classical limit.
+fn feynman_hibbs_slopes(rep: f64, att: f64, sigma: f64, rm: f64, order: usize) -> [f64; 2] {
+    let d = Dual64::from(0.0).derive();
+    let u = feynman_hibbs_potential(rep, att, sigma, d, Dual64::from(sigma), order);
+    let slope_sigma = -u[0].eps[0] / u[1].re;
+    let u = feynman_hibbs_potential(rep, att, sigma, d, Dual64::from(sigma * rm), order);
+    let slope_min = -u[1].eps[0] / u[2].re;
+    [slope_sigma, slope_min]
+}
+
 fn bh_coefficients(rep: f64, att: f64) -> Array1<f64> {
     let inv_a76 = 1.0 / mean_field_constant(7.0, att, 1.0);
     let am6 = mean_field_constant(rep, att, 1.0);
@@ -268,4 +1311,480 @@ pub mod utils {
         let pr = PureRecord::new(identifier, 1.0, model_record, None);
         UVParameters::new_pure(pr)
     }
+
+    pub fn water_parameters() -> UVParameters {
+        let water_json = r#"
+            {
+                "identifier": {
+                    "cas": "7732-18-5",
+                    "name": "water"
+                },
+                "model_record": {
+                    "m": 1.065587,
+                    "rep": 12.0,
+                    "att": 6.0,
+                    "sigma": 3.000683,
+                    "epsilon_k": 366.5121,
+                    "kappa_ab": 0.034867983,
+                    "epsilon_k_ab": 2500.6706
+                },
+                "molarweight": 18.0152
+            }"#;
+        let water_record: PureRecord<UVRecord, JobackRecord> =
+            serde_json::from_str(water_json).expect("Unable to parse json.");
+        UVParameters::new_pure(water_record)
+    }
+
+    pub fn water_methanol_parameters(cross_association: Option<(f64, f64)>) -> UVParameters {
+        let methanol_json = r#"
+            {
+                "identifier": {
+                    "cas": "67-56-1",
+                    "name": "methanol"
+                },
+                "model_record": {
+                    "m": 1.5255,
+                    "rep": 12.0,
+                    "att": 6.0,
+                    "sigma": 3.23,
+                    "epsilon_k": 188.9,
+                    "kappa_ab": 0.035176,
+                    "epsilon_k_ab": 2899.5
+                },
+                "molarweight": 32.042
+            }"#;
+        let methanol_record: PureRecord<UVRecord, JobackRecord> =
+            serde_json::from_str(methanol_json).expect("Unable to parse json.");
+        let water_record = water_parameters().pure_records[0].clone();
+        let binary_record = match cross_association {
+            Some((kappa_ab, epsilon_k_ab)) => UVBinaryRecord {
+                kappa_ab: Some(kappa_ab),
+                epsilon_k_ab: Some(epsilon_k_ab),
+                ..Default::default()
+            },
+            None => UVBinaryRecord::default(),
+        };
+        let binary_records = Array2::from_elem((2, 2), binary_record);
+        UVParameters::from_records(vec![water_record, methanol_record], binary_records)
+    }
+
+    pub fn dme_parameters() -> UVParameters {
+        let dme_json = r#"
+            {
+                "identifier": {
+                    "cas": "115-10-6",
+                    "name": "dimethyl-ether"
+                },
+                "model_record": {
+                    "m": 2.2634,
+                    "rep": 12.0,
+                    "att": 6.0,
+                    "sigma": 3.2723,
+                    "epsilon_k": 210.29,
+                    "mu": 1.3
+                },
+                "molarweight": 46.0688
+            }"#;
+        let dme_record: PureRecord<UVRecord, JobackRecord> =
+            serde_json::from_str(dme_json).expect("Unable to parse json.");
+        UVParameters::new_pure(dme_record)
+    }
+
+    pub fn carbon_dioxide_parameters() -> UVParameters {
+        let co2_json = r#"
+            {
+                "identifier": {
+                    "cas": "124-38-9",
+                    "name": "carbon-dioxide"
+                },
+                "model_record": {
+                    "m": 1.5131,
+                    "rep": 12.0,
+                    "att": 6.0,
+                    "sigma": 3.1869,
+                    "epsilon_k": 163.333,
+                    "q": 4.4
+                },
+                "molarweight": 44.0098
+            }"#;
+        let co2_record: PureRecord<UVRecord, JobackRecord> =
+            serde_json::from_str(co2_json).expect("Unable to parse json.");
+        UVParameters::new_pure(co2_record)
+    }
+
+    pub fn dme_co2_methane_parameters() -> UVParameters {
+        let records = vec![
+            dme_parameters().pure_records[0].clone(),
+            carbon_dioxide_parameters().pure_records[0].clone(),
+            methane_parameters(12.0, 6.0).pure_records[0].clone(),
+        ];
+        UVParameters::from_records(records, Array2::default((3, 3)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_relative_eq;
+    use feos_core::parameter::Identifier;
+    use num_dual::Dual64;
+
+    fn mie_record(rep: f64, att: f64) -> PureRecord<UVRecord, JobackRecord> {
+        let identifier = Identifier::new("1", None, None, None, None, None);
+        let model_record = UVRecord::new(rep, att, 1.0, 1.0);
+        PureRecord::new(identifier, 1.0, model_record, None)
+    }
+
+    #[test]
+    fn default_segment_number() {
+        let record: UVRecord =
+            serde_json::from_str(r#"{"rep": 12.0, "att": 6.0, "sigma": 3.4, "epsilon_k": 120.0}"#)
+                .unwrap();
+        assert_eq!(record.m, 1.0);
+        assert!(record.check().is_ok());
+        assert_eq!(UVRecord::default().m, 1.0);
+    }
+
+    #[test]
+    fn attractive_exponent() {
+        assert!(mie_record(12.0, 6.0).model_record.check().is_ok());
+        assert!(mie_record(20.0, 8.0).model_record.check().is_err());
+        assert!(mie_record(12.0, 5.0).model_record.check().is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid parameter att=8 of Identifier(cas=1)")]
+    fn attractive_exponent_records() {
+        UVParameters::new_pure(mie_record(20.0, 8.0));
+    }
+
+    #[test]
+    fn invalid_records() {
+        let error = |rep, sigma, epsilon_k| {
+            let mut record = mie_record(rep, 6.0);
+            record.model_record.sigma = sigma;
+            record.model_record.epsilon_k = epsilon_k;
+            match UVParameters::try_new_pure(record) {
+                Err(UVTheoryError::InvalidParameter {
+                    component, field, ..
+                }) => (component.unwrap(), field),
+                _ => panic!("expected an invalid parameter"),
+            }
+        };
+        assert_eq!(error(5.0, 1.0, 1.0), ("Identifier(cas=1)".into(), "rep"));
+        assert_eq!(
+            error(12.0, -1.0, 1.0),
+            ("Identifier(cas=1)".into(), "sigma")
+        );
+        assert_eq!(
+            error(12.0, 1.0, f64::NAN),
+            ("Identifier(cas=1)".into(), "epsilon_k")
+        );
+        assert!(UVParameters::try_new_pure(mie_record(12.0, 6.0)).is_ok());
+
+        // association sites
+        let association =
+            |na, nb| UVRecord::new(12.0, 6.0, 1.0, 1.0).with_association(0.03, 2500.0, na, nb);
+        assert!(matches!(
+            association(0.0, 1.0).check(),
+            Err(UVTheoryError::InvalidParameter { field: "na", .. })
+        ));
+        assert!(association(1.0, 0.0).check().is_ok());
+        assert!(association(0.0, 0.0).check().is_ok());
+
+        // cross interactions
+        let records = || vec![mie_record(12.0, 6.0), hydrogen_record(None)];
+        let result = UVParameters::try_new_binary(records(), Some(UVBinaryRecord::from(1.5)));
+        assert!(matches!(
+            result,
+            Err(UVTheoryError::InvalidParameter {
+                field: "epsilon_k_ij",
+                ..
+            })
+        ));
+        let result = UVParameters::try_from_records(records(), Array2::default((3, 3)));
+        assert!(matches!(
+            result,
+            Err(UVTheoryError::IncompatibleParameters(_))
+        ));
+    }
+
+    fn binary_parameters(
+        binary_record: UVBinaryRecord,
+        combining_rule: CombiningRule,
+    ) -> UVParameters {
+        let record = |name: &str, rep, sigma, epsilon_k| {
+            let identifier = Identifier::new(name, None, None, None, None, None);
+            let model_record = UVRecord::new(rep, 6.0, sigma, epsilon_k);
+            PureRecord::new(identifier, 1.0, model_record, None)
+        };
+        let pure_records = vec![record("1", 12.0, 1.0, 1.0), record("2", 20.0, 2.0, 4.0)];
+        let binary_records = Array2::from_elem((2, 2), binary_record);
+        UVParameters::try_from_records_with_combining_rule(
+            pure_records,
+            binary_records,
+            combining_rule,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn combining_rules() {
+        let p = binary_parameters(UVBinaryRecord::from(0.1), CombiningRule::LorentzBerthelot);
+        assert_eq!(p.sigma_ij[[0, 1]], 1.5);
+        assert_eq!(p.eps_k_ij[[0, 1]], 0.9 * 2.0);
+        assert_eq!(p.rep_ij[[0, 1]], 240f64.sqrt());
+        assert_eq!(p.eps_k_ij, p.eps_k_ij.t());
+
+        let p = binary_parameters(Default::default(), CombiningRule::Lafitte);
+        assert_eq!(p.rep_ij[[0, 1]], 3.0 + 153f64.sqrt());
+        assert_eq!(p.att_ij[[0, 1]], 6.0);
+        assert_relative_eq!(p.eps_k_ij[[0, 1]], 2.0 * 8f64.sqrt() / 1.5f64.powi(3));
+
+        let p = binary_parameters(Default::default(), CombiningRule::HudsonMcCoubrey);
+        assert_relative_eq!(p.eps_k_ij[[0, 1]], 2.0 * 8.0 / 1.5f64.powi(6));
+
+        // Kong's rule conserves the geometric mean of the attractive coefficients
+        let p = binary_parameters(Default::default(), CombiningRule::Kong);
+        assert_relative_eq!(
+            p.eps_k_ij[[0, 1]] * p.sigma_ij[[0, 1]].powi(6),
+            (4.0 * 2f64.powi(6)).sqrt()
+        );
+        assert!(p.sigma_ij[[0, 1]] > 1.0 && p.sigma_ij[[0, 1]] < 2.0);
+
+        // all rules reduce to the pure component parameters for identical components
+        for rule in [
+            CombiningRule::LorentzBerthelot,
+            CombiningRule::Lafitte,
+            CombiningRule::HudsonMcCoubrey,
+            CombiningRule::Kong,
+        ] {
+            let (rep, att, sigma, epsilon_k) =
+                rule.apply((12.0, 6.0, 3.5, 150.0), (12.0, 6.0, 3.5, 150.0));
+            assert_relative_eq!(rep, 12.0, max_relative = 1e-12);
+            assert_relative_eq!(att, 6.0, max_relative = 1e-12);
+            assert_relative_eq!(sigma, 3.5, max_relative = 1e-12);
+            assert_relative_eq!(epsilon_k, 150.0, max_relative = 1e-12);
+        }
+    }
+
+    #[test]
+    fn binary_record_overrides() {
+        let binary_record = UVBinaryRecord {
+            k_ij: 0.1,
+            l_ij: 0.05,
+            ..Default::default()
+        };
+        let p = binary_parameters(binary_record, CombiningRule::LorentzBerthelot);
+        assert_relative_eq!(p.sigma_ij[[0, 1]], 0.95 * 1.5);
+        assert_relative_eq!(p.sigma_ij[[1, 0]], 0.95 * 1.5);
+
+        let binary_record: UVBinaryRecord = serde_json::from_str(
+            r#"{"k_ij": 0.1, "sigma_ij": 1.6, "epsilon_k_ij": 1.7, "rep_ij": 14.0}"#,
+        )
+        .unwrap();
+        let p = binary_parameters(binary_record, CombiningRule::Lafitte);
+        assert_eq!(p.sigma_ij[[0, 1]], 1.6);
+        assert_eq!(p.eps_k_ij[[1, 0]], 1.7);
+        assert_eq!(p.rep_ij[[0, 1]], 14.0);
+        assert_eq!(p.att_ij[[0, 1]], 6.0);
+
+        // the combining rule is kept for subsets
+        let p = p.with_combining_rule(CombiningRule::Kong).unwrap();
+        assert_eq!(p.subset(&[1, 0]).combining_rule, CombiningRule::Kong);
+    }
+
+    fn hydrogen_record(fh: Option<usize>) -> PureRecord<UVRecord, JobackRecord> {
+        let identifier = Identifier::new("hydrogen", None, None, None, None, None);
+        let model_record = UVRecord {
+            fh,
+            ..UVRecord::new(9.0, 6.0, 3.0243, 26.706)
+        };
+        PureRecord::new(identifier, 2.0157309551872, model_record, None)
+    }
+
+    #[test]
+    fn feynman_hibbs_effective_parameters() {
+        let t = 26.706;
+        let p = UVParameters::new_pure(hydrogen_record(Some(1)));
+        let effective = p.effective_parameters(t);
+        assert_relative_eq!(effective.sigma(0), 3.2540054024660556, epsilon = 5e-7);
+        assert_relative_eq!(effective.epsilon_k(0), 21.654396207986697, epsilon = 1e-6);
+
+        // temperature derivatives of the effective parameters
+        let h = 1e-5;
+        let effective = p.effective_parameters(Dual64::from(t).derive());
+        let (e1, e2) = (p.effective_parameters(t + h), p.effective_parameters(t - h));
+        assert_relative_eq!(
+            effective.sigma(0).eps[0],
+            (e1.sigma(0) - e2.sigma(0)) / (2.0 * h),
+            max_relative = 1e-6
+        );
+        assert_relative_eq!(
+            effective.epsilon_k(0).eps[0],
+            (e1.epsilon_k(0) - e2.epsilon_k(0)) / (2.0 * h),
+            max_relative = 1e-6
+        );
+
+        // classical potential without correction
+        let p = UVParameters::new_pure(hydrogen_record(None));
+        let effective = p.effective_parameters(t);
+        assert_eq!(effective.sigma(0), 3.0243);
+        assert_eq!(effective.epsilon_k(0), 26.706);
+    }
+
+    #[test]
+    fn feynman_hibbs_convergence() {
+        let p = UVParameters::new_pure(hydrogen_record(Some(2)));
+        // the linear start points are exact in the classical limit
+        let (sigma, depth) = p.try_feynman_hibbs_ij(0, 0, 1e12).unwrap();
+        assert_relative_eq!(sigma, 3.0243, max_relative = 1e-10);
+        assert_relative_eq!(depth, 1.0, max_relative = 1e-10);
+
+        // non-convergence is reported and results in NaN parameters
+        assert!(newton(0.0, 1.0, |x: f64| (x * x + 1.0, 2.0 * x + 1e-3)).is_err());
+        let (sigma, depth) = p.feynman_hibbs_ij(0, 0, f64::NAN);
+        assert!(sigma.is_nan() && depth.is_nan());
+    }
+
+    #[test]
+    fn feynman_hibbs_orders() {
+        let records = vec![hydrogen_record(Some(1)), hydrogen_record(Some(2))];
+        let result = UVParameters::try_new_binary(records, None);
+        assert!(matches!(
+            result,
+            Err(UVTheoryError::IncompatibleParameters(e)) if e.contains("cannot combine Feynman-Hibbs")
+        ));
+    }
+
+    fn segment_records() -> Vec<SegmentRecord<UVRecord, JobackRecord>> {
+        let segment = |id: &str, mw, m, rep, sigma, epsilon_k, assoc: Option<(f64, f64)>| {
+            let mut model_record = UVRecord::new(rep, 6.0, sigma, epsilon_k).with_segments(m);
+            if let Some((kappa_ab, epsilon_k_ab)) = assoc {
+                model_record = model_record.with_association(kappa_ab, epsilon_k_ab, 1.0, 1.0);
+            }
+            SegmentRecord::new(id.into(), mw, model_record, None)
+        };
+        vec![
+            segment("CH3", 15.035, 0.6, 12.0, 3.7, 190.0, None),
+            segment("CH2", 14.027, 0.45, 14.0, 3.9, 240.0, None),
+            segment("OH", 17.007, 0.4, 12.0, 3.0, 300.0, Some((0.03, 2500.0))),
+        ]
+    }
+
+    #[test]
+    fn group_contribution() {
+        let butane = Identifier::new("106-97-8", Some("butane"), None, None, None, None);
+        let propanol = Identifier::new("71-23-8", Some("1-propanol"), None, None, None, None);
+        let segments = |s: &[&str]| s.iter().map(|s| s.to_string()).collect();
+        let list = ChemicalRecord::new(
+            butane.clone(),
+            segments(&["CH3", "CH2", "CH2", "CH3"]),
+            None,
+        );
+        let count = ChemicalRecord::new_count(
+            butane,
+            HashMap::from([("CH3".to_string(), 2.0), ("CH2".to_string(), 2.0)]),
+            None,
+        );
+        let p_list = UVParameters::try_from_segments(vec![list], segment_records(), None).unwrap();
+        let p_count =
+            UVParameters::try_from_segments(vec![count.clone()], segment_records(), None).unwrap();
+        assert_relative_eq!(p_list.m[0], 2.1, max_relative = 1e-14);
+        assert_relative_eq!(
+            p_list.sigma[0],
+            ((1.2 * 3.7f64.powi(3) + 0.9 * 3.9f64.powi(3)) / 2.1).cbrt(),
+            max_relative = 1e-14
+        );
+        assert_relative_eq!(
+            p_list.epsilon_k[0],
+            (1.2 * 190.0 + 0.9 * 240.0) / 2.1,
+            max_relative = 1e-14
+        );
+        assert_relative_eq!(
+            p_list.rep[0],
+            (1.2 * 12.0 + 0.9 * 14.0) / 2.1,
+            max_relative = 1e-14
+        );
+        assert_relative_eq!(p_list.molarweight[0], 58.124, max_relative = 1e-14);
+        assert_eq!(p_list.sigma[0], p_count.sigma[0]);
+        assert_eq!(p_list.nassoc, 0);
+
+        // binary interaction between the CH2 and OH groups
+        let propanol = ChemicalRecord::new(propanol, segments(&["CH3", "CH2", "CH2", "OH"]), None);
+        let binary = vec![BinaryRecord::new(
+            "OH".to_string(),
+            "CH2".to_string(),
+            UVBinaryRecord::from(0.1),
+        )];
+        let p =
+            UVParameters::try_from_segments(vec![count, propanol], segment_records(), Some(binary))
+                .unwrap();
+        assert_eq!(p.nassoc, 1);
+        assert_eq!(p.na[1], 1.0);
+        assert_eq!(p.kappa_ab[1], 0.03);
+        assert_relative_eq!(p.binary_records[[0, 1]].k_ij, 2.0 * 0.1 / 16.0);
+        assert_relative_eq!(p.binary_records[[1, 0]].k_ij, 2.0 * 0.1 / 16.0);
+    }
+
+    #[test]
+    fn group_contribution_multipoles() {
+        let ketone = Identifier::new("67-64-1", Some("acetone"), None, None, None, None);
+        let acetone = ChemicalRecord::new(
+            ketone,
+            vec!["CH3".to_string(), "C=O".to_string(), "CH3".to_string()],
+            None,
+        );
+        let mut segments = segment_records();
+        segments.push(SegmentRecord::new(
+            "C=O".into(),
+            28.010,
+            UVRecord::new(12.0, 6.0, 3.0, 300.0)
+                .with_segments(0.5)
+                .with_dipole(2.9),
+            None,
+        ));
+        let result = UVParameters::try_from_segments(vec![acetone.clone()], segments.clone(), None);
+        assert!(matches!(
+            result,
+            Err(UVTheoryError::IncompatibleParameters(e)) if e.contains("group C=O")
+        ));
+
+        let groups: Vec<_> = acetone
+            .segment_count(&segments)
+            .unwrap()
+            .into_iter()
+            .map(|(s, n)| (s.model_record, n))
+            .collect();
+        let result = std::panic::catch_unwind(|| UVRecord::from_segments(&groups));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn group_contribution_json() {
+        let dir = std::env::temp_dir().join(format!("uvtheory_gc_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (file_pure, file_segments) = (dir.join("pure.json"), dir.join("segments.json"));
+        std::fs::write(
+            &file_pure,
+            r#"[{"identifier": {"cas": "110-54-3", "name": "hexane"}, "segments": ["CH3", "CH2", "CH2", "CH2", "CH2", "CH3"]}]"#,
+        )
+        .unwrap();
+        std::fs::write(
+            &file_segments,
+            serde_json::to_string(&segment_records()).unwrap(),
+        )
+        .unwrap();
+        let p = UVParameters::try_from_json_segments(
+            &["hexane"],
+            &file_pure,
+            &file_segments,
+            None,
+            IdentifierOption::Name,
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_relative_eq!(p.m[0], 3.0, max_relative = 1e-14);
+        assert_eq!(p.pure_records[0].identifier.cas, "110-54-3");
+    }
 }
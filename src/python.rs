@@ -1,29 +1,64 @@
-use crate::parameters::{NoRecord, UVBinaryRecord, UVParameters, UVRecord};
-use feos_core::parameter::{Identifier, IdentifierOption, Parameter, ParameterError, PureRecord};
-use feos_core::python::parameter::PyIdentifier;
+use crate::errors::UVTheoryError;
+use crate::parameters::{CombiningRule, UVBinaryRecord, UVParameters, UVRecord};
+use feos_core::joback::JobackRecord;
+use feos_core::parameter::{
+    BinaryRecord, Identifier, IdentifierOption, ParameterError, PureRecord, SegmentRecord,
+};
+use feos_core::python::joback::PyJobackRecord;
+use feos_core::python::parameter::{PyBinarySegmentRecord, PyChemicalRecord, PyIdentifier};
 use feos_core::*;
 use ndarray::Array2;
 use numpy::PyArray2;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use std::convert::TryFrom;
-use std::rc::Rc;
+use std::sync::Arc;
 
-/// Create a set of UV Theory parameters from records.
-#[pyclass(name = "NoRecord", unsendable)]
-#[derive(Clone)]
-struct PyNoRecord(NoRecord);
+impl From<UVTheoryError> for PyErr {
+    fn from(e: UVTheoryError) -> PyErr {
+        PyValueError::new_err(e.to_string())
+    }
+}
 
 /// Create a set of UV Theory parameters from records.
-#[pyclass(name = "UVRecord", unsendable)]
-#[pyo3(text_signature = "(rep, att, sigma, epsilon_k)")]
+#[pyclass(name = "UVRecord")]
+#[pyo3(
+    text_signature = "(rep, att, sigma, epsilon_k, m=1.0, mu=None, q=None, kappa_ab=None, epsilon_k_ab=None, na=None, nb=None, fh=None)"
+)]
 #[derive(Clone)]
 pub struct PyUVRecord(UVRecord);
 
 #[pymethods]
 impl PyUVRecord {
     #[new]
-    fn new(rep: f64, att: f64, sigma: f64, epsilon_k: f64) -> Self {
-        Self(UVRecord::new(rep, att, sigma, epsilon_k))
+    #[args(m = "1.0")]
+    fn new(
+        rep: f64,
+        att: f64,
+        sigma: f64,
+        epsilon_k: f64,
+        m: f64,
+        mu: Option<f64>,
+        q: Option<f64>,
+        kappa_ab: Option<f64>,
+        epsilon_k_ab: Option<f64>,
+        na: Option<f64>,
+        nb: Option<f64>,
+        fh: Option<usize>,
+    ) -> PyResult<Self> {
+        let record = UVRecord {
+            m,
+            mu,
+            q,
+            kappa_ab,
+            epsilon_k_ab,
+            na,
+            nb,
+            fh,
+            ..UVRecord::new(rep, att, sigma, epsilon_k)
+        };
+        record.check()?;
+        Ok(Self(record))
     }
 
     fn __repr__(&self) -> PyResult<String> {
@@ -47,12 +82,12 @@ impl_json_handling!(PyUVRecord);
 ///     When not provided, all entries of `pure_records` are used.
 /// search_option : {'Name', 'Cas', 'Inchi', 'IupacName', 'Formula', 'Smiles'}, optional, defaults to 'Name'.
 ///     Identifier that is used to search substance.
-#[pyclass(name = "UVParameters", unsendable)]
+#[pyclass(name = "UVParameters")]
 #[pyo3(
     text_signature = "(pure_records, binary_records=None, substances=None, search_option='Name')"
 )]
 #[derive(Clone)]
-pub struct PyUVParameters(pub Rc<UVParameters>);
+pub struct PyUVParameters(pub Arc<UVParameters>);
 
 #[pymethods]
 impl PyUVParameters {
@@ -68,26 +103,289 @@ impl PyUVParameters {
     ///     Mie diameter in units of Angstrom
     /// epsilon_k : List[float]
     ///     Mie energy parameter in units of Kelvin
+    /// m : List[float], optional
+    ///     segment numbers, defaults to 1 for all components
     ///
     /// Returns
     /// -------
     /// UVParameters
-    #[pyo3(text_signature = "(rep, att, sigma, epsilon_k)")]
+    #[pyo3(text_signature = "(rep, att, sigma, epsilon_k, m=None)")]
     #[staticmethod]
-    fn from_lists(rep: Vec<f64>, att: Vec<f64>, sigma: Vec<f64>, epsilon_k: Vec<f64>) -> Self {
+    fn from_lists(
+        rep: Vec<f64>,
+        att: Vec<f64>,
+        sigma: Vec<f64>,
+        epsilon_k: Vec<f64>,
+        m: Option<Vec<f64>>,
+    ) -> PyResult<Self> {
         let n = rep.len();
+        if [att.len(), sigma.len(), epsilon_k.len()]
+            .iter()
+            .chain(m.as_ref().map(|m| m.len()).iter())
+            .any(|&l| l != n)
+        {
+            return Err(PyValueError::new_err(
+                "the lists of parameters must have the same length",
+            ));
+        }
+        let m = m.unwrap_or_else(|| vec![1.0; n]);
         let pure_records = (0..n)
             .map(|i| {
                 let identifier =
                     Identifier::new(format!("{}", i).as_str(), None, None, None, None, None);
-                let model_record = UVRecord::new(rep[i], att[i], sigma[i], epsilon_k[i]);
+                let model_record =
+                    UVRecord::new(rep[i], att[i], sigma[i], epsilon_k[i]).with_segments(m[i]);
                 PureRecord::new(identifier, 1.0, model_record, None)
             })
             .collect();
-        let binary = Array2::from_shape_fn((n, n), |(_, _)| UVBinaryRecord { k_ij: 0.0 });
-        Self(Rc::new(UVParameters::from_records(pure_records, binary)))
+        let binary = Array2::from_shape_fn((n, n), |(_, _)| UVBinaryRecord::from(0.0));
+        Ok(Self(Arc::new(UVParameters::try_from_records(
+            pure_records,
+            binary,
+        )?)))
+    }
+
+    /// Create parameters of the same components with a different combining rule.
+    ///
+    /// Parameters
+    /// ----------
+    /// combining_rule : CombiningRule
+    ///     combining rule for the cross interactions
+    ///
+    /// Returns
+    /// -------
+    /// UVParameters
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If the combined cross parameters are invalid.
+    #[pyo3(text_signature = "($self, combining_rule)")]
+    fn with_combining_rule(&self, combining_rule: CombiningRule) -> PyResult<Self> {
+        Ok(Self(Arc::new(self.0.with_combining_rule(combining_rule)?)))
     }
 }
 
-impl_pure_record!(UVRecord, PyUVRecord, NoRecord, PyNoRecord);
-impl_parameter!(UVParameters, PyUVParameters);
+impl_pure_record!(UVRecord, PyUVRecord, JobackRecord, PyJobackRecord);
+impl_segment_record!(UVRecord, PyUVRecord, JobackRecord, PyJobackRecord);
+
+#[pymethods]
+impl PyUVParameters {
+    /// Creates parameters from records.
+    ///
+    /// Parameters
+    /// ----------
+    /// pure_records : [PureRecord]
+    ///     A list of pure component parameters.
+    /// binary_records : numpy.ndarray[float]
+    ///     A matrix of binary interaction parameters.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If the parameters are invalid.
+    #[staticmethod]
+    #[pyo3(text_signature = "(pure_records, binary_records)")]
+    fn from_records(
+        pure_records: Vec<PyPureRecord>,
+        binary_records: &PyArray2<f64>,
+    ) -> PyResult<Self> {
+        Ok(Self(Arc::new(UVParameters::try_from_records(
+            pure_records.into_iter().map(|pr| pr.0).collect(),
+            binary_records.to_owned_array().mapv(f64::into),
+        )?)))
+    }
+
+    /// Creates parameters for a pure component from a pure record.
+    ///
+    /// Parameters
+    /// ----------
+    /// pure_record : PureRecord
+    ///     The pure component parameters.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If the parameters are invalid.
+    #[staticmethod]
+    #[pyo3(text_signature = "(pure_record)")]
+    fn new_pure(pure_record: PyPureRecord) -> PyResult<Self> {
+        Ok(Self(Arc::new(UVParameters::try_new_pure(pure_record.0)?)))
+    }
+
+    /// Creates parameters for a binary system from pure records and an optional
+    /// binary interaction parameter.
+    ///
+    /// Parameters
+    /// ----------
+    /// pure_records : [PureRecord]
+    ///     A list of pure component parameters.
+    /// binary_record : float, optional
+    ///     The binary interaction parameter.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If the parameters are invalid.
+    #[staticmethod]
+    #[pyo3(text_signature = "(pure_records, binary_record)")]
+    fn new_binary(pure_records: Vec<PyPureRecord>, binary_record: Option<f64>) -> PyResult<Self> {
+        Ok(Self(Arc::new(UVParameters::try_new_binary(
+            pure_records.into_iter().map(|pr| pr.0).collect(),
+            binary_record.map(f64::into),
+        )?)))
+    }
+
+    /// Creates parameters from json files.
+    ///
+    /// Parameters
+    /// ----------
+    /// substances : List[str]
+    ///     The substances to search.
+    /// pure_path : str
+    ///     Path to file containing pure substance parameters.
+    /// binary_path : str, optional
+    ///     Path to file containing binary substance parameters.
+    /// search_option : str, optional, defaults to "Name"
+    ///     Identifier that is used to search substance.
+    ///     One of 'Name', 'Cas', 'Inchi', 'IupacName', 'Formula', 'Smiles'
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If the parameters are invalid.
+    #[staticmethod]
+    #[pyo3(text_signature = "(substances, pure_path, binary_path=None, search_option='Name')")]
+    fn from_json(
+        substances: Vec<&str>,
+        pure_path: String,
+        binary_path: Option<String>,
+        search_option: Option<&str>,
+    ) -> PyResult<Self> {
+        let io = match search_option {
+            Some(o) => IdentifierOption::try_from(o)?,
+            None => IdentifierOption::Name,
+        };
+        Ok(Self(Arc::new(UVParameters::try_from_json(
+            substances,
+            pure_path,
+            binary_path,
+            io,
+        )?)))
+    }
+
+    /// Creates parameters from json files.
+    ///
+    /// Parameters
+    /// ----------
+    /// input : List[Tuple[List[str], str]]
+    ///     The substances to search and their respective parameter files.
+    ///     E.g. [(["methane", "propane"], "parameters/alkanes.json"), (["methanol"], "parameters/alcohols.json")]
+    /// binary_path : str, optional
+    ///     Path to file containing binary substance parameters.
+    /// search_option : str, optional, defaults to "Name"
+    ///     Identifier that is used to search substance.
+    ///     One of 'Name', 'Cas', 'Inchi', 'IupacName', 'Formula', 'Smiles'
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If the parameters are invalid.
+    #[staticmethod]
+    #[pyo3(text_signature = "(input, binary_path=None, search_option='Name')")]
+    fn from_multiple_json(
+        input: Vec<(Vec<&str>, &str)>,
+        binary_path: Option<&str>,
+        search_option: Option<&str>,
+    ) -> PyResult<Self> {
+        let io = match search_option {
+            Some(o) => IdentifierOption::try_from(o)?,
+            None => IdentifierOption::Name,
+        };
+        Ok(Self(Arc::new(UVParameters::try_from_multiple_json(
+            &input,
+            binary_path,
+            io,
+        )?)))
+    }
+
+    /// Creates parameters from segment records (homosegmented group contribution method).
+    ///
+    /// Parameters
+    /// ----------
+    /// chemical_records : [ChemicalRecord]
+    ///     A list of the segments (or segment counts) of every component.
+    /// segment_records : [SegmentRecord]
+    ///     A list of records containing the parameters of
+    ///     all individual segments.
+    /// binary_segment_records : [BinarySegmentRecord], optional
+    ///     A list of binary segment-segment interaction parameters.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If the parameters are invalid.
+    #[staticmethod]
+    #[pyo3(text_signature = "(chemical_records, segment_records, binary_segment_records=None)")]
+    fn from_segments(
+        chemical_records: Vec<PyChemicalRecord>,
+        segment_records: Vec<PySegmentRecord>,
+        binary_segment_records: Option<Vec<PyBinarySegmentRecord>>,
+    ) -> PyResult<Self> {
+        let binary_segment_records = binary_segment_records.map(|records| {
+            records
+                .into_iter()
+                .map(|r| BinaryRecord::new(r.0.id1, r.0.id2, r.0.model_record.into()))
+                .collect()
+        });
+        Ok(Self(Arc::new(UVParameters::try_from_segments(
+            chemical_records.into_iter().map(|cr| cr.0).collect(),
+            segment_records.into_iter().map(|sr| sr.0).collect(),
+            binary_segment_records,
+        )?)))
+    }
+
+    /// Creates parameters using segments from json files.
+    ///
+    /// Parameters
+    /// ----------
+    /// substances : List[str]
+    ///     The substances to search.
+    /// pure_path : str
+    ///     Path to file containing the chemical records of the substances.
+    /// segments_path : str
+    ///     Path to file containing segment parameters.
+    /// binary_path : str, optional
+    ///     Path to file containing binary segment-segment parameters.
+    /// search_option : str, optional, defaults to "Name"
+    ///     Identifier that is used to search substance.
+    ///     One of 'Name', 'Cas', 'Inchi', 'IupacName', 'Formula', 'Smiles'
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If the parameters are invalid.
+    #[staticmethod]
+    #[pyo3(
+        text_signature = "(substances, pure_path, segments_path, binary_path=None, search_option='Name')"
+    )]
+    fn from_json_segments(
+        substances: Vec<&str>,
+        pure_path: String,
+        segments_path: String,
+        binary_path: Option<String>,
+        search_option: Option<&str>,
+    ) -> PyResult<Self> {
+        let io = match search_option {
+            Some(o) => IdentifierOption::try_from(o)?,
+            None => IdentifierOption::Name,
+        };
+        Ok(Self(Arc::new(UVParameters::try_from_json_segments(
+            &substances,
+            pure_path,
+            segments_path,
+            binary_path,
+            io,
+        )?)))
+    }
+}
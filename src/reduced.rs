@@ -0,0 +1,330 @@
+//! Evaluation of uv-theory in reduced units.
+//!
+//! Temperatures are reduced by the energy parameter `epsilon/k` of the reference,
+//! densities by `sigma^-3`, energies by `epsilon` and pressures by
+//! `epsilon/sigma^3`. Internally, the equation of state is parametrized with
+//! `sigma = 1 A` and `epsilon/k = 1 K`, so that no SI quantities are involved.
+use crate::{
+    UVBinaryRecord, UVParameters, UVRecord, UVTheory, UVTheoryError, UVTheoryOptions,
+    UVTheoryResult,
+};
+use feos_core::parameter::{Identifier, PureRecord};
+use feos_core::{EquationOfState, HelmholtzEnergy, HelmholtzEnergyDual, StateHD};
+use ndarray::{Array1, Array2};
+use num_dual::DualNum;
+use std::sync::Arc;
+
+/// Residual properties in reduced units.
+#[derive(Clone, Debug)]
+pub struct ReducedProperties {
+    /// Residual Helmholtz energy per particle A^res / (N epsilon)
+    pub helmholtz_energy: f64,
+    /// Residual pressure p^res sigma^3 / epsilon
+    pub pressure: f64,
+    /// Residual internal energy per particle U^res / (N epsilon)
+    pub internal_energy: f64,
+    /// Residual chemical potentials mu_i^res / epsilon
+    pub chemical_potential: Array1<f64>,
+    /// Derivative of the residual pressure with respect to the reduced density
+    pub dp_drho: f64,
+    /// Derivative of the residual pressure with respect to the reduced temperature
+    pub dp_dt: f64,
+    /// Residual isochoric heat capacity per particle C_v^res / (N k)
+    pub c_v: f64,
+}
+
+/// uv-theory in reduced units.
+pub struct ReducedUVTheory {
+    eos: UVTheory,
+}
+
+impl ReducedUVTheory {
+    /// Pure Mie fluid with repulsive exponent `rep` and attractive exponent `att`.
+    ///
+    /// Returns an error for invalid exponents or incompatible options.
+    pub fn pure(rep: f64, att: f64, options: UVTheoryOptions) -> UVTheoryResult<Self> {
+        let record = UVRecord::new(rep, att, 1.0, 1.0);
+        let pure_record = PureRecord::new(Identifier::default(), 1.0, record, None);
+        Self::from_parameters(UVParameters::try_new_pure(pure_record)?, options)
+    }
+
+    /// Mixture of Mie fluids defined by the parameters of all pairs.
+    ///
+    /// The segment diameters `sigma_ij` and energy parameters `epsilon_ij` are
+    /// given relative to those of the reference.
+    ///
+    /// Returns an error if the matrices are not square matrices of the same shape,
+    /// for invalid parameters or for incompatible options.
+    pub fn mixture(
+        rep_ij: &Array2<f64>,
+        att_ij: &Array2<f64>,
+        sigma_ij: &Array2<f64>,
+        epsilon_ij: &Array2<f64>,
+        options: UVTheoryOptions,
+    ) -> UVTheoryResult<Self> {
+        let n = rep_ij.nrows();
+        for p in [rep_ij, att_ij, sigma_ij, epsilon_ij] {
+            if p.shape() != [n, n] {
+                return Err(UVTheoryError::IncompatibleParameters(format!(
+                    "expected pair parameters of shape [{}, {}], got {:?}",
+                    n,
+                    n,
+                    p.shape()
+                )));
+            }
+        }
+        let pure_records = (0..n)
+            .map(|i| {
+                let record = UVRecord::new(
+                    rep_ij[[i, i]],
+                    att_ij[[i, i]],
+                    sigma_ij[[i, i]],
+                    epsilon_ij[[i, i]],
+                );
+                let identifier = Identifier::new(&i.to_string(), None, None, None, None, None);
+                PureRecord::new(identifier, 1.0, record, None)
+            })
+            .collect();
+        let binary_records = Array2::from_shape_fn((n, n), |(i, j)| UVBinaryRecord {
+            sigma_ij: Some(sigma_ij[[i, j]]),
+            epsilon_k_ij: Some(epsilon_ij[[i, j]]),
+            rep_ij: Some(rep_ij[[i, j]]),
+            att_ij: Some(att_ij[[i, j]]),
+            ..Default::default()
+        });
+        Self::from_parameters(
+            UVParameters::try_from_records(pure_records, binary_records)?,
+            options,
+        )
+    }
+
+    /// uv-theory for parameters in reduced units, i.e., with segment diameters
+    /// in units of sigma (in Angstrom) and energy parameters in units of epsilon (in Kelvin).
+    ///
+    /// Returns an error for incompatible options.
+    pub fn from_parameters(
+        parameters: UVParameters,
+        options: UVTheoryOptions,
+    ) -> UVTheoryResult<Self> {
+        Ok(Self {
+            eos: UVTheory::with_options(Arc::new(parameters), options)?,
+        })
+    }
+
+    /// Number of components.
+    pub fn components(&self) -> usize {
+        self.eos.components()
+    }
+
+    /// Reduced residual Helmholtz energy A^res / (NkT) of every contribution.
+    ///
+    /// Derivatives of any order are obtained by evaluating the function
+    /// with the corresponding dual numbers.
+    pub fn helmholtz_energy_contributions<D: DualNum<f64>>(
+        &self,
+        temperature: D,
+        density: D,
+        molefracs: &Array1<D>,
+    ) -> Vec<(String, D)>
+    where
+        dyn HelmholtzEnergy: HelmholtzEnergyDual<D>,
+    {
+        let state = StateHD::new(temperature, density.recip(), molefracs.clone());
+        self.eos.evaluate_residual_contributions(&state)
+    }
+
+    /// Reduced residual Helmholtz energy A^res / (NkT).
+    pub fn helmholtz_energy<D: DualNum<f64>>(
+        &self,
+        temperature: D,
+        density: D,
+        molefracs: &Array1<D>,
+    ) -> D
+    where
+        dyn HelmholtzEnergy: HelmholtzEnergyDual<D>,
+    {
+        self.helmholtz_energy_contributions(temperature, density, molefracs)
+            .into_iter()
+            .fold(D::zero(), |acc, (_, a)| acc + a)
+    }
+
+    /// Residual properties of every contribution.
+    pub fn properties_contributions(
+        &self,
+        temperature: f64,
+        density: f64,
+        molefracs: &Array1<f64>,
+    ) -> Vec<(String, ReducedProperties)> {
+        let (t, rho) = (temperature, density);
+        self.eos
+            .derivatives_contributions(t, rho, molefracs.view())
+            .into_iter()
+            .map(|(name, d)| {
+                let properties = ReducedProperties {
+                    helmholtz_energy: d.a * t,
+                    pressure: d.a_rho * rho * rho * t,
+                    internal_energy: -d.a_t * t * t,
+                    chemical_potential: d.mu * t,
+                    dp_drho: (d.a_rhorho * rho + 2.0 * d.a_rho) * rho * t,
+                    dp_dt: (d.a_rho + d.a_trho * t) * rho * rho,
+                    c_v: -2.0 * d.a_t * t - d.a_tt * t * t,
+                };
+                (name, properties)
+            })
+            .collect()
+    }
+
+    /// Residual properties.
+    pub fn properties(
+        &self,
+        temperature: f64,
+        density: f64,
+        molefracs: &Array1<f64>,
+    ) -> ReducedProperties {
+        let mut contributions = self
+            .properties_contributions(temperature, density, molefracs)
+            .into_iter()
+            .map(|(_, p)| p);
+        let first = contributions.next().unwrap();
+        contributions.fold(first, |acc, p| ReducedProperties {
+            helmholtz_energy: acc.helmholtz_energy + p.helmholtz_energy,
+            pressure: acc.pressure + p.pressure,
+            internal_energy: acc.internal_energy + p.internal_energy,
+            chemical_potential: acc.chemical_potential + p.chemical_potential,
+            dp_drho: acc.dp_drho + p.dp_drho,
+            dp_dt: acc.dp_dt + p.dp_dt,
+            c_v: acc.c_v + p.c_v,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parameters::utils::test_parameters;
+    use approx::assert_relative_eq;
+    use feos_core::{Contributions, State};
+    use ndarray::arr1;
+    use quantity::si::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn reduced_properties() {
+        // reduced properties agree with SI properties for sigma = 1 A and epsilon/k = 1 K
+        let reduced = ReducedUVTheory::pure(12.0, 6.0, UVTheoryOptions::default()).unwrap();
+        let eos = Rc::new(UVTheory::new(Arc::new(test_parameters(
+            12.0, 6.0, 1.0, 1.0,
+        ))));
+        let (t, rho) = (1.3, 0.6);
+        let x = arr1(&[1.0]);
+        let properties = reduced.properties(t, rho, &x);
+        let state = State::new_pure(&eos, t * KELVIN, rho / (ANGSTROM.powi(3) * NAV)).unwrap();
+        let energy = KB * KELVIN;
+        let pressure = energy / ANGSTROM.powi(3);
+        assert_relative_eq!(
+            properties.helmholtz_energy,
+            (state.helmholtz_energy(Contributions::ResidualNvt) / state.total_moles)
+                .to_reduced(RGAS * KELVIN)
+                .unwrap(),
+            max_relative = 1e-12
+        );
+        assert_relative_eq!(
+            properties.pressure,
+            state
+                .pressure(Contributions::ResidualNvt)
+                .to_reduced(pressure)
+                .unwrap(),
+            max_relative = 1e-12
+        );
+        assert_relative_eq!(
+            properties.internal_energy,
+            (state.internal_energy(Contributions::ResidualNvt) / state.total_moles)
+                .to_reduced(RGAS * KELVIN)
+                .unwrap(),
+            max_relative = 1e-12
+        );
+        assert_relative_eq!(
+            properties.chemical_potential[0],
+            state
+                .chemical_potential(Contributions::ResidualNvt)
+                .get(0)
+                .to_reduced(RGAS * KELVIN)
+                .unwrap(),
+            max_relative = 1e-12
+        );
+        assert_relative_eq!(
+            properties.dp_drho,
+            state
+                .dp_drho(Contributions::ResidualNvt)
+                .to_reduced(pressure * ANGSTROM.powi(3) * NAV)
+                .unwrap(),
+            max_relative = 1e-12
+        );
+        assert_relative_eq!(
+            properties.dp_dt,
+            state
+                .dp_dt(Contributions::ResidualNvt)
+                .to_reduced(pressure / KELVIN)
+                .unwrap(),
+            max_relative = 1e-12
+        );
+        assert_relative_eq!(
+            properties.c_v,
+            state
+                .c_v(Contributions::ResidualNvt)
+                .to_reduced(RGAS)
+                .unwrap(),
+            max_relative = 1e-12
+        );
+    }
+
+    #[test]
+    fn reduced_mixture() {
+        // a mixture of identical components behaves like the pure fluid
+        let pure = ReducedUVTheory::pure(12.0, 6.0, UVTheoryOptions::default()).unwrap();
+        let ones = Array2::ones((2, 2));
+        let mixture = ReducedUVTheory::mixture(
+            &(&ones * 12.0),
+            &(&ones * 6.0),
+            &ones,
+            &ones,
+            UVTheoryOptions::default(),
+        )
+        .unwrap();
+        let p_pure = pure.properties_contributions(1.5, 0.7, &arr1(&[1.0]));
+        let p_mix = mixture.properties_contributions(1.5, 0.7, &arr1(&[0.4, 0.6]));
+        for ((name, p), (name_mix, p_mix)) in p_pure.iter().zip(p_mix.iter()) {
+            assert_eq!(name, name_mix);
+            assert_relative_eq!(
+                p.helmholtz_energy,
+                p_mix.helmholtz_energy,
+                max_relative = 1e-12
+            );
+            assert_relative_eq!(p.pressure, p_mix.pressure, max_relative = 1e-12);
+            assert_relative_eq!(
+                p.chemical_potential[0],
+                p_mix.chemical_potential[1],
+                max_relative = 1e-12
+            );
+        }
+    }
+
+    #[test]
+    fn reduced_errors() {
+        assert!(matches!(
+            ReducedUVTheory::pure(6.0, 6.0, UVTheoryOptions::default()),
+            Err(UVTheoryError::InvalidParameter { field: "rep", .. })
+        ));
+        let ones = Array2::ones((2, 2));
+        assert!(matches!(
+            ReducedUVTheory::mixture(
+                &(&ones * 12.0),
+                &Array2::from_elem((3, 3), 6.0),
+                &ones,
+                &ones,
+                UVTheoryOptions::default(),
+            ),
+            Err(UVTheoryError::IncompatibleParameters(_))
+        ));
+    }
+}
@@ -0,0 +1,611 @@
+use super::levenberg_marquardt;
+use crate::errors::{UVTheoryError, UVTheoryResult};
+use crate::{CombiningRule, UVBinaryRecord, UVParameters, UVRecord, UVTheory, UVTheoryOptions};
+use feos_core::joback::JobackRecord;
+use feos_core::parameter::{BinaryRecord, Identifier, PureRecord};
+use feos_core::{
+    Contributions, DensityInitialization, EosResult, PhaseEquilibrium, SolverOptions, State,
+};
+use ndarray::{arr1, Array1, Array2};
+use quantity::si::*;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Step size of the finite differences of the binary parameters.
+const STEP: f64 = 1e-6;
+
+/// Parameter of a [UVBinaryRecord] that is adjusted in a [BinaryRegression].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryParameter {
+    KIj,
+    KIjT,
+    KIjLnT,
+    LIj,
+    /// Explicit cross segment diameter, initialized with the combining rule if not given.
+    SigmaIj,
+    /// Explicit cross energy parameter, initialized with the combining rule if not given.
+    EpsilonKIj,
+    /// Explicit cross repulsive exponent, initialized with the combining rule if not given.
+    RepIj,
+}
+
+impl BinaryParameter {
+    fn get(&self, record: &UVBinaryRecord, parameters: &UVParameters) -> f64 {
+        match self {
+            Self::KIj => record.k_ij,
+            Self::KIjT => record.k_ij_t,
+            Self::KIjLnT => record.k_ij_ln_t,
+            Self::LIj => record.l_ij,
+            Self::SigmaIj => record.sigma_ij.unwrap_or(parameters.sigma_ij[[0, 1]]),
+            Self::EpsilonKIj => record.epsilon_k_ij.unwrap_or(parameters.eps_k_ij[[0, 1]]),
+            Self::RepIj => record.rep_ij.unwrap_or(parameters.rep_ij[[0, 1]]),
+        }
+    }
+
+    fn set(&self, record: &mut UVBinaryRecord, value: f64) {
+        match self {
+            Self::KIj => record.k_ij = value,
+            Self::KIjT => record.k_ij_t = value,
+            Self::KIjLnT => record.k_ij_ln_t = value,
+            Self::LIj => record.l_ij = value,
+            Self::SigmaIj => record.sigma_ij = Some(value),
+            Self::EpsilonKIj => record.epsilon_k_ij = Some(value),
+            Self::RepIj => record.rep_ij = Some(value),
+        }
+    }
+}
+
+impl fmt::Display for BinaryParameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KIj => write!(f, "k_ij"),
+            Self::KIjT => write!(f, "k_ij_t"),
+            Self::KIjLnT => write!(f, "k_ij_ln_t"),
+            Self::LIj => write!(f, "l_ij"),
+            Self::SigmaIj => write!(f, "sigma_ij"),
+            Self::EpsilonKIj => write!(f, "epsilon_k_ij"),
+            Self::RepIj => write!(f, "rep_ij"),
+        }
+    }
+}
+
+/// Experimental phase equilibrium data of a binary mixture.
+///
+/// All mole fractions are those of the first component.
+#[derive(Clone, Debug)]
+pub enum BinaryData {
+    /// Bubble points, either isothermal or isobaric. The bubble point pressure
+    /// is calculated at the given temperature and liquid composition and
+    /// compared to the pressure and, if given, the vapor composition.
+    BubblePoint {
+        temperature: SIArray1,
+        pressure: SIArray1,
+        liquid_molefracs: Array1<f64>,
+        vapor_molefracs: Option<Array1<f64>>,
+    },
+    /// Liquid-liquid tie lines. The deviations are the differences of the
+    /// logarithmic fugacities of both components in the two phases.
+    LiquidLiquid {
+        temperature: SIArray1,
+        pressure: SIArray1,
+        molefracs_phase_1: Array1<f64>,
+        molefracs_phase_2: Array1<f64>,
+    },
+}
+
+impl BinaryData {
+    fn temperature(&self) -> &SIArray1 {
+        match self {
+            Self::BubblePoint { temperature, .. } | Self::LiquidLiquid { temperature, .. } => {
+                temperature
+            }
+        }
+    }
+
+    fn pressure(&self) -> &SIArray1 {
+        match self {
+            Self::BubblePoint { pressure, .. } | Self::LiquidLiquid { pressure, .. } => pressure,
+        }
+    }
+
+    fn molefracs(&self) -> [Option<&Array1<f64>>; 2] {
+        match self {
+            Self::BubblePoint {
+                liquid_molefracs,
+                vapor_molefracs,
+                ..
+            } => [Some(liquid_molefracs), vapor_molefracs.as_ref()],
+            Self::LiquidLiquid {
+                molefracs_phase_1,
+                molefracs_phase_2,
+                ..
+            } => [Some(molefracs_phase_1), Some(molefracs_phase_2)],
+        }
+    }
+
+    /// Number of data points.
+    pub fn len(&self) -> usize {
+        self.temperature().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of residuals per data point.
+    fn residuals_per_point(&self) -> usize {
+        match self {
+            Self::BubblePoint {
+                vapor_molefracs, ..
+            } => 1 + vapor_molefracs.is_some() as usize,
+            Self::LiquidLiquid { .. } => 2,
+        }
+    }
+
+    fn check(&self) -> UVTheoryResult<()> {
+        let invalid =
+            |reason: &str| Err(UVTheoryError::InvalidData(format!("{}: {}", self, reason)));
+        if self.is_empty() {
+            return invalid("no data points");
+        }
+        let n = self.len();
+        if self.pressure().len() != n || self.molefracs().iter().flatten().any(|x| x.len() != n) {
+            return invalid("the numbers of temperatures, pressures and compositions differ");
+        }
+        if !(self.temperature().has_unit(&KELVIN) && self.pressure().has_unit(&PASCAL)) {
+            return invalid("wrong units");
+        }
+        if self
+            .molefracs()
+            .iter()
+            .flatten()
+            .any(|x| x.iter().any(|&x| !(0.0..=1.0).contains(&x)))
+        {
+            return invalid("mole fractions must be between 0 and 1");
+        }
+        Ok(())
+    }
+
+    /// Deviations of point `i`.
+    fn deviations(&self, eos: &Rc<UVTheory>, i: usize) -> EosResult<Vec<f64>> {
+        let t = self.temperature().get(i);
+        let p = self.pressure().get(i);
+        match self {
+            Self::BubblePoint {
+                liquid_molefracs,
+                vapor_molefracs,
+                ..
+            } => {
+                let x = arr1(&[liquid_molefracs[i], 1.0 - liquid_molefracs[i]]);
+                let vle = PhaseEquilibrium::bubble_point(
+                    eos,
+                    t,
+                    &x,
+                    Some(p),
+                    None,
+                    (SolverOptions::default(), SolverOptions::default()),
+                )?;
+                let mut deviations =
+                    vec![(vle.vapor().pressure(Contributions::Total) / p).into_value()? - 1.0];
+                if let Some(y) = vapor_molefracs {
+                    deviations.push(vle.vapor().molefracs[0] - y[i]);
+                }
+                Ok(deviations)
+            }
+            Self::LiquidLiquid {
+                molefracs_phase_1,
+                molefracs_phase_2,
+                ..
+            } => {
+                let ln_fugacity = |x1: f64| -> EosResult<Array1<f64>> {
+                    let x = arr1(&[x1, 1.0 - x1]);
+                    let state =
+                        State::new_npt(eos, t, p, &(&x * MOL), DensityInitialization::Liquid)?;
+                    Ok(state.ln_phi() + x.mapv(f64::ln))
+                };
+                let d = ln_fugacity(molefracs_phase_1[i])? - ln_fugacity(molefracs_phase_2[i])?;
+                Ok(d.to_vec())
+            }
+        }
+    }
+}
+
+impl fmt::Display for BinaryData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BubblePoint { .. } => write!(f, "bubble point"),
+            Self::LiquidLiquid { .. } => write!(f, "liquid-liquid equilibrium"),
+        }
+    }
+}
+
+/// Adjustment of the binary interaction parameters of a binary mixture to
+/// experimental phase equilibrium data.
+///
+/// The pure component parameters are kept fixed. The objective function is the
+/// weighted sum of the mean squared deviations of every data set: relative
+/// deviations of bubble point pressures, absolute deviations of vapor mole
+/// fractions and differences of the logarithmic fugacities of the coexisting
+/// liquid phases. It is minimized with the Levenberg-Marquardt algorithm and
+/// derivatives by central finite differences.
+pub struct BinaryRegression {
+    pure_records: Vec<PureRecord<UVRecord, JobackRecord>>,
+    binary_record: UVBinaryRecord,
+    combining_rule: CombiningRule,
+    parameters: Vec<BinaryParameter>,
+    initial_values: Array1<f64>,
+    options: UVTheoryOptions,
+    data: Vec<(BinaryData, f64)>,
+}
+
+impl BinaryRegression {
+    /// Adjust `parameters` of the binary record of the (binary) `uv_parameters`.
+    pub fn new(
+        uv_parameters: &UVParameters,
+        parameters: Vec<BinaryParameter>,
+        options: UVTheoryOptions,
+    ) -> UVTheoryResult<Self> {
+        if uv_parameters.ncomponents != 2 {
+            return Err(UVTheoryError::IncompatibleParameters(format!(
+                "binary parameters can only be adjusted for 2 components, got {}",
+                uv_parameters.ncomponents
+            )));
+        }
+        let binary_record = uv_parameters.binary_records[[0, 1]].clone();
+        let initial_values = parameters
+            .iter()
+            .map(|p| p.get(&binary_record, uv_parameters))
+            .collect();
+        Ok(Self {
+            pure_records: uv_parameters.pure_records.clone(),
+            binary_record,
+            combining_rule: uv_parameters.combining_rule,
+            parameters,
+            initial_values,
+            options,
+            data: Vec::new(),
+        })
+    }
+
+    /// Add a data set with the given weight in the objective function.
+    pub fn add_data(&mut self, data: BinaryData, weight: f64) -> UVTheoryResult<()> {
+        data.check()?;
+        if !(weight.is_finite() && weight > 0.0) {
+            return Err(UVTheoryError::InvalidData(format!(
+                "{}: weight must be positive",
+                data
+            )));
+        }
+        self.data.push((data, weight));
+        Ok(())
+    }
+
+    fn binary_record(&self, values: &Array1<f64>) -> UVBinaryRecord {
+        let mut binary_record = self.binary_record.clone();
+        for (p, &value) in self.parameters.iter().zip(values) {
+            p.set(&mut binary_record, value);
+        }
+        binary_record
+    }
+
+    fn eos(&self, values: &Array1<f64>) -> UVTheoryResult<Rc<UVTheory>> {
+        let binary_record = self.binary_record(values);
+        let binary_records = Array2::from_shape_fn([2, 2], |(i, j)| {
+            if i == j {
+                UVBinaryRecord::default()
+            } else {
+                binary_record.clone()
+            }
+        });
+        let parameters = UVParameters::try_from_records_with_combining_rule(
+            self.pure_records.clone(),
+            binary_records,
+            self.combining_rule,
+        )?;
+        Ok(Rc::new(UVTheory::with_options(
+            Arc::new(parameters),
+            self.options.clone(),
+        )?))
+    }
+
+    /// Deviations of every data point, `None` for points at which the
+    /// phase equilibrium cannot be calculated.
+    fn deviations(&self, eos: &Rc<UVTheory>) -> Vec<Vec<Option<Vec<f64>>>> {
+        self.data
+            .iter()
+            .map(|(data, _)| {
+                (0..data.len())
+                    .map(|i| data.deviations(eos, i).ok())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn residuals(&self, values: &Array1<f64>) -> UVTheoryResult<Array1<f64>> {
+        let eos = self.eos(values)?;
+        let mut residuals = Vec::new();
+        for ((data, weight), deviations) in self.data.iter().zip(self.deviations(&eos)) {
+            let scale = (weight / data.len() as f64).sqrt();
+            for d in deviations {
+                // points that cannot be calculated are penalized with a deviation of 1
+                match d {
+                    Some(d) => residuals.extend(d.into_iter().map(|d| scale * d)),
+                    None => residuals.extend((0..data.residuals_per_point()).map(|_| scale)),
+                }
+            }
+        }
+        Ok(Array1::from_vec(residuals))
+    }
+
+    fn jacobian(&self, values: &Array1<f64>) -> UVTheoryResult<Array2<f64>> {
+        let n = self
+            .data
+            .iter()
+            .map(|(d, _)| d.len() * d.residuals_per_point())
+            .sum();
+        let mut jacobian = Array2::zeros((n, values.len()));
+        for j in 0..values.len() {
+            let step = STEP * values[j].abs().max(1.0);
+            let mut perturbed = values.clone();
+            perturbed[j] += step;
+            let plus = self.residuals(&perturbed)?;
+            perturbed[j] -= 2.0 * step;
+            let minus = self.residuals(&perturbed)?;
+            jacobian
+                .column_mut(j)
+                .assign(&((plus - minus) / (2.0 * step)));
+        }
+        Ok(jacobian)
+    }
+
+    /// Adjust the parameters.
+    pub fn solve(&self, options: SolverOptions) -> UVTheoryResult<BinaryRegressionResult> {
+        if self.data.is_empty() {
+            return Err(UVTheoryError::InvalidData("no data sets".into()));
+        }
+        let result = levenberg_marquardt(
+            self.initial_values.clone(),
+            |x| self.residuals(x),
+            |x| self.jacobian(x),
+            options,
+        )?;
+        let eos = self.eos(&result.x)?;
+        let deviations = self
+            .data
+            .iter()
+            .zip(self.deviations(&eos))
+            .map(|((data, _), d)| BinaryDeviations::new(data, d))
+            .collect();
+        Ok(BinaryRegressionResult {
+            binary_record: BinaryRecord::new(
+                self.pure_records[0].identifier.clone(),
+                self.pure_records[1].identifier.clone(),
+                self.binary_record(&result.x),
+            ),
+            parameters: self.parameters.clone(),
+            values: result.x.clone(),
+            covariance: result.covariance(),
+            deviations,
+            iterations: result.iterations,
+        })
+    }
+}
+
+/// Deviations of a data set of a [BinaryRegression], `NaN` for points at
+/// which the phase equilibrium cannot be calculated.
+#[derive(Clone, Debug)]
+pub enum BinaryDeviations {
+    BubblePoint {
+        /// Relative deviations of the bubble point pressures.
+        pressure: Array1<f64>,
+        /// Absolute deviations of the vapor mole fractions.
+        vapor_molefracs: Option<Array1<f64>>,
+    },
+    LiquidLiquid {
+        /// Differences of the logarithmic fugacities with shape `(points, components)`.
+        ln_fugacity: Array2<f64>,
+    },
+}
+
+impl BinaryDeviations {
+    fn new(data: &BinaryData, deviations: Vec<Option<Vec<f64>>>) -> Self {
+        let column = |k: usize| {
+            deviations
+                .iter()
+                .map(|d| d.as_ref().map_or(f64::NAN, |d| d[k]))
+                .collect()
+        };
+        match data {
+            BinaryData::BubblePoint {
+                vapor_molefracs, ..
+            } => Self::BubblePoint {
+                pressure: column(0),
+                vapor_molefracs: vapor_molefracs.as_ref().map(|_| column(1)),
+            },
+            BinaryData::LiquidLiquid { .. } => Self::LiquidLiquid {
+                ln_fugacity: Array2::from_shape_fn((deviations.len(), 2), |(i, k)| {
+                    deviations[i].as_ref().map_or(f64::NAN, |d| d[k])
+                }),
+            },
+        }
+    }
+}
+
+/// Average absolute value, ignoring `NaN`s.
+fn aad<'a, I: Iterator<Item = &'a f64>>(deviations: I) -> f64 {
+    let valid: Vec<f64> = deviations
+        .filter(|d| !d.is_nan())
+        .map(|d| d.abs())
+        .collect();
+    valid.iter().sum::<f64>() / valid.len() as f64
+}
+
+/// Result of a [BinaryRegression].
+#[derive(Clone, Debug)]
+pub struct BinaryRegressionResult {
+    /// Binary record with the adjusted parameters and the identifiers of both components.
+    pub binary_record: BinaryRecord<Identifier, UVBinaryRecord>,
+    /// Adjusted parameters in the order of `values` and the covariance matrix.
+    pub parameters: Vec<BinaryParameter>,
+    pub values: Array1<f64>,
+    /// Covariance matrix of the adjusted parameters.
+    pub covariance: Array2<f64>,
+    /// Deviations of every data set.
+    pub deviations: Vec<BinaryDeviations>,
+    pub iterations: usize,
+}
+
+impl BinaryRegressionResult {
+    /// Standard deviations of the adjusted parameters.
+    pub fn standard_deviations(&self) -> Array1<f64> {
+        self.covariance.diag().mapv(f64::sqrt)
+    }
+
+    /// Average absolute relative deviation of all bubble point pressures.
+    pub fn aad_pressure(&self) -> f64 {
+        aad(self
+            .deviations
+            .iter()
+            .filter_map(|d| match d {
+                BinaryDeviations::BubblePoint { pressure, .. } => Some(pressure),
+                _ => None,
+            })
+            .flatten())
+    }
+
+    /// Average absolute deviation of all vapor mole fractions.
+    pub fn aad_vapor_molefracs(&self) -> f64 {
+        aad(self
+            .deviations
+            .iter()
+            .filter_map(|d| match d {
+                BinaryDeviations::BubblePoint {
+                    vapor_molefracs, ..
+                } => vapor_molefracs.as_ref(),
+                _ => None,
+            })
+            .flatten())
+    }
+
+    /// The binary record as JSON, in the format of the binary parameter files
+    /// read by [UVParameters::try_from_json].
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&[&self.binary_record])
+            .expect("binary records can always be serialized")
+    }
+}
+
+impl fmt::Display for BinaryRegressionResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "|parameter|value|standard deviation|")?;
+        write!(f, "|-|-|-|")?;
+        for ((p, v), s) in self
+            .parameters
+            .iter()
+            .zip(&self.values)
+            .zip(self.standard_deviations())
+        {
+            write!(f, "\n|{}|{}|{}|", p, v, s)?;
+        }
+        let aad_p = self.aad_pressure();
+        if !aad_p.is_nan() {
+            write!(f, "\n\nAAD pressure: {} %", 100.0 * aad_p)?;
+        }
+        let aad_y = self.aad_vapor_molefracs();
+        if !aad_y.is_nan() {
+            write!(f, "\nAAD vapor mole fraction: {}", aad_y)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_relative_eq;
+    use feos_core::parameter::Parameter;
+
+    fn parameters(epsilon_k: [f64; 2], k_ij: f64) -> UVParameters {
+        let record = |id: &str, epsilon_k| {
+            let model_record = UVRecord::new(12.0, 6.0, 3.4, epsilon_k);
+            let identifier = Identifier::new(id, Some(id), None, None, None, None);
+            PureRecord::new(identifier, 40.0, model_record, None)
+        };
+        UVParameters::new_binary(
+            vec![record("1", epsilon_k[0]), record("2", epsilon_k[1])],
+            Some(k_ij.into()),
+        )
+    }
+
+    #[test]
+    fn fit_bubble_points() {
+        let eos = Rc::new(UVTheory::new(Arc::new(parameters([120.0, 150.0], 0.05))));
+        let t = 120.0 * KELVIN;
+        let x = arr1(&[0.2, 0.4, 0.6, 0.8]);
+        let vle: Vec<_> = x
+            .iter()
+            .map(|&x| {
+                PhaseEquilibrium::bubble_point(
+                    &eos,
+                    t,
+                    &arr1(&[x, 1.0 - x]),
+                    None,
+                    None,
+                    Default::default(),
+                )
+                .unwrap()
+            })
+            .collect();
+        let data = BinaryData::BubblePoint {
+            temperature: SIArray1::from_shape_fn(4, |_| t),
+            pressure: SIArray1::from_shape_fn(4, |i| vle[i].vapor().pressure(Contributions::Total)),
+            liquid_molefracs: x,
+            vapor_molefracs: Some(Array1::from_shape_fn(4, |i| vle[i].vapor().molefracs[0])),
+        };
+
+        let mut regression = BinaryRegression::new(
+            &parameters([120.0, 150.0], 0.0),
+            vec![BinaryParameter::KIj],
+            Default::default(),
+        )
+        .unwrap();
+        regression.add_data(data, 1.0).unwrap();
+        let result = regression.solve(Default::default()).unwrap();
+        assert_relative_eq!(result.binary_record.model_record.k_ij, 0.05, epsilon = 1e-6);
+        assert!(result.aad_pressure() < 1e-6);
+        assert!(result.aad_vapor_molefracs() < 1e-6);
+
+        let records: Vec<BinaryRecord<Identifier, UVBinaryRecord>> =
+            serde_json::from_str(&result.to_json()).unwrap();
+        assert_eq!(records[0].id1.name, Some("1".into()));
+        assert_relative_eq!(records[0].model_record.k_ij, 0.05, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn fit_liquid_liquid() {
+        let eos = Rc::new(UVTheory::new(Arc::new(parameters([120.0, 130.0], 0.2))));
+        let (t, p) = (80.0 * KELVIN, 10.0 * MEGA * PASCAL);
+        let feed = arr1(&[0.5, 0.5]) * MOL;
+        let lle =
+            PhaseEquilibrium::tp_flash(&eos, t, p, &feed, None, Default::default(), None).unwrap();
+        let (x1, x2) = (lle.liquid().molefracs[0], lle.vapor().molefracs[0]);
+        assert!((x1 - x2).abs() > 0.1);
+
+        let mut regression = BinaryRegression::new(
+            &parameters([120.0, 130.0], 0.15),
+            vec![BinaryParameter::KIj],
+            Default::default(),
+        )
+        .unwrap();
+        let data = BinaryData::LiquidLiquid {
+            temperature: arr1(&[80.0]) * KELVIN,
+            pressure: arr1(&[10.0]) * MEGA * PASCAL,
+            molefracs_phase_1: arr1(&[x1]),
+            molefracs_phase_2: arr1(&[x2]),
+        };
+        regression.add_data(data, 1.0).unwrap();
+        let result = regression.solve(Default::default()).unwrap();
+        assert_relative_eq!(result.binary_record.model_record.k_ij, 0.2, epsilon = 1e-5);
+    }
+}
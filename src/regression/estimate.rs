@@ -0,0 +1,178 @@
+use crate::errors::{UVTheoryError, UVTheoryResult};
+use crate::{UVParameters, UVRecord, UVTheory, UVTheoryOptions, ValidityDomain};
+use feos_core::parameter::{Identifier, PureRecord};
+use feos_core::{Contributions, EosError, PhaseEquilibrium, SolverOptions, State};
+use quantity::si::*;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Tolerance of the repulsive exponent.
+const TOL_REP: f64 = 1e-8;
+const MAX_ITER_REP: usize = 100;
+
+/// Properties of a Mie n-6 fluid with `sigma = 1 A` and `epsilon / k = 1 K`
+/// in reduced units.
+struct ReducedCriticalPoint {
+    eos: Rc<UVTheory>,
+    temperature: f64,
+    pressure: f64,
+    acentric_factor: f64,
+}
+
+impl ReducedCriticalPoint {
+    fn new(rep: f64, options: &UVTheoryOptions) -> UVTheoryResult<Self> {
+        let record = UVRecord::new(rep, 6.0, 1.0, 1.0);
+        let pure_record = PureRecord::new(Identifier::default(), 1.0, record, None);
+        let parameters = UVParameters::try_new_pure(pure_record)?;
+        let eos = Rc::new(UVTheory::with_options(
+            Arc::new(parameters),
+            options.clone(),
+        )?);
+        let cp = State::critical_point(&eos, None, Some(1.3 * KELVIN), SolverOptions::default())?;
+        let temperature = cp.temperature.to_reduced(KELVIN)?;
+        let pressure = cp.pressure(Contributions::Total);
+        let vle = PhaseEquilibrium::pure(
+            &eos,
+            0.7 * temperature * KELVIN,
+            None,
+            SolverOptions::default(),
+        )?;
+        let acentric_factor = -(vle.vapor().pressure(Contributions::Total) / pressure)
+            .into_value()?
+            .log10()
+            - 1.0;
+        Ok(Self {
+            eos,
+            temperature,
+            pressure: pressure.to_reduced(KB * KELVIN / ANGSTROM.powi(3))?,
+            acentric_factor,
+        })
+    }
+}
+
+impl UVRecord {
+    /// Estimate the parameters of a spherical Mie n-6 fluid from the critical
+    /// temperature, the critical pressure and the acentric factor.
+    ///
+    /// The repulsive exponent is determined from the acentric factor of the
+    /// Mie fluid, which only depends on the exponent, within the range of
+    /// exponents of the default [ValidityDomain]. The energy parameter then
+    /// follows from the critical temperature. The segment diameter follows from
+    /// the critical pressure or, if `liquid_density` is given as pair of a
+    /// temperature and a saturated liquid (molar) density, from the liquid density,
+    /// which is usually described better than the critical pressure.
+    ///
+    /// The estimate is a corresponding-states approximation and intended as
+    /// initial guess for a [PureRegression](crate::PureRegression).
+    pub fn from_critical_constants(
+        critical_temperature: SINumber,
+        critical_pressure: SINumber,
+        acentric_factor: f64,
+        liquid_density: Option<(SINumber, SINumber)>,
+        options: &UVTheoryOptions,
+    ) -> UVTheoryResult<Self> {
+        let tc = critical_temperature.to_reduced(KELVIN)?;
+        let pc = critical_pressure.to_reduced(KB * KELVIN / ANGSTROM.powi(3))?;
+
+        // bisection of the acentric factor, which increases with the repulsive exponent
+        let [mut rep_min, mut rep_max] = ValidityDomain::default().rep;
+        let mut lower = ReducedCriticalPoint::new(rep_min, options)?;
+        let mut upper = ReducedCriticalPoint::new(rep_max, options)?;
+        if !(lower.acentric_factor..=upper.acentric_factor).contains(&acentric_factor) {
+            return Err(UVTheoryError::invalid_parameter(
+                "acentric_factor",
+                acentric_factor,
+                &format!(
+                    "must be between {:.4} and {:.4} for Mie n-6 fluids with {} <= n <= {}",
+                    lower.acentric_factor, upper.acentric_factor, rep_min, rep_max
+                ),
+            ));
+        }
+        let mut iter = 0;
+        while rep_max - rep_min > TOL_REP {
+            iter += 1;
+            if iter > MAX_ITER_REP {
+                return Err(EosError::NotConverged("from_critical_constants".into()).into());
+            }
+            let rep = 0.5 * (rep_min + rep_max);
+            let mid = ReducedCriticalPoint::new(rep, options)?;
+            if mid.acentric_factor < acentric_factor {
+                rep_min = rep;
+                lower = mid;
+            } else {
+                rep_max = rep;
+                upper = mid;
+            }
+        }
+        let (rep, reduced) =
+            if acentric_factor - lower.acentric_factor < upper.acentric_factor - acentric_factor {
+                (rep_min, lower)
+            } else {
+                (rep_max, upper)
+            };
+
+        let epsilon_k = tc / reduced.temperature;
+        let sigma = match liquid_density {
+            Some((temperature, density)) => {
+                let t = temperature.to_reduced(KELVIN)? / epsilon_k;
+                let vle = PhaseEquilibrium::pure(
+                    &reduced.eos,
+                    t * KELVIN,
+                    None,
+                    SolverOptions::default(),
+                )?;
+                let rho_reduced = (vle.liquid().density * NAV * ANGSTROM.powi(3)).into_value()?;
+                let rho = (density * NAV * ANGSTROM.powi(3)).into_value()?;
+                (rho_reduced / rho).cbrt()
+            }
+            None => (reduced.pressure * epsilon_k / pc).cbrt(),
+        };
+        let record = Self::new(rep, 6.0, sigma, epsilon_k);
+        record.check()?;
+        Ok(record)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn critical_constants_round_trip() {
+        let options = UVTheoryOptions::default();
+        // critical constants of uv-theory with rep = 14, sigma = 3.5 A, epsilon_k = 150 K
+        let reduced = ReducedCriticalPoint::new(14.0, &options).unwrap();
+        let tc = reduced.temperature * 150.0 * KELVIN;
+        let pc = reduced.pressure * 150.0 / 3.5f64.powi(3) * KB * KELVIN / ANGSTROM.powi(3);
+        let vle =
+            PhaseEquilibrium::pure(&reduced.eos, 0.8 * KELVIN, None, Default::default()).unwrap();
+        let density = vle.liquid().density / 3.5f64.powi(3);
+
+        let record =
+            UVRecord::from_critical_constants(tc, pc, reduced.acentric_factor, None, &options)
+                .unwrap();
+        assert_relative_eq!(record.rep, 14.0, max_relative = 1e-6);
+        assert_relative_eq!(record.att, 6.0);
+        assert_relative_eq!(record.sigma, 3.5, max_relative = 1e-6);
+        assert_relative_eq!(record.epsilon_k, 150.0, max_relative = 1e-6);
+
+        let record = UVRecord::from_critical_constants(
+            tc,
+            pc,
+            reduced.acentric_factor,
+            Some((120.0 * KELVIN, density)),
+            &options,
+        )
+        .unwrap();
+        assert_relative_eq!(record.sigma, 3.5, max_relative = 1e-6);
+
+        assert!(matches!(
+            UVRecord::from_critical_constants(tc, pc, 0.5, None, &options),
+            Err(UVTheoryError::InvalidParameter {
+                field: "acentric_factor",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,209 @@
+//! Regression of model parameters to experimental data.
+use crate::errors::{UVTheoryError, UVTheoryResult};
+use feos_core::{log_iter, log_result, EosError, SolverOptions, Verbosity};
+use ndarray::{Array1, Array2};
+
+mod binary;
+mod estimate;
+mod pure;
+pub use binary::{
+    BinaryData, BinaryDeviations, BinaryParameter, BinaryRegression, BinaryRegressionResult,
+};
+pub use pure::{PureData, PureParameter, PureRegression, PureRegressionResult};
+
+const MAX_ITER_REGRESSION: usize = 100;
+const TOL_REGRESSION: f64 = 1e-8;
+const LAMBDA_0: f64 = 1e-3;
+const MAX_LAMBDA: f64 = 1e10;
+
+/// Converged result of the Levenberg-Marquardt algorithm.
+pub(crate) struct LeastSquares {
+    pub x: Array1<f64>,
+    pub residuals: Array1<f64>,
+    pub jacobian: Array2<f64>,
+    pub iterations: usize,
+}
+
+impl LeastSquares {
+    /// Covariance matrix of the parameters, `s^2 (J^T J)^-1` with the
+    /// residual variance `s^2 = |r|^2 / (n - p)`.
+    pub fn covariance(&self) -> Array2<f64> {
+        let (n, p) = self.jacobian.dim();
+        let s2 = self.residuals.dot(&self.residuals) / (n.saturating_sub(p).max(1)) as f64;
+        let jtj = self.jacobian.t().dot(&self.jacobian);
+        inverse(&jtj).map_or_else(|| Array2::from_elem((p, p), f64::NAN), |inv| inv * s2)
+    }
+}
+
+/// Minimize the sum of squared residuals with the Levenberg-Marquardt algorithm.
+///
+/// `residuals` and `jacobian` may fail for parameters that are not valid for the
+/// model, in which case the step is rejected and the damping is increased.
+pub(crate) fn levenberg_marquardt<R, J>(
+    x0: Array1<f64>,
+    residuals: R,
+    jacobian: J,
+    options: SolverOptions,
+) -> UVTheoryResult<LeastSquares>
+where
+    R: Fn(&Array1<f64>) -> UVTheoryResult<Array1<f64>>,
+    J: Fn(&Array1<f64>) -> UVTheoryResult<Array2<f64>>,
+{
+    let (max_iter, tol, verbosity) = options.unwrap_or(MAX_ITER_REGRESSION, TOL_REGRESSION);
+    let mut x = x0;
+    let mut r = residuals(&x)?;
+    let mut cost = r.dot(&r);
+    let mut lambda = LAMBDA_0;
+
+    log_iter!(verbosity, " iter |    residual    |     lambda     ");
+    log_iter!(verbosity, "{:-<39}", "");
+    log_iter!(
+        verbosity,
+        " {:4} | {:14.8e} | {:14.8e}",
+        0,
+        cost.sqrt(),
+        lambda
+    );
+    for iter in 1..=max_iter {
+        let jac = jacobian(&x)?;
+        let jtj = jac.t().dot(&jac);
+        let gradient = jac.t().dot(&r);
+
+        // increase the damping until the step reduces the residuals
+        let mut step = None;
+        while lambda < MAX_LAMBDA {
+            let mut a = jtj.clone();
+            for i in 0..a.nrows() {
+                a[(i, i)] += lambda * jtj[(i, i)].max(f64::EPSILON);
+            }
+            if let Some(dx) = solve(&a, &-&gradient) {
+                let x_new = &x + &dx;
+                if let Ok(r_new) = residuals(&x_new) {
+                    let cost_new = r_new.dot(&r_new);
+                    if cost_new.is_finite() && cost_new <= cost {
+                        step = Some((dx, x_new, r_new, cost_new));
+                        break;
+                    }
+                }
+            }
+            lambda *= 10.0;
+        }
+        let (dx, x_new, r_new, cost_new) = match step {
+            Some(step) => step,
+            None => {
+                // no further reduction of the residuals is possible
+                log_result!(
+                    verbosity,
+                    "Levenberg-Marquardt: converged in {} step(s)\n",
+                    iter - 1
+                );
+                return Ok(LeastSquares {
+                    x,
+                    residuals: r,
+                    jacobian: jac,
+                    iterations: iter - 1,
+                });
+            }
+        };
+        lambda = (lambda / 10.0).max(f64::EPSILON);
+        let converged = dx
+            .iter()
+            .zip(x.iter())
+            .all(|(d, x)| d.abs() <= tol * (x.abs() + tol))
+            || cost - cost_new <= tol * cost;
+        x = x_new;
+        r = r_new;
+        cost = cost_new;
+        log_iter!(
+            verbosity,
+            " {:4} | {:14.8e} | {:14.8e}",
+            iter,
+            cost.sqrt(),
+            lambda
+        );
+
+        if converged {
+            log_result!(
+                verbosity,
+                "Levenberg-Marquardt: converged in {} step(s)\n",
+                iter
+            );
+            return Ok(LeastSquares {
+                jacobian: jacobian(&x)?,
+                x,
+                residuals: r,
+                iterations: iter,
+            });
+        }
+    }
+    Err(UVTheoryError::EosError(EosError::NotConverged(
+        "levenberg_marquardt".into(),
+    )))
+}
+
+/// Solve the linear system `a x = b` by Gaussian elimination with partial pivoting.
+fn solve(a: &Array2<f64>, b: &Array1<f64>) -> Option<Array1<f64>> {
+    let n = b.len();
+    let mut a = a.clone();
+    let mut b = b.clone();
+    for k in 0..n {
+        let pivot = (k..n).max_by(|&i, &j| a[(i, k)].abs().total_cmp(&a[(j, k)].abs()))?;
+        if a[(pivot, k)].abs() < f64::MIN_POSITIVE || !a[(pivot, k)].is_finite() {
+            return None;
+        }
+        for j in 0..n {
+            a.swap((k, j), (pivot, j));
+        }
+        b.swap(k, pivot);
+        for i in k + 1..n {
+            let f = a[(i, k)] / a[(k, k)];
+            for j in k..n {
+                a[(i, j)] -= f * a[(k, j)];
+            }
+            b[i] -= f * b[k];
+        }
+    }
+    let mut x = Array1::zeros(n);
+    for k in (0..n).rev() {
+        let sum: f64 = (k + 1..n).map(|j| a[(k, j)] * x[j]).sum();
+        x[k] = (b[k] - sum) / a[(k, k)];
+    }
+    Some(x)
+}
+
+/// Inverse of a (small) square matrix.
+fn inverse(a: &Array2<f64>) -> Option<Array2<f64>> {
+    let n = a.nrows();
+    let mut inv = Array2::zeros((n, n));
+    for j in 0..n {
+        let mut e = Array1::zeros(n);
+        e[j] = 1.0;
+        inv.column_mut(j).assign(&solve(a, &e)?);
+    }
+    Some(inv)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_relative_eq;
+    use ndarray::arr1;
+
+    #[test]
+    fn rosenbrock() {
+        // Rosenbrock function as least squares problem
+        let residuals = |x: &Array1<f64>| Ok(arr1(&[10.0 * (x[1] - x[0] * x[0]), 1.0 - x[0]]));
+        let jacobian = |x: &Array1<f64>| {
+            Ok(Array2::from_shape_vec((2, 2), vec![-20.0 * x[0], 10.0, -1.0, 0.0]).unwrap())
+        };
+        let result = levenberg_marquardt(
+            arr1(&[-1.2, 1.0]),
+            residuals,
+            jacobian,
+            SolverOptions::default(),
+        )
+        .unwrap();
+        assert_relative_eq!(result.x[0], 1.0, max_relative = 1e-6);
+        assert_relative_eq!(result.x[1], 1.0, max_relative = 1e-6);
+    }
+}
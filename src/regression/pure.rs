@@ -0,0 +1,624 @@
+use super::levenberg_marquardt;
+use crate::errors::{UVTheoryError, UVTheoryResult};
+use crate::{UVParameters, UVRecord, UVTheory, UVTheoryOptions};
+use feos_core::joback::JobackRecord;
+use feos_core::parameter::PureRecord;
+use feos_core::{
+    Contributions, DensityInitialization, EosResult, PhaseEquilibrium, SolverOptions, State,
+};
+use ndarray::{arr1, Array1, Array2};
+use quantity::si::*;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Relative step size of the finite differences of the parameters.
+const STEP: f64 = 1e-6;
+
+/// Parameter of a [UVRecord] that is adjusted in a [PureRegression].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PureParameter {
+    M,
+    Sigma,
+    EpsilonK,
+    Rep,
+}
+
+impl PureParameter {
+    fn get(&self, record: &UVRecord) -> f64 {
+        match self {
+            Self::M => record.m,
+            Self::Sigma => record.sigma,
+            Self::EpsilonK => record.epsilon_k,
+            Self::Rep => record.rep,
+        }
+    }
+
+    fn set(&self, record: &mut UVRecord, value: f64) {
+        match self {
+            Self::M => record.m = value,
+            Self::Sigma => record.sigma = value,
+            Self::EpsilonK => record.epsilon_k = value,
+            Self::Rep => record.rep = value,
+        }
+    }
+}
+
+impl fmt::Display for PureParameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::M => write!(f, "m"),
+            Self::Sigma => write!(f, "sigma"),
+            Self::EpsilonK => write!(f, "epsilon_k"),
+            Self::Rep => write!(f, "rep"),
+        }
+    }
+}
+
+/// Experimental data of a pure component.
+#[derive(Clone, Debug)]
+pub enum PureData {
+    VaporPressure {
+        temperature: SIArray1,
+        vapor_pressure: SIArray1,
+    },
+    /// Saturated liquid densities, either molar densities or mass densities.
+    LiquidDensity {
+        temperature: SIArray1,
+        liquid_density: SIArray1,
+    },
+    /// Molar enthalpies of vaporization.
+    EnthalpyOfVaporization {
+        temperature: SIArray1,
+        enthalpy_of_vaporization: SIArray1,
+    },
+    /// Speeds of sound of the stable phase at the given temperature and pressure.
+    SpeedOfSound {
+        temperature: SIArray1,
+        pressure: SIArray1,
+        speed_of_sound: SIArray1,
+    },
+}
+
+impl PureData {
+    fn temperature(&self) -> &SIArray1 {
+        match self {
+            Self::VaporPressure { temperature, .. }
+            | Self::LiquidDensity { temperature, .. }
+            | Self::EnthalpyOfVaporization { temperature, .. }
+            | Self::SpeedOfSound { temperature, .. } => temperature,
+        }
+    }
+
+    fn target(&self) -> &SIArray1 {
+        match self {
+            Self::VaporPressure { vapor_pressure, .. } => vapor_pressure,
+            Self::LiquidDensity { liquid_density, .. } => liquid_density,
+            Self::EnthalpyOfVaporization {
+                enthalpy_of_vaporization,
+                ..
+            } => enthalpy_of_vaporization,
+            Self::SpeedOfSound { speed_of_sound, .. } => speed_of_sound,
+        }
+    }
+
+    /// Number of data points.
+    pub fn len(&self) -> usize {
+        self.target().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn check(&self) -> UVTheoryResult<()> {
+        let invalid =
+            |reason: &str| Err(UVTheoryError::InvalidData(format!("{}: {}", self, reason)));
+        let temperature = self.temperature();
+        if temperature.len() != self.len() {
+            return invalid("the numbers of temperatures and data points differ");
+        }
+        if self.is_empty() {
+            return invalid("no data points");
+        }
+        let target = self.target();
+        let units_ok = temperature.has_unit(&KELVIN)
+            && match self {
+                Self::VaporPressure { .. } => target.has_unit(&PASCAL),
+                Self::LiquidDensity { .. } => {
+                    target.has_unit(&(MOL / METER.powi(3)))
+                        || target.has_unit(&(KILOGRAM / METER.powi(3)))
+                }
+                Self::EnthalpyOfVaporization { .. } => target.has_unit(&(JOULE / MOL)),
+                Self::SpeedOfSound { pressure, .. } => {
+                    if pressure.len() != self.len() {
+                        return invalid("the numbers of pressures and data points differ");
+                    }
+                    pressure.has_unit(&PASCAL) && target.has_unit(&(METER / SECOND))
+                }
+            };
+        if !units_ok {
+            return invalid("wrong units");
+        }
+        Ok(())
+    }
+
+    /// Ratio of the calculated and the experimental value of point `i`.
+    fn ratio(&self, eos: &Rc<UVTheory>, i: usize) -> EosResult<f64> {
+        let t = self.temperature().get(i);
+        let target = self.target().get(i);
+        let calculated = match self {
+            Self::SpeedOfSound { pressure, .. } => {
+                let moles = arr1(&[1.0]) * MOL;
+                State::new_npt(eos, t, pressure.get(i), &moles, DensityInitialization::None)?
+                    .speed_of_sound()
+            }
+            _ => {
+                let vle = PhaseEquilibrium::pure(eos, t, None, SolverOptions::default())?;
+                match self {
+                    Self::VaporPressure { .. } => vle.vapor().pressure(Contributions::Total),
+                    Self::LiquidDensity { liquid_density, .. } => {
+                        liquid_density_like(vle.liquid(), liquid_density)
+                    }
+                    _ => {
+                        vle.vapor().molar_enthalpy(Contributions::Total)
+                            - vle.liquid().molar_enthalpy(Contributions::Total)
+                    }
+                }
+            }
+        };
+        Ok((calculated / target).into_value()?)
+    }
+
+    /// Derivatives of the logarithm of the calculated value of point `i`
+    /// with respect to the parameters.
+    ///
+    /// Vapor pressures and liquid densities are differentiated analytically
+    /// using the conditions of phase equilibrium, so that only derivatives of
+    /// the Helmholtz energy and the pressure at constant temperature and density
+    /// have to be evaluated for the perturbed parameters. All other properties
+    /// are differentiated numerically.
+    fn ln_derivatives(
+        &self,
+        eos: &Rc<UVTheory>,
+        perturbed: &[Perturbation],
+        i: usize,
+    ) -> EosResult<Array1<f64>> {
+        let t = self.temperature().get(i);
+        let mut derivatives = Array1::zeros(perturbed.len());
+        match self {
+            Self::VaporPressure { .. } | Self::LiquidDensity { .. } => {
+                let vle = PhaseEquilibrium::pure(eos, t, None, SolverOptions::default())?;
+                let (liquid, vapor) = (vle.liquid(), vle.vapor());
+                let rt = RGAS * t;
+                let p = vapor.pressure(Contributions::Total);
+                // (dp/dtheta)_sat = (da/dtheta^L - da/dtheta^V) / (v^V - v^L)
+                let dv = (1.0 / vapor.density - 1.0 / liquid.density) * p / rt;
+                for (d, perturbation) in derivatives.iter_mut().zip(perturbed) {
+                    let helmholtz_energy = |eos: &Rc<UVTheory>, density| -> EosResult<f64> {
+                        Ok(State::new_pure(eos, t, density)?
+                            .molar_helmholtz_energy(Contributions::ResidualNvt)
+                            .to_reduced(rt)?)
+                    };
+                    let da = perturbation.derivative(eos, |eos| {
+                        Ok(helmholtz_energy(eos, liquid.density)?
+                            - helmholtz_energy(eos, vapor.density)?)
+                    })?;
+                    let dln_p = da / dv.into_value()?;
+                    *d = match self {
+                        Self::VaporPressure { .. } => dln_p,
+                        _ => {
+                            // (drho/dtheta)_sat = ((dp/dtheta)_sat - (dp/dtheta)_T,rho) / (dp/drho)_T,theta
+                            let dp = perturbation.derivative(eos, |eos| {
+                                Ok(State::new_pure(eos, t, liquid.density)?
+                                    .pressure(Contributions::Total)
+                                    .to_reduced(p)?)
+                            })?;
+                            let dp_dlnrho = (liquid.dp_drho(Contributions::Total) * liquid.density
+                                / p)
+                                .into_value()?;
+                            (dln_p - dp) / dp_dlnrho
+                        }
+                    };
+                }
+            }
+            _ => {
+                for (d, perturbation) in derivatives.iter_mut().zip(perturbed) {
+                    *d = perturbation.derivative(eos, |eos| Ok(self.ratio(eos, i)?.ln()))?;
+                }
+            }
+        }
+        Ok(derivatives)
+    }
+}
+
+/// Calculated density in the same kind of units as `data`.
+fn liquid_density_like(state: &State<SIUnit, UVTheory>, data: &SIArray1) -> SINumber {
+    if data.has_unit(&(KILOGRAM / METER.powi(3))) {
+        state.mass_density()
+    } else {
+        state.density
+    }
+}
+
+impl fmt::Display for PureData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::VaporPressure { .. } => write!(f, "vapor pressure"),
+            Self::LiquidDensity { .. } => write!(f, "liquid density"),
+            Self::EnthalpyOfVaporization { .. } => write!(f, "enthalpy of vaporization"),
+            Self::SpeedOfSound { .. } => write!(f, "speed of sound"),
+        }
+    }
+}
+
+/// Equations of state with one parameter perturbed up and down.
+struct Perturbation {
+    plus: Rc<UVTheory>,
+    /// `None`, if the parameter cannot be decreased, e.g., for `m = 1`.
+    minus: Option<Rc<UVTheory>>,
+    step: f64,
+}
+
+impl Perturbation {
+    /// Central (or, if `minus` is `None`, forward) difference of `f`
+    /// around the unperturbed equation of state `eos`.
+    fn derivative<F: Fn(&Rc<UVTheory>) -> EosResult<f64>>(
+        &self,
+        eos: &Rc<UVTheory>,
+        f: F,
+    ) -> EosResult<f64> {
+        Ok(match &self.minus {
+            Some(minus) => (f(&self.plus)? - f(minus)?) / (2.0 * self.step),
+            None => (f(&self.plus)? - f(eos)?) / self.step,
+        })
+    }
+}
+
+/// Adjustment of the parameters of a pure component to experimental data.
+///
+/// The objective function is the weighted sum of the mean squared relative
+/// deviations of every data set. It is minimized with the Levenberg-Marquardt
+/// algorithm, starting from the parameters of the given record.
+///
+/// # Example
+///
+/// ```no_run
+/// # use feos_core::parameter::PureRecord;
+/// # use feos_core::joback::JobackRecord;
+/// # use feos_uvtheory::{PureData, PureParameter, PureRegression, UVRecord, UVTheoryResult};
+/// # use quantity::si::*;
+/// # fn fit(record: PureRecord<UVRecord, JobackRecord>, t: SIArray1, p: SIArray1, rho: SIArray1) -> UVTheoryResult<()> {
+/// let mut regression = PureRegression::new(
+///     record,
+///     vec![PureParameter::Sigma, PureParameter::EpsilonK],
+///     Default::default(),
+/// );
+/// regression.add_data(PureData::VaporPressure { temperature: t.clone(), vapor_pressure: p }, 1.0)?;
+/// regression.add_data(PureData::LiquidDensity { temperature: t, liquid_density: rho }, 1.0)?;
+/// let result = regression.solve(Default::default())?;
+/// println!("{}", result);
+/// # Ok(())
+/// # }
+/// ```
+pub struct PureRegression {
+    pure_record: PureRecord<UVRecord, JobackRecord>,
+    parameters: Vec<PureParameter>,
+    options: UVTheoryOptions,
+    data: Vec<(PureData, f64)>,
+}
+
+impl PureRegression {
+    /// Adjust `parameters` of `pure_record`, all other parameters are kept fixed.
+    pub fn new(
+        pure_record: PureRecord<UVRecord, JobackRecord>,
+        parameters: Vec<PureParameter>,
+        options: UVTheoryOptions,
+    ) -> Self {
+        Self {
+            pure_record,
+            parameters,
+            options,
+            data: Vec::new(),
+        }
+    }
+
+    /// Add a data set with the given weight in the objective function.
+    pub fn add_data(&mut self, data: PureData, weight: f64) -> UVTheoryResult<()> {
+        data.check()?;
+        if !(weight.is_finite() && weight > 0.0) {
+            return Err(UVTheoryError::InvalidData(format!(
+                "{}: weight must be positive",
+                data
+            )));
+        }
+        self.data.push((data, weight));
+        Ok(())
+    }
+
+    fn values(&self) -> Array1<f64> {
+        self.parameters
+            .iter()
+            .map(|p| p.get(&self.pure_record.model_record))
+            .collect()
+    }
+
+    fn pure_record(&self, values: &Array1<f64>) -> PureRecord<UVRecord, JobackRecord> {
+        let mut pure_record = self.pure_record.clone();
+        for (p, &value) in self.parameters.iter().zip(values) {
+            p.set(&mut pure_record.model_record, value);
+        }
+        pure_record
+    }
+
+    fn eos(&self, values: &Array1<f64>) -> UVTheoryResult<Rc<UVTheory>> {
+        let parameters = UVParameters::try_new_pure(self.pure_record(values))?;
+        Ok(Rc::new(UVTheory::with_options(
+            Arc::new(parameters),
+            self.options.clone(),
+        )?))
+    }
+
+    fn perturbations(&self, values: &Array1<f64>) -> UVTheoryResult<Vec<Perturbation>> {
+        (0..values.len())
+            .map(|j| {
+                let step = STEP * values[j].abs().max(1.0);
+                let mut perturbed = values.clone();
+                perturbed[j] += step;
+                let plus = self.eos(&perturbed)?;
+                perturbed[j] -= 2.0 * step;
+                let minus = self.eos(&perturbed).ok();
+                Ok(Perturbation { plus, minus, step })
+            })
+            .collect()
+    }
+
+    /// Relative deviations of every data set, `NaN` for points at which
+    /// the property cannot be calculated.
+    fn relative_deviations(&self, eos: &Rc<UVTheory>) -> Vec<Array1<f64>> {
+        self.data
+            .iter()
+            .map(|(data, _)| {
+                (0..data.len())
+                    .map(|i| data.ratio(eos, i).map_or(f64::NAN, |r| r - 1.0))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn scale(&self) -> impl Iterator<Item = f64> + '_ {
+        self.data
+            .iter()
+            .map(|(data, weight)| (weight / data.len() as f64).sqrt())
+    }
+
+    fn residuals(&self, values: &Array1<f64>) -> UVTheoryResult<Array1<f64>> {
+        let eos = self.eos(values)?;
+        let deviations = self.relative_deviations(&eos);
+        // points that cannot be calculated are penalized with a deviation of 100%
+        Ok(deviations
+            .iter()
+            .zip(self.scale())
+            .flat_map(|(d, s)| d.iter().map(move |&d| s * if d.is_nan() { 1.0 } else { d }))
+            .collect())
+    }
+
+    fn jacobian(&self, values: &Array1<f64>) -> UVTheoryResult<Array2<f64>> {
+        let eos = self.eos(values)?;
+        let perturbed = self.perturbations(values)?;
+        let n = self.data.iter().map(|(d, _)| d.len()).sum();
+        let mut jacobian = Array2::zeros((n, values.len()));
+        let mut rows = jacobian.rows_mut().into_iter();
+        for ((data, _), scale) in self.data.iter().zip(self.scale()) {
+            for i in 0..data.len() {
+                let mut row = rows.next().unwrap();
+                // the penalty of points that cannot be calculated is constant
+                if let (Ok(ratio), Ok(derivatives)) = (
+                    data.ratio(&eos, i),
+                    data.ln_derivatives(&eos, &perturbed, i),
+                ) {
+                    row.assign(&(derivatives * ratio * scale));
+                }
+            }
+        }
+        Ok(jacobian)
+    }
+
+    /// Adjust the parameters.
+    pub fn solve(&self, options: SolverOptions) -> UVTheoryResult<PureRegressionResult> {
+        if self.data.is_empty() {
+            return Err(UVTheoryError::InvalidData("no data sets".into()));
+        }
+        let result = levenberg_marquardt(
+            self.values(),
+            |x| self.residuals(x),
+            |x| self.jacobian(x),
+            options,
+        )?;
+        let eos = self.eos(&result.x)?;
+        Ok(PureRegressionResult {
+            pure_record: self.pure_record(&result.x),
+            parameters: self.parameters.clone(),
+            covariance: result.covariance(),
+            relative_deviations: self.relative_deviations(&eos),
+            data: self.data.iter().map(|(d, _)| d.to_string()).collect(),
+            iterations: result.iterations,
+        })
+    }
+}
+
+/// Result of a [PureRegression].
+#[derive(Clone, Debug)]
+pub struct PureRegressionResult {
+    /// Pure record with the adjusted parameters.
+    pub pure_record: PureRecord<UVRecord, JobackRecord>,
+    /// Adjusted parameters in the order of the covariance matrix.
+    pub parameters: Vec<PureParameter>,
+    /// Covariance matrix of the adjusted parameters.
+    pub covariance: Array2<f64>,
+    /// Relative deviations `(calc - exp) / exp` of every data set, `NaN` for
+    /// points at which the property cannot be calculated.
+    pub relative_deviations: Vec<Array1<f64>>,
+    data: Vec<String>,
+    pub iterations: usize,
+}
+
+impl PureRegressionResult {
+    /// The record with the adjusted parameters.
+    pub fn record(&self) -> &UVRecord {
+        &self.pure_record.model_record
+    }
+
+    /// Standard deviations of the adjusted parameters.
+    pub fn standard_deviations(&self) -> Array1<f64> {
+        self.covariance.diag().mapv(f64::sqrt)
+    }
+
+    /// Average absolute relative deviation of every data set, ignoring
+    /// points that cannot be calculated.
+    pub fn aad(&self) -> Array1<f64> {
+        self.relative_deviations
+            .iter()
+            .map(|d| {
+                let valid: Vec<f64> = d.iter().filter(|d| !d.is_nan()).map(|d| d.abs()).collect();
+                valid.iter().sum::<f64>() / valid.len() as f64
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for PureRegressionResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "|parameter|value|standard deviation|")?;
+        writeln!(f, "|-|-|-|")?;
+        let record = self.record();
+        for (p, s) in self.parameters.iter().zip(self.standard_deviations()) {
+            writeln!(f, "|{}|{}|{}|", p, p.get(record), s)?;
+        }
+        writeln!(f, "\n|data|points|AAD / %|failed|")?;
+        write!(f, "|-|-|-|-|")?;
+        for ((data, deviations), aad) in self
+            .data
+            .iter()
+            .zip(&self.relative_deviations)
+            .zip(self.aad())
+        {
+            write!(
+                f,
+                "\n|{}|{}|{}|{}|",
+                data,
+                deviations.len(),
+                100.0 * aad,
+                deviations.iter().filter(|d| d.is_nan()).count()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::assert_relative_eq;
+    use feos_core::parameter::{Identifier, Parameter};
+
+    fn argon(sigma: f64, epsilon_k: f64) -> PureRecord<UVRecord, JobackRecord> {
+        let record = UVRecord::new(12.0, 6.0, sigma, epsilon_k);
+        PureRecord::new(Identifier::default(), 39.948, record, None)
+    }
+
+    fn regression() -> PureRegression {
+        // synthetic data of the reference parameters
+        let eos = Rc::new(UVTheory::new(Arc::new(UVParameters::new_pure(argon(
+            3.4, 120.0,
+        )))));
+        let temperature = arr1(&[90.0, 100.0, 110.0, 120.0, 130.0]) * KELVIN;
+        let vle: Vec<_> = temperature
+            .into_iter()
+            .map(|t| PhaseEquilibrium::pure(&eos, t, None, Default::default()).unwrap())
+            .collect();
+        let vapor_pressure =
+            SIArray1::from_shape_fn(5, |i| vle[i].vapor().pressure(Contributions::Total));
+        let liquid_density = SIArray1::from_shape_fn(5, |i| vle[i].liquid().mass_density());
+        let enthalpy_of_vaporization = SIArray1::from_shape_fn(5, |i| {
+            vle[i].vapor().molar_enthalpy(Contributions::Total)
+                - vle[i].liquid().molar_enthalpy(Contributions::Total)
+        });
+
+        let mut regression = PureRegression::new(
+            argon(3.3, 110.0),
+            vec![PureParameter::Sigma, PureParameter::EpsilonK],
+            Default::default(),
+        );
+        regression
+            .add_data(
+                PureData::VaporPressure {
+                    temperature: temperature.clone(),
+                    vapor_pressure,
+                },
+                1.0,
+            )
+            .unwrap();
+        regression
+            .add_data(
+                PureData::LiquidDensity {
+                    temperature: temperature.clone(),
+                    liquid_density,
+                },
+                1.0,
+            )
+            .unwrap();
+        regression
+            .add_data(
+                PureData::EnthalpyOfVaporization {
+                    temperature,
+                    enthalpy_of_vaporization,
+                },
+                0.5,
+            )
+            .unwrap();
+        regression
+    }
+
+    #[test]
+    fn analytic_derivatives() {
+        let regression = regression();
+        let x = regression.values();
+        let jacobian = regression.jacobian(&x).unwrap();
+        for j in 0..x.len() {
+            let h = 1e-5 * x[j];
+            let mut xp = x.clone();
+            xp[j] += h;
+            let mut xm = x.clone();
+            xm[j] -= h;
+            let fd = (regression.residuals(&xp).unwrap() - regression.residuals(&xm).unwrap())
+                / (2.0 * h);
+            for (&analytic, &numeric) in jacobian.column(j).iter().zip(fd.iter()) {
+                assert_relative_eq!(analytic, numeric, max_relative = 1e-4, epsilon = 1e-8);
+            }
+        }
+    }
+
+    #[test]
+    fn fit_argon() {
+        let regression = regression();
+        let result = regression.solve(Default::default()).unwrap();
+        assert_relative_eq!(result.record().sigma, 3.4, max_relative = 1e-6);
+        assert_relative_eq!(result.record().epsilon_k, 120.0, max_relative = 1e-6);
+        for aad in result.aad() {
+            assert!(aad < 1e-6);
+        }
+        assert_eq!(result.covariance.dim(), (2, 2));
+        assert!(result.standard_deviations().iter().all(|s| s.is_finite()));
+
+        let invalid = PureData::VaporPressure {
+            temperature: arr1(&[100.0]) * KELVIN,
+            vapor_pressure: arr1(&[1.0]) * KELVIN,
+        };
+        let mut regression = PureRegression::new(argon(3.4, 120.0), vec![], Default::default());
+        assert!(matches!(
+            regression.add_data(invalid, 1.0),
+            Err(UVTheoryError::InvalidData(_))
+        ));
+    }
+}
@@ -0,0 +1,385 @@
+use super::UVTheorySolid;
+use crate::eos::hard_sphere_wca::diameter_wca;
+use crate::UVTheory;
+use feos_core::{
+    Contributions, EosError, EosResult, EquationOfState, PhaseEquilibrium, SolverOptions, State,
+};
+use quantity::si::*;
+use std::f64::consts::FRAC_PI_6;
+use std::rc::Rc;
+
+const MAX_ITER_SOLID: usize = 100;
+const TOL_SOLID: f64 = 1e-10;
+/// Maximum change of the logarithm of densities and pressures in a single Newton step.
+const MAX_STEP: f64 = 0.1;
+/// Maximum change of the pressure (in units of the density times RT) in a single Newton step.
+const MAX_STEP_PRESSURE: f64 = 1.0;
+
+/// Initial packing fractions (with the WCA diameter) of the coexisting
+/// solid and liquid phases.
+const ETA_SOLID: f64 = 0.55;
+const ETA_LIQUID: f64 = 0.49;
+
+/// Coexisting solid and fluid phases of a pure component.
+#[derive(Clone)]
+pub struct SolidFluidEquilibrium {
+    pub solid: State<SIUnit, UVTheorySolid>,
+    pub fluid: State<SIUnit, UVTheory>,
+}
+
+/// Triple point of a pure component.
+#[derive(Clone)]
+pub struct TriplePoint {
+    pub solid: State<SIUnit, UVTheorySolid>,
+    pub liquid: State<SIUnit, UVTheory>,
+    pub vapor: State<SIUnit, UVTheory>,
+}
+
+impl SolidFluidEquilibrium {
+    /// Solid-liquid equilibrium (melting line) at the given temperature.
+    pub fn melting(
+        solid: &Rc<UVTheorySolid>,
+        fluid: &Rc<UVTheory>,
+        temperature: SINumber,
+        initial_state: Option<&Self>,
+        options: SolverOptions,
+    ) -> EosResult<Self> {
+        let (rho_solid, rho_fluid) = match initial_state {
+            Some(init) => (init.solid.density, init.fluid.density),
+            None => {
+                let d = diameter_wca(&solid.parameters, temperature.to_reduced(KELVIN)?)[0];
+                let rho = |eta: f64| eta / (FRAC_PI_6 * d.powi(3)) / (ANGSTROM.powi(3) * NAV);
+                (rho(ETA_SOLID), rho(ETA_LIQUID))
+            }
+        };
+        Self::iterate(
+            solid,
+            fluid,
+            temperature,
+            rho_solid,
+            rho_fluid,
+            false,
+            options,
+        )
+        .map_err(|_| EosError::NotConverged("SolidFluidEquilibrium::melting".into()))
+    }
+
+    /// Solid-vapor equilibrium (sublimation line) at the given temperature.
+    pub fn sublimation(
+        solid: &Rc<UVTheorySolid>,
+        fluid: &Rc<UVTheory>,
+        temperature: SINumber,
+        initial_state: Option<&Self>,
+        options: SolverOptions,
+    ) -> EosResult<Self> {
+        let (rho_solid, rho_fluid) = match initial_state {
+            Some(init) => (init.solid.density, init.fluid.density),
+            None => {
+                // solid at vanishing pressure in equilibrium with an ideal gas
+                let d = diameter_wca(&solid.parameters, temperature.to_reduced(KELVIN)?)[0];
+                let mut rho = ETA_SOLID / (FRAC_PI_6 * d.powi(3)) / (ANGSTROM.powi(3) * NAV);
+                for _ in 0..MAX_ITER_SOLID {
+                    let state = State::new_pure(solid, temperature, rho)?;
+                    let (p, dp_drho) = (
+                        state.pressure(Contributions::Total),
+                        state.dp_drho(Contributions::Total),
+                    );
+                    rho -= p / dp_drho;
+                    if p.to_reduced(RGAS * temperature * rho)?.abs() < TOL_SOLID {
+                        break;
+                    }
+                }
+                let state = State::new_pure(solid, temperature, rho)?;
+                let mu_res = state
+                    .chemical_potential(Contributions::ResidualNvt)
+                    .get(0)
+                    .to_reduced(RGAS * temperature)?;
+                (rho, rho * mu_res.exp())
+            }
+        };
+        Self::iterate(
+            solid,
+            fluid,
+            temperature,
+            rho_solid,
+            rho_fluid,
+            true,
+            options,
+        )
+        .map_err(|_| EosError::NotConverged("SolidFluidEquilibrium::sublimation".into()))
+    }
+
+    /// Newton iteration for the pressure at which the chemical potentials
+    /// of both phases are equal.
+    ///
+    /// For sublimation, the logarithm of the pressure is iterated.
+    fn iterate(
+        solid: &Rc<UVTheorySolid>,
+        fluid: &Rc<UVTheory>,
+        temperature: SINumber,
+        rho_solid: SINumber,
+        rho_fluid: SINumber,
+        log_pressure: bool,
+        options: SolverOptions,
+    ) -> EosResult<Self> {
+        let (max_iter, tol, _) = options.unwrap_or(MAX_ITER_SOLID, TOL_SOLID);
+        let rt = RGAS * temperature;
+        let mut s = State::new_pure(solid, temperature, rho_solid)?;
+        let mut f = State::new_pure(fluid, temperature, rho_fluid)?;
+        let mut p = f.pressure(Contributions::Total);
+        for _ in 0..max_iter {
+            s = density_at_pressure(s, p, tol)?;
+            f = density_at_pressure(f, p, tol)?;
+            let delta_mu = (s.chemical_potential(Contributions::Total).get(0)
+                - f.chemical_potential(Contributions::Total).get(0))
+            .to_reduced(rt)?;
+            if delta_mu.abs() < tol {
+                return Ok(Self { solid: s, fluid: f });
+            }
+            let delta_v = 1.0 / s.density - 1.0 / f.density;
+            if log_pressure {
+                let step = -delta_mu / delta_v.to_reduced(rt / p)?;
+                p = p * step.clamp(-MAX_STEP, MAX_STEP).exp();
+            } else {
+                let step = -delta_mu / (delta_v * f.density).into_value()?;
+                p += step.clamp(-MAX_STEP_PRESSURE, MAX_STEP_PRESSURE) * rt * f.density;
+            }
+            if !p.to_reduced(PASCAL)?.is_finite() {
+                return Err(EosError::IterationFailed(
+                    "SolidFluidEquilibrium::iterate".into(),
+                ));
+            }
+        }
+        Err(EosError::NotConverged(
+            "SolidFluidEquilibrium::iterate".into(),
+        ))
+    }
+}
+
+/// State at the given pressure obtained by a Newton iteration in the
+/// logarithm of the density starting from the density of `state`.
+fn density_at_pressure<E: EquationOfState>(
+    mut state: State<SIUnit, E>,
+    pressure: SINumber,
+    tol: f64,
+) -> EosResult<State<SIUnit, E>> {
+    for _ in 0..MAX_ITER_SOLID {
+        let p = state.pressure(Contributions::Total);
+        let dp_dln_rho = state.dp_drho(Contributions::Total) * state.density;
+        let step = -(p - pressure).to_reduced(dp_dln_rho)?;
+        if !step.is_finite() || dp_dln_rho.to_reduced(PASCAL)? < 0.0 {
+            return Err(EosError::IterationFailed("density_at_pressure".into()));
+        }
+        let density = state.density * step.clamp(-MAX_STEP, MAX_STEP).exp();
+        state = State::new_pure(&state.eos, state.temperature, density)?;
+        if step.abs() < tol {
+            return Ok(state);
+        }
+    }
+    Err(EosError::NotConverged("density_at_pressure".into()))
+}
+
+impl TriplePoint {
+    /// Triple point at which the melting line meets the vapor pressure curve.
+    ///
+    /// The temperature is iterated with the secant method starting from
+    /// `initial_temperature`.
+    pub fn new(
+        solid: &Rc<UVTheorySolid>,
+        fluid: &Rc<UVTheory>,
+        initial_temperature: SINumber,
+        options: SolverOptions,
+    ) -> EosResult<Self> {
+        let (max_iter, tol, _) = options.unwrap_or(MAX_ITER_SOLID, TOL_SOLID);
+        let mut sle: Option<SolidFluidEquilibrium> = None;
+        let mut vle: Option<PhaseEquilibrium<SIUnit, UVTheory, 2>> = None;
+
+        // difference between melting pressure and vapor pressure
+        let mut residual = |t: SINumber| -> EosResult<(f64, SolidFluidEquilibrium, _)> {
+            let s = SolidFluidEquilibrium::melting(
+                solid,
+                fluid,
+                t,
+                sle.as_ref(),
+                SolverOptions::default(),
+            )?;
+            let v = PhaseEquilibrium::pure(fluid, t, vle.as_ref(), SolverOptions::default())?;
+            let res = (s.fluid.pressure(Contributions::Total)
+                - v.vapor().pressure(Contributions::Total))
+            .to_reduced(RGAS * t * v.liquid().density)?;
+            sle = Some(s.clone());
+            vle = Some(v.clone());
+            Ok((res, s, v))
+        };
+
+        let mut t_old = initial_temperature;
+        let (mut res_old, _, _) = residual(t_old)?;
+        let mut t = initial_temperature * 1.01;
+        for _ in 0..max_iter {
+            let (res, s, v) = residual(t)?;
+            if res.abs() < tol {
+                return Ok(Self {
+                    solid: s.solid,
+                    liquid: v.liquid().clone(),
+                    vapor: v.vapor().clone(),
+                });
+            }
+            let t_new = t - (t - t_old) * res / (res - res_old);
+            t_old = t;
+            res_old = res;
+            t = t_new;
+        }
+        Err(EosError::NotConverged("TriplePoint::new".into()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::eos::hard_sphere_bh::diameter_bh_i;
+    use crate::parameters::utils::test_parameters;
+    use approx::assert_relative_eq;
+    use std::sync::Arc;
+
+    fn lennard_jones() -> (Rc<UVTheorySolid>, Rc<UVTheory>) {
+        let parameters = Arc::new(test_parameters(12.0, 6.0, 1.0, 1.0));
+        (
+            Rc::new(UVTheorySolid::new(parameters.clone()).unwrap()),
+            Rc::new(UVTheory::new(parameters)),
+        )
+    }
+
+    #[test]
+    fn melting() {
+        let (solid, fluid) = lennard_jones();
+        let sle_1 =
+            SolidFluidEquilibrium::melting(&solid, &fluid, KELVIN, None, Default::default())
+                .unwrap();
+        let sle_2 = SolidFluidEquilibrium::melting(
+            &solid,
+            &fluid,
+            1.5 * KELVIN,
+            Some(&sle_1),
+            Default::default(),
+        )
+        .unwrap();
+        for sle in [&sle_1, &sle_2] {
+            assert!(sle.solid.density > sle.fluid.density);
+            assert_relative_eq!(
+                sle.solid
+                    .pressure(Contributions::Total)
+                    .to_reduced(PASCAL)
+                    .unwrap(),
+                sle.fluid
+                    .pressure(Contributions::Total)
+                    .to_reduced(PASCAL)
+                    .unwrap(),
+                max_relative = 1e-8
+            );
+        }
+        assert!(
+            sle_2.fluid.pressure(Contributions::Total) > sle_1.fluid.pressure(Contributions::Total)
+        );
+
+        // coexisting densities of the Lennard-Jones fluid, which are not used
+        // to adjust the correlation entropy
+        // (Hansen and Verlet, Phys. Rev. 184, 151 (1969))
+        let rho_unit = 1.0 / (ANGSTROM.powi(3) * NAV);
+        for (t, rho_s, rho_l) in [
+            (0.75, 0.973, 0.875),
+            (1.15, 1.024, 0.936),
+            (1.35, 1.053, 0.964),
+            (2.74, 1.179, 1.113),
+        ] {
+            let sle = SolidFluidEquilibrium::melting(
+                &solid,
+                &fluid,
+                t * KELVIN,
+                None,
+                Default::default(),
+            )
+            .unwrap();
+            assert_relative_eq!(
+                sle.solid.density.to_reduced(rho_unit).unwrap(),
+                rho_s,
+                max_relative = 5e-2
+            );
+            assert_relative_eq!(
+                sle.fluid.density.to_reduced(rho_unit).unwrap(),
+                rho_l,
+                max_relative = 5e-2
+            );
+        }
+    }
+
+    #[test]
+    fn melting_steep_repulsion() {
+        // At high temperatures, the Mie 48-6 fluid freezes like hard spheres
+        // with the Barker-Henderson diameter
+        // (Hoover and Ree, J. Chem. Phys. 49, 3609 (1968)).
+        let parameters = Arc::new(test_parameters(48.0, 6.0, 1.0, 1.0));
+        let solid = Rc::new(UVTheorySolid::new(parameters.clone()).unwrap());
+        let fluid = Rc::new(UVTheory::new(parameters.clone()));
+        let t = 10.0;
+        let sle =
+            SolidFluidEquilibrium::melting(&solid, &fluid, t * KELVIN, None, Default::default())
+                .unwrap();
+        let rho_unit = 1.0 / (ANGSTROM.powi(3) * NAV * diameter_bh_i(&parameters, 0, t).powi(3));
+        let rho_s = sle.solid.density.to_reduced(rho_unit).unwrap();
+        let rho_l = sle.fluid.density.to_reduced(rho_unit).unwrap();
+        assert_relative_eq!(rho_s, 1.041, max_relative = 5e-2);
+        assert_relative_eq!(rho_l, 0.943, max_relative = 5e-2);
+        assert_relative_eq!(rho_s / rho_l, 1.041 / 0.943, max_relative = 1e-2);
+    }
+
+    #[test]
+    fn triple_point() {
+        // triple point of the Lennard-Jones fluid
+        // (Mastny and de Pablo, J. Chem. Phys. 127, 104504 (2007));
+        // the correlation entropy is adjusted to the temperature, the
+        // densities are predicted
+        let (solid, fluid) = lennard_jones();
+        let tp = TriplePoint::new(&solid, &fluid, 0.7 * KELVIN, Default::default()).unwrap();
+        let rho_unit = 1.0 / (ANGSTROM.powi(3) * NAV);
+        assert_relative_eq!(
+            tp.solid.temperature.to_reduced(KELVIN).unwrap(),
+            0.694,
+            max_relative = 1e-2
+        );
+        assert_relative_eq!(
+            tp.solid.density.to_reduced(rho_unit).unwrap(),
+            0.961,
+            max_relative = 1e-2
+        );
+        assert_relative_eq!(
+            tp.liquid.density.to_reduced(rho_unit).unwrap(),
+            0.845,
+            max_relative = 1e-2
+        );
+        assert!(tp.solid.density > tp.liquid.density);
+        assert!(tp.liquid.density > tp.vapor.density);
+
+        // below the triple point, the sublimation pressure is lower than the
+        // vapor pressure of the metastable liquid
+        let t = 0.5 * KELVIN;
+        let sve = SolidFluidEquilibrium::sublimation(&solid, &fluid, t, None, Default::default())
+            .unwrap();
+        let vle = PhaseEquilibrium::pure(&fluid, t, None, Default::default()).unwrap();
+        assert_relative_eq!(
+            sve.solid
+                .chemical_potential(Contributions::Total)
+                .get(0)
+                .to_reduced(RGAS * t)
+                .unwrap(),
+            sve.fluid
+                .chemical_potential(Contributions::Total)
+                .get(0)
+                .to_reduced(RGAS * t)
+                .unwrap(),
+            max_relative = 1e-8
+        );
+        assert!(
+            sve.fluid.pressure(Contributions::Total) < vle.vapor().pressure(Contributions::Total)
+        );
+    }
+}
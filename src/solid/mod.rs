@@ -0,0 +1,273 @@
+use crate::errors::{UVTheoryError, UVTheoryResult};
+use crate::parameters::{mean_field_constant, mie_prefactor, UVParameters};
+use crate::virial::integrate;
+use feos_core::joback::Joback;
+use feos_core::parameter::Parameter;
+use feos_core::{
+    EquationOfState, HelmholtzEnergy, HelmholtzEnergyDual, IdealGasContribution, MolarWeight,
+    StateHD,
+};
+use lazy_static::lazy_static;
+use ndarray::Array1;
+use num_dual::DualNum;
+use quantity::si::*;
+use std::collections::BTreeMap;
+use std::f64::consts::{PI, SQRT_2};
+use std::fmt;
+use std::sync::Arc;
+
+mod coexistence;
+pub use coexistence::{SolidFluidEquilibrium, TriplePoint};
+
+/// Squared distances of the lattice shells (in units of the squared nearest
+/// neighbor distance) that are summed explicitly.
+const MAX_SHELL: usize = 36;
+
+/// Radius of the cell (in units of the nearest neighbor distance).
+const CELL_RADIUS: f64 = 0.5;
+
+/// Entropy per particle (in units of k) of the correlated motion of neighboring
+/// particles, which is neglected in the cell model. The value is adjusted to the
+/// triple point of the Lennard-Jones fluid (Mastny and de Pablo,
+/// J. Chem. Phys. 127, 104504 (2007)) and used for all repulsive exponents.
+const CORRELATION_ENTROPY: f64 = 0.3;
+
+lazy_static! {
+    /// Squared distances and coordination numbers of the shells of the FCC lattice.
+    static ref FCC_SHELLS: Vec<(f64, f64)> = {
+        let n = (2.0 * MAX_SHELL as f64).sqrt().ceil() as i32;
+        let mut shells = BTreeMap::new();
+        for i in -n..=n {
+            for j in -n..=n {
+                for k in -n..=n {
+                    let r2 = i * i + j * j + k * k;
+                    if (i + j + k) % 2 == 0 && r2 > 0 && r2 as usize <= 2 * MAX_SHELL {
+                        *shells.entry(r2 as usize / 2).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+        shells
+            .into_iter()
+            .map(|(r2, count)| (r2 as f64, count as f64))
+            .collect()
+    };
+}
+
+/// Helmholtz energy model of the FCC solid of Mie particles.
+///
+/// The solid is described with the cell model of Lennard-Jones and Devonshire:
+/// every particle moves in the potential of its neighbors, which are smeared
+/// over spherical shells around their lattice sites. The model is available for
+/// pure components consisting of a single segment and shares the ideal gas
+/// contribution with `UVTheory`, so that solid-fluid equilibria can be
+/// calculated with `SolidFluidEquilibrium` and `TriplePoint`.
+///
+/// The cell model neglects correlations between the motions of neighboring
+/// particles, which underestimates the entropy and thus the stability of the
+/// solid (triple point of the Lennard-Jones fluid at T* = 0.59 instead of 0.69).
+/// A constant correlation entropy, which does not change the pressure of the
+/// solid, corrects the triple point. At higher temperatures, the melting
+/// pressures remain too large (by about 20% at T* = 1.15), while the
+/// coexisting densities deviate by less than 5% up to T* = 2.74.
+///
+/// For repulsive exponents n != 12, the correlation entropy of the
+/// Lennard-Jones solid is used unchanged. This is an approximation, because
+/// the correlated motion of neighboring particles depends on the steepness of
+/// the repulsion. For steep repulsion (n = 48 at T* = 10), the coexisting
+/// densities agree with those of hard spheres within 5%, if they are reduced
+/// with the Barker-Henderson diameter. Triple points and melting lines of
+/// softer potentials (n < 12) are not validated.
+pub struct UVTheorySolid {
+    parameters: Arc<UVParameters>,
+    contributions: Vec<Box<dyn HelmholtzEnergy>>,
+    joback: Joback,
+}
+
+impl UVTheorySolid {
+    /// Returns an error if the parameters are not those of a single spherical component.
+    pub fn new(parameters: Arc<UVParameters>) -> UVTheoryResult<Self> {
+        if parameters.ncomponents != 1 || parameters.m[0] != 1.0 {
+            return Err(UVTheoryError::IncompatibleParameters(format!(
+                "the solid model is only available for pure components with m = 1, got {} component(s) with m = {}",
+                parameters.ncomponents, parameters.m
+            )));
+        }
+        Ok(Self::with_checked_parameters(parameters))
+    }
+
+    /// Solid model for parameters that are known to describe a single spherical component.
+    fn with_checked_parameters(parameters: Arc<UVParameters>) -> Self {
+        let contributions: Vec<Box<dyn HelmholtzEnergy>> = vec![Box::new(CellModel {
+            parameters: parameters.clone(),
+        })];
+        let joback = match &parameters.joback_records {
+            Some(joback_records) => Joback::new(joback_records.clone()),
+            None => Joback::default(parameters.ncomponents),
+        };
+        Self {
+            parameters,
+            contributions,
+            joback,
+        }
+    }
+}
+
+impl EquationOfState for UVTheorySolid {
+    fn components(&self) -> usize {
+        self.parameters.pure_records.len()
+    }
+
+    fn subset(&self, component_list: &[usize]) -> Self {
+        Self::with_checked_parameters(Arc::new(self.parameters.subset(component_list)))
+    }
+
+    fn compute_max_density(&self, _moles: &Array1<f64>) -> f64 {
+        // close packing of spheres with diameter sigma
+        SQRT_2 / self.parameters.sigma[0].powi(3)
+    }
+
+    fn residual(&self) -> &[Box<dyn HelmholtzEnergy>] {
+        &self.contributions
+    }
+
+    fn ideal_gas(&self) -> &dyn IdealGasContribution {
+        &self.joback
+    }
+}
+
+impl MolarWeight<SIUnit> for UVTheorySolid {
+    fn molar_weight(&self) -> SIArray1 {
+        self.parameters.molarweight.clone() * GRAM / MOL
+    }
+}
+
+/// Cell model of the FCC solid.
+///
+/// The residual Helmholtz energy per particle is
+/// `A/NkT = U_0/(2kT) - ln(rho v_f) + 1 - S_corr/k`
+/// with the static lattice energy `U_0`, the correlation entropy `S_corr` and the free volume
+/// `v_f = int exp(-(psi(r) - psi(0))/kT) dr` of the cell potential `psi`.
+/// The lattice shells within six nearest neighbor distances are considered
+/// explicitly, the remainder of the lattice energy is evaluated in
+/// mean-field approximation.
+#[derive(Debug, Clone)]
+struct CellModel {
+    parameters: Arc<UVParameters>,
+}
+
+/// Average of r^-n over a spherical shell with radius `r_shell` seen from a
+/// point at distance `x` from its center.
+fn shell_average(n: f64, x: f64, r_shell: f64) -> f64 {
+    if x == 0.0 {
+        return r_shell.powf(-n);
+    }
+    ((r_shell + x).powf(2.0 - n) - (r_shell - x).powf(2.0 - n)) / ((2.0 - n) * 2.0 * x * r_shell)
+}
+
+/// Lattice sum of r^-n for a particle displaced by `x` from its lattice site
+/// (in units of the nearest neighbor distance).
+fn lattice_sum(n: f64, x: f64) -> f64 {
+    FCC_SHELLS
+        .iter()
+        .map(|&(r2, count)| count * shell_average(n, x, r2.sqrt()))
+        .sum()
+}
+
+impl<D: DualNum<f64>> HelmholtzEnergyDual<D> for CellModel {
+    fn helmholtz_energy(&self, state: &StateHD<D>) -> D {
+        let p = &self.parameters;
+        let effective = p.effective_parameters(state.temperature);
+        let (rep, att) = (p.rep[0], p.att[0]);
+        let c = mie_prefactor(rep, att);
+        let beta = effective.epsilon_k(0) / state.temperature;
+
+        // nearest neighbor distance in units of sigma
+        let rho = state.partial_density.sum() * effective.sigma(0).powi(3);
+        let a = (rho.recip() * SQRT_2).cbrt();
+        let (a_rep, a_att) = (a.powf(-rep) * c, a.powf(-att) * c);
+        let psi = |x: f64| a_rep * lattice_sum(rep, x) - a_att * lattice_sum(att, x);
+
+        let psi0 = psi(0.0);
+        let integrand = |x: f64| (-(psi(x) - psi0) * beta).exp() * x * x;
+        let free_volume = integrate(&integrand, 0.0, CELL_RADIUS, 1e-12, 0) * a.powi(3) * 4.0 * PI;
+        let tail = mean_field_constant(
+            D::from(rep),
+            D::from(att),
+            a * (MAX_SHELL as f64 + 0.5).sqrt(),
+        ) * rho
+            * 4.0
+            * PI;
+        let lattice_energy = (psi0 - tail) * 0.5;
+        (lattice_energy * beta - (rho * free_volume).ln() + 1.0 - CORRELATION_ENTROPY)
+            * state.moles.sum()
+    }
+}
+
+impl fmt::Display for CellModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cell Model")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parameters::utils::{test_parameters, test_parameters_mixture, water_parameters};
+    use approx::assert_relative_eq;
+    use feos_core::{Contributions, State};
+    use ndarray::arr1;
+    use std::rc::Rc;
+
+    #[test]
+    fn fcc_lattice() {
+        let coordination: Vec<f64> = FCC_SHELLS.iter().take(4).map(|s| s.1).collect();
+        assert_eq!(coordination, vec![12.0, 6.0, 24.0, 12.0]);
+
+        // lattice sums of the Lennard-Jones crystal
+        assert_relative_eq!(lattice_sum(12.0, 0.0), 12.13188, max_relative = 1e-6);
+        assert_relative_eq!(lattice_sum(6.0, 0.0), 14.45392, max_relative = 5e-3);
+
+        // the average over a shell is continuous at the lattice site
+        assert_relative_eq!(
+            lattice_sum(12.0, 1e-6),
+            lattice_sum(12.0, 0.0),
+            max_relative = 1e-9
+        );
+    }
+
+    #[test]
+    fn lennard_jones_solid() {
+        // pressures of the Lennard-Jones solid at melting conditions
+        // (Hansen and Verlet, Phys. Rev. 184, 151 (1969))
+        let eos =
+            Rc::new(UVTheorySolid::new(Arc::new(test_parameters(12.0, 6.0, 1.0, 1.0))).unwrap());
+        for (t, rho, p) in [(1.15, 1.024, 5.68), (2.74, 1.179, 32.2)] {
+            let state = State::new_pure(&eos, t * KELVIN, rho / (ANGSTROM.powi(3) * NAV)).unwrap();
+            assert_relative_eq!(
+                state
+                    .pressure(Contributions::Total)
+                    .to_reduced(KB * KELVIN / ANGSTROM.powi(3))
+                    .unwrap(),
+                p,
+                max_relative = 5e-2
+            );
+        }
+    }
+
+    #[test]
+    fn incompatible_parameters() {
+        let mixture = test_parameters_mixture(
+            arr1(&[12.0, 12.0]),
+            arr1(&[6.0, 6.0]),
+            arr1(&[1.0, 1.0]),
+            arr1(&[1.0, 1.0]),
+        );
+        for parameters in [mixture, water_parameters()] {
+            assert!(matches!(
+                UVTheorySolid::new(Arc::new(parameters)),
+                Err(UVTheoryError::IncompatibleParameters(_))
+            ));
+        }
+    }
+}
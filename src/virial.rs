@@ -0,0 +1,401 @@
+//! Exact virial coefficients of Mie potentials.
+//!
+//! The second and third virial coefficients are obtained by adaptive
+//! Gauss-Kronrod quadrature of the Mayer function
+//! `f(r) = exp(-u(r) / kT) - 1`. The temperature can be a dual number so
+//! that temperature derivatives of the virial coefficients are exact.
+use crate::parameters::{mean_field_constant, mie_prefactor, UVParameters};
+use ndarray::Array1;
+use num_dual::DualNum;
+use std::f64::consts::PI;
+
+/// Upper integration limit (in units of sigma) for full-range potentials.
+const R_MAX: f64 = 10.0;
+/// Reduced energies above which the Mayer function is -1 to machine precision.
+const BETA_U_MAX: f64 = 100.0;
+/// Maximum number of bisections of an integration interval.
+const MAX_DEPTH: usize = 30;
+/// Width of the intervals on which the antiderivative used for the third
+/// virial coefficient is tabulated.
+const ANTIDERIVATIVE_SPACING: f64 = 0.05;
+
+/// Nodes of the 15-point Kronrod rule (the nodes with odd index are the
+/// nodes of the 7-point Gauss rule).
+const XGK: [f64; 8] = [
+    0.991455371120812639206854697526329,
+    0.949107912342758524526189684047851,
+    0.864864423359769072789712788640926,
+    0.741531185599394439863864773280788,
+    0.586087235467691130294144845693013,
+    0.405845151377397166906606412076961,
+    0.207784955007898467600689403773245,
+    0.000000000000000000000000000000000,
+];
+/// Weights of the 15-point Kronrod rule.
+const WGK: [f64; 8] = [
+    0.022935322010529224963732008058970,
+    0.063092092629978553290700663189204,
+    0.104790010322250183839876322541518,
+    0.140653259715525918745189590510238,
+    0.169004726639267902826583426598550,
+    0.190350578064785409913256402421014,
+    0.204432940075298892414161999234649,
+    0.209482141084727828012999174891714,
+];
+/// Weights of the 7-point Gauss rule.
+const WG: [f64; 4] = [
+    0.129484966168869693270611432679082,
+    0.279705391489276667901467771423780,
+    0.381830050505118944950369775488975,
+    0.417959183673469387755102040816327,
+];
+
+/// Mie potential in reduced units.
+///
+/// Distances are reduced by the segment diameter `sigma` and energies by the
+/// energy parameter `epsilon`. The potential can be truncated (and shifted)
+/// at a cutoff radius, consistent with `UVTheoryOptions`.
+#[derive(Clone, Copy, Debug)]
+pub struct MiePotential {
+    pub rep: f64,
+    pub att: f64,
+    pub cutoff_radius: Option<f64>,
+    pub shifted: bool,
+}
+
+impl MiePotential {
+    /// Full-range Mie potential with repulsive exponent `rep` and attractive exponent `att`.
+    pub fn new(rep: f64, att: f64) -> Self {
+        Self {
+            rep,
+            att,
+            cutoff_radius: None,
+            shifted: false,
+        }
+    }
+
+    /// Potential truncated (and optionally shifted) at `cutoff_radius`.
+    pub fn truncated(self, cutoff_radius: f64, shifted: bool) -> Self {
+        Self {
+            cutoff_radius: Some(cutoff_radius),
+            shifted,
+            ..self
+        }
+    }
+
+    /// Reduced potential energy u(r) / epsilon.
+    pub fn potential(&self, r: f64) -> f64 {
+        let c = mie_prefactor(self.rep, self.att);
+        let u = |r: f64| c * (r.powf(-self.rep) - r.powf(-self.att));
+        match self.cutoff_radius {
+            None => u(r),
+            Some(rc) if r < rc => u(r) - if self.shifted { u(rc) } else { 0.0 },
+            Some(_) => 0.0,
+        }
+    }
+
+    /// Mayer function f(r) = exp(-u(r) / (epsilon T*)) - 1.
+    pub fn mayer_f<D: DualNum<f64>>(&self, r: f64, reduced_temperature: D) -> D {
+        let beta = reduced_temperature.recip();
+        let u = self.potential(r);
+        if beta.re() * u > BETA_U_MAX {
+            -D::one()
+        } else {
+            (-beta * u).exp_m1()
+        }
+    }
+
+    /// Reduced second virial coefficient B2 / sigma^3 at the reduced temperature
+    /// T* = kT / epsilon.
+    ///
+    /// For the full-range potential, the contribution beyond 10 sigma is
+    /// evaluated in first order of the inverse temperature.
+    pub fn second_virial_coefficient<D: DualNum<f64>>(&self, reduced_temperature: D) -> D {
+        let r_max = self.cutoff_radius.unwrap_or(R_MAX);
+        let integrand = |r: f64| self.mayer_f(r, reduced_temperature) * r * r;
+        let b2 =
+            -integrate_piecewise(&integrand, 0.0, r_max, &self.breakpoints(), 1e-12) * 2.0 * PI;
+        match self.cutoff_radius {
+            Some(_) => b2,
+            None => {
+                b2 - reduced_temperature.recip()
+                    * mean_field_constant(self.rep, self.att, r_max)
+                    * 2.0
+                    * PI
+            }
+        }
+    }
+
+    /// Reduced third virial coefficient B3 / sigma^6 at the reduced temperature
+    /// T* = kT / epsilon.
+    ///
+    /// The integration is restricted to pair distances below 10 sigma for the
+    /// full-range potential.
+    pub fn third_virial_coefficient<D: DualNum<f64>>(&self, reduced_temperature: D) -> D {
+        let r_max = self.cutoff_radius.unwrap_or(R_MAX);
+        let breakpoints = self.breakpoints();
+        let f = |r: f64| self.mayer_f(r, reduced_temperature);
+        let g = |t: f64| f(t) * t;
+        let antiderivative = Antiderivative::new(&g, 2.0 * r_max, &breakpoints);
+
+        // B3 = -8 pi^2 / 3 int int r s f(r) f(s) int_{|r-s|}^{r+s} t f(t) dt ds dr,
+        // the integrand is symmetric in r and s.
+        let outer = |r: f64| {
+            let f_r = f(r);
+            if f_r.re() == 0.0 {
+                return D::zero();
+            }
+            let inner =
+                |s: f64| f(s) * s * (antiderivative.value(r + s) - antiderivative.value(r - s));
+            integrate_piecewise(&inner, 0.0, r, &breakpoints, 1e-11) * f_r * r
+        };
+        -integrate_piecewise(&outer, 0.0, r_max, &breakpoints, 1e-9) * 16.0 / 3.0 * PI * PI
+    }
+
+    /// Points at which the integrands are not smooth or change rapidly.
+    fn breakpoints(&self) -> Vec<f64> {
+        let r_min = (self.rep / self.att).powf(1.0 / (self.rep - self.att));
+        let mut breakpoints = vec![1.0, r_min];
+        breakpoints.extend(self.cutoff_radius);
+        breakpoints
+    }
+}
+
+impl UVParameters {
+    /// Mie potential between segments of components `i` and `j`.
+    pub fn pair_potential(&self, i: usize, j: usize) -> MiePotential {
+        MiePotential::new(self.rep_ij[[i, j]], self.att_ij[[i, j]])
+    }
+
+    /// Exact second virial coefficient between segments of components `i` and `j`
+    /// in units of Angstrom^3 for the temperature in Kelvin.
+    ///
+    /// The classical (full-range) potential is used, i.e. Feynman-Hibbs
+    /// corrections are not considered.
+    pub fn exact_second_virial_coefficient_ij<D: DualNum<f64>>(
+        &self,
+        i: usize,
+        j: usize,
+        temperature: D,
+    ) -> D {
+        let eps_k = self.eps_k_ij_at(i, j, temperature);
+        self.pair_potential(i, j)
+            .second_virial_coefficient(temperature / eps_k)
+            * self.sigma_ij[[i, j]].powi(3)
+    }
+
+    /// Exact second virial coefficient of the segment fluid in units of Angstrom^3
+    /// for the temperature in Kelvin and the mole fractions of the segments.
+    ///
+    /// For molecules consisting of a single segment, this is the second virial
+    /// coefficient of the mixture.
+    pub fn exact_second_virial_coefficient<D: DualNum<f64>>(
+        &self,
+        temperature: D,
+        molefracs: &Array1<D>,
+    ) -> D {
+        let mut b2 = D::zero();
+        for i in 0..self.ncomponents {
+            for j in 0..self.ncomponents {
+                b2 += molefracs[i]
+                    * molefracs[j]
+                    * self.exact_second_virial_coefficient_ij(i, j, temperature);
+            }
+        }
+        b2
+    }
+
+    /// Exact third virial coefficient of the segments of component `i` in units of
+    /// Angstrom^6 for the temperature in Kelvin.
+    pub fn exact_third_virial_coefficient<D: DualNum<f64>>(&self, i: usize, temperature: D) -> D {
+        let eps_k = self.eps_k_ij_at(i, i, temperature);
+        self.pair_potential(i, i)
+            .third_virial_coefficient(temperature / eps_k)
+            * self.sigma_ij[[i, i]].powi(6)
+    }
+}
+
+/// Antiderivative F(x) = int_0^x g(t) dt, tabulated on an equidistant grid.
+struct Antiderivative<'a, D, G> {
+    integrand: &'a G,
+    values: Vec<D>,
+    breakpoints: &'a [f64],
+}
+
+impl<'a, D: DualNum<f64>, G: Fn(f64) -> D> Antiderivative<'a, D, G> {
+    fn new(integrand: &'a G, x_max: f64, breakpoints: &'a [f64]) -> Self {
+        let n = (x_max / ANTIDERIVATIVE_SPACING).ceil() as usize;
+        let mut values = Vec::with_capacity(n + 1);
+        values.push(D::zero());
+        for k in 0..n {
+            let a = k as f64 * ANTIDERIVATIVE_SPACING;
+            let b = a + ANTIDERIVATIVE_SPACING;
+            values.push(values[k] + integrate_piecewise(integrand, a, b, breakpoints, 1e-14));
+        }
+        Self {
+            integrand,
+            values,
+            breakpoints,
+        }
+    }
+
+    fn value(&self, x: f64) -> D {
+        let k = ((x / ANTIDERIVATIVE_SPACING) as usize).min(self.values.len() - 1);
+        let a = k as f64 * ANTIDERIVATIVE_SPACING;
+        if x <= a {
+            return self.values[k];
+        }
+        self.values[k] + integrate_piecewise(self.integrand, a, x, self.breakpoints, 1e-13)
+    }
+}
+
+/// Integral of `f` from `a` to `b`, split at the breakpoints within the interval.
+fn integrate_piecewise<D: DualNum<f64>, F: Fn(f64) -> D>(
+    f: &F,
+    a: f64,
+    b: f64,
+    breakpoints: &[f64],
+    tol: f64,
+) -> D {
+    let mut points = vec![a];
+    points.extend(breakpoints.iter().filter(|&&x| a < x && x < b));
+    points.sort_by(|x, y| x.partial_cmp(y).unwrap());
+    points.push(b);
+    points
+        .windows(2)
+        .map(|w| integrate(f, w[0], w[1], tol, 0))
+        .fold(D::zero(), |acc, i| acc + i)
+}
+
+/// Adaptive Gauss-Kronrod quadrature with an absolute tolerance `tol` on the
+/// real part of the integral.
+pub(crate) fn integrate<D: DualNum<f64>, F: Fn(f64) -> D>(
+    f: &F,
+    a: f64,
+    b: f64,
+    tol: f64,
+    depth: usize,
+) -> D {
+    let (integral, error) = gauss_kronrod(f, a, b);
+    if error <= tol || depth >= MAX_DEPTH {
+        integral
+    } else {
+        let m = 0.5 * (a + b);
+        integrate(f, a, m, 0.5 * tol, depth + 1) + integrate(f, m, b, 0.5 * tol, depth + 1)
+    }
+}
+
+/// 15-point Kronrod rule and the error estimate from the embedded 7-point Gauss rule.
+fn gauss_kronrod<D: DualNum<f64>, F: Fn(f64) -> D>(f: &F, a: f64, b: f64) -> (D, f64) {
+    let center = 0.5 * (a + b);
+    let half_length = 0.5 * (b - a);
+    let f_center = f(center);
+    let mut kronrod = f_center * WGK[7];
+    let mut gauss = f_center * WG[3];
+    for k in 0..7 {
+        let dx = half_length * XGK[k];
+        let f_sum = f(center - dx) + f(center + dx);
+        kronrod += f_sum * WGK[k];
+        if k % 2 == 1 {
+            gauss += f_sum * WG[k / 2];
+        }
+    }
+    let error = ((kronrod.re() - gauss.re()) * half_length).abs();
+    (kronrod * half_length, error)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parameters::utils::test_parameters_mixture;
+    use approx::assert_relative_eq;
+    use ndarray::arr1;
+    use num_dual::Dual64;
+
+    #[test]
+    fn hard_sphere_limit() {
+        // steep potentials at high temperatures behave like hard spheres
+        // (with a smaller effective diameter)
+        let mie = MiePotential::new(400.0, 6.0);
+        let t = 1e3;
+        let b2 = mie.second_virial_coefficient(t);
+        let b3 = mie.third_virial_coefficient(t);
+        assert_relative_eq!(b3 / b2.powi(2), 5.0 / 8.0, max_relative = 1e-2);
+    }
+
+    #[test]
+    fn lennard_jones() {
+        // B2 / b0 and B3 / b0^2 with b0 = 2 pi / 3 sigma^3
+        // (Hirschfelder, Curtiss and Bird, Molecular Theory of Gases and Liquids
+        // (1954), Tables I-B and I-C)
+        let reference = [(1.0, -2.53809, 0.4297), (2.0, -0.62763, 0.4371)];
+        let lj = MiePotential::new(12.0, 6.0);
+        let b0 = 2.0 * PI / 3.0;
+        for (t, b2, b3) in reference {
+            assert_relative_eq!(
+                lj.second_virial_coefficient(t) / b0,
+                b2,
+                max_relative = 1e-4
+            );
+            assert_relative_eq!(
+                lj.third_virial_coefficient(t) / b0.powi(2),
+                b3,
+                max_relative = 1e-3
+            );
+        }
+    }
+
+    #[test]
+    fn temperature_derivatives() {
+        for mie in [
+            MiePotential::new(12.0, 6.0),
+            MiePotential::new(20.0, 6.0).truncated(2.5, true),
+        ] {
+            let t = 1.3;
+            let h = 1e-6;
+            let b2 = mie.second_virial_coefficient(Dual64::from(t).derive());
+            let b2_h = mie.second_virial_coefficient(t + h);
+            let b2_mh = mie.second_virial_coefficient(t - h);
+            assert_relative_eq!(b2.re, mie.second_virial_coefficient(t), epsilon = 1e-14);
+            assert_relative_eq!(b2.eps[0], (b2_h - b2_mh) / (2.0 * h), max_relative = 1e-6);
+            let b3 = mie.third_virial_coefficient(Dual64::from(t).derive());
+            let b3_h = mie.third_virial_coefficient(t + h);
+            let b3_mh = mie.third_virial_coefficient(t - h);
+            assert_relative_eq!(b3.eps[0], (b3_h - b3_mh) / (2.0 * h), max_relative = 1e-4);
+        }
+    }
+
+    #[test]
+    fn mixture() {
+        let p = test_parameters_mixture(
+            arr1(&[12.0, 14.0]),
+            arr1(&[6.0, 6.0]),
+            arr1(&[1.0, 1.5]),
+            arr1(&[1.0, 2.0]),
+        );
+        let t = 2.5;
+        let b2 = [
+            p.exact_second_virial_coefficient_ij(0, 0, t),
+            p.exact_second_virial_coefficient_ij(0, 1, t),
+            p.exact_second_virial_coefficient_ij(1, 1, t),
+        ];
+        assert_relative_eq!(
+            b2[0],
+            MiePotential::new(12.0, 6.0).second_virial_coefficient(t / p.eps_k_ij[[0, 0]]),
+            max_relative = 1e-14
+        );
+        assert_relative_eq!(
+            b2[1],
+            p.pair_potential(0, 1)
+                .second_virial_coefficient(t / p.eps_k_ij[[0, 1]])
+                * p.sigma_ij[[0, 1]].powi(3),
+            max_relative = 1e-14
+        );
+        let x = arr1(&[0.3, 0.7]);
+        assert_relative_eq!(
+            p.exact_second_virial_coefficient(t, &x),
+            x[0] * x[0] * b2[0] + 2.0 * x[0] * x[1] * b2[1] + x[1] * x[1] * b2[2],
+            max_relative = 1e-14
+        );
+    }
+}